@@ -0,0 +1,100 @@
+//! `gline-server`: local HTTP inference server, sharing one model between several workers
+//!
+//! Example:
+//!
+//! ```console
+//! $ gline-server --model-dir models/gliner-multitask-large-v0.5 --port 8080
+//! $ curl -s localhost:8080/ner -d '{"texts": ["Bill Gates founded Microsoft"], "labels": ["person", "company"]}'
+//! ```
+//!
+//! See `Service` for the endpoints, and run `gline-server --help` for the complete list of options.
+
+mod service;
+
+use std::path::PathBuf;
+use clap::{Parser, ValueEnum};
+use orp::params::RuntimeParameters;
+use ort::session::Session;
+use gliner::model::GLiNER;
+use gliner::model::auto::AutoGLiNER;
+use gliner::model::params::Parameters;
+use gliner::model::pipeline::{span::SpanMode, token::TokenMode};
+use gliner::util::result::Result;
+use service::{ServedModel, Service};
+
+
+#[derive(Parser)]
+#[command(name = "gline-server", version, about = "HTTP inference server for GLiNER models")]
+struct Args {
+    /// Path to a model directory, with `gliner_config.json`, `tokenizer.json` and `onnx/model.onnx`
+    #[arg(long, conflicts_with_all = ["model", "tokenizer"], required_unless_present_all = ["model", "tokenizer"])]
+    model_dir: Option<PathBuf>,
+    /// Path to the ONNX model
+    #[arg(long, requires = "tokenizer")]
+    model: Option<PathBuf>,
+    /// Path to the tokenizer (`tokenizer.json`)
+    #[arg(long, requires = "model")]
+    tokenizer: Option<PathBuf>,
+    /// Model mode (detected from the model by default, relation extraction requires the token mode)
+    #[arg(long, value_enum, default_value_t = Mode::Auto)]
+    mode: Mode,
+    /// Number of threads used by the ONNX runtime
+    #[arg(long, default_value_t = 4)]
+    threads: usize,
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1")]
+    host: String,
+    /// Port to listen on
+    #[arg(long, short, default_value_t = 8080)]
+    port: u16,
+    /// Number of requests processed concurrently
+    #[arg(long, default_value_t = 2)]
+    workers: usize,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Mode {
+    Auto,
+    Token,
+    Span,
+}
+
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let service = Service::new(&name(&args), load(&args)?);
+    let server = tiny_http::Server::http((args.host.as_str(), args.port))?;
+    eprintln!("Listening on http://{}:{}", args.host, args.port);
+    std::thread::scope(|scope| {
+        for _ in 0..args.workers.max(1) {
+            scope.spawn(|| service.serve(&server));
+        }
+    });
+    Ok(())
+}
+
+
+/// Loads the model specified by the arguments
+fn load(args: &Args) -> Result<ServedModel> {
+    let (params, runtime_params) = (Parameters::default(), RuntimeParameters::default().with_threads(args.threads));
+    Ok(match (&args.model_dir, &args.tokenizer, &args.model) {
+        (Some(dir), _, _) => match args.mode {
+            Mode::Auto => AutoGLiNER::from_dir(params, runtime_params, dir)?.into(),
+            Mode::Token => ServedModel::Token(GLiNER::<TokenMode, Session>::from_dir(params, runtime_params, dir)?),
+            Mode::Span => ServedModel::Span(GLiNER::<SpanMode, Session>::from_dir(params, runtime_params, dir)?),
+        },
+        (None, Some(tokenizer), Some(model)) => match args.mode {
+            Mode::Auto => AutoGLiNER::new(params, runtime_params, tokenizer, model)?.into(),
+            Mode::Token => ServedModel::Token(GLiNER::<TokenMode, Session>::new(params, runtime_params, tokenizer, model)?),
+            Mode::Span => ServedModel::Span(GLiNER::<SpanMode, Session>::new(params, runtime_params, tokenizer, model)?),
+        },
+        _ => return Err("either a model directory or both model and tokenizer paths must be provided".into()),
+    })
+}
+
+
+/// Name of the model, as reported by `GET /model`
+fn name(args: &Args) -> String {
+    let path = args.model_dir.as_ref().or(args.model.as_ref());
+    path.and_then(|p| p.file_name()).map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+}
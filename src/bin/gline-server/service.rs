@@ -0,0 +1,340 @@
+//! HTTP endpoints
+
+use std::io::Read;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use tiny_http::{Header, Method, Request, Response, Server};
+use gliner::model::GLiNER;
+use gliner::model::auto::AutoGLiNER;
+use gliner::model::backend::Backend;
+use gliner::model::input::{text::TextInput, relation::schema::{RelationSchema, RelationSpec}};
+use gliner::model::params::Parameters;
+use gliner::model::pipeline::{relation::RelationPipeline, span::SpanMode, token::TokenMode};
+use gliner::util::error::GlinerError;
+use ort::session::Session;
+
+
+/// Maximum size of a request body, in bytes
+const MAX_BODY_SIZE: usize = 16 * 1024 * 1024;
+
+
+/// Model served, relation extraction being only available in token mode
+pub enum ServedModel<B = Session> {
+    Token(GLiNER<TokenMode, B>),
+    Span(GLiNER<SpanMode, B>),
+}
+
+impl From<AutoGLiNER> for ServedModel {
+    fn from(model: AutoGLiNER) -> Self {
+        match model {
+            AutoGLiNER::Token(model) => Self::Token(model),
+            AutoGLiNER::Span(model) => Self::Span(model),
+        }
+    }
+}
+
+
+/// Body of `POST /ner`
+#[derive(Deserialize)]
+struct NerRequest {
+    texts: Vec<String>,
+    labels: Vec<String>,
+    #[serde(default)]
+    parameters: Map<String, Value>,
+}
+
+/// Body of `POST /relations`
+#[derive(Deserialize)]
+struct RelationRequest {
+    texts: Vec<String>,
+    labels: Vec<String>,
+    relations: Vec<RelationDefinition>,
+    #[serde(default)]
+    parameters: Map<String, Value>,
+}
+
+/// Relation label, with optional restrictions on the labels of its subjects and objects
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RelationDefinition {
+    Label(String),
+    Spec { relation: String, subjects: Option<Vec<String>>, objects: Option<Vec<String>> },
+}
+
+
+/// Failure of a request: HTTP status and message
+struct Failure {
+    status: u16,
+    message: String,
+}
+
+impl Failure {
+    fn new(status: u16, message: impl ToString) -> Self {
+        Self { status, message: message.to_string() }
+    }
+
+    fn bad_request(message: impl ToString) -> Self {
+        Self::new(400, message)
+    }
+}
+
+impl From<GlinerError> for Failure {
+    fn from(error: GlinerError) -> Self {
+        match error {
+            GlinerError::InvalidInput { .. } | GlinerError::InvalidConfiguration(_) | GlinerError::InvalidLabel { .. } => Self::bad_request(error),
+            _ => Self::new(500, error),
+        }
+    }
+}
+
+impl From<Box<dyn std::error::Error + Send + Sync>> for Failure {
+    fn from(error: Box<dyn std::error::Error + Send + Sync>) -> Self {
+        GlinerError::from(error).into()
+    }
+}
+
+
+/// Answers the requests with a shared model:
+///
+/// * `GET /health`: `{ "status": "ok" }`
+/// * `GET /model`: name and mode of the model, whether it extracts relations, and default parameters
+/// * `POST /ner`: `{ "texts": [...], "labels": [...], "parameters": {...} }`, answered with a `SpanOutput`
+/// * `POST /relations`: same as `/ner` with `"relations": ["founded", { "relation": "born in", "subjects": ["person"], "objects": ["city"] }, ...]`,
+///   answered with a `RelationOutput`
+///
+/// Parameters are optional, and override the default ones of the model (see `Parameters`). Failures are answered
+/// with `{ "error": "..." }`.
+pub struct Service<B = Session> {
+    name: String,
+    model: ServedModel<B>,
+}
+
+impl<B: Backend> Service<B> {
+    pub fn new(name: &str, model: ServedModel<B>) -> Self {
+        Self { name: name.to_string(), model }
+    }
+
+    /// Answers the requests received by the given server until it is unblocked
+    pub fn serve(&self, server: &Server) {
+        for mut request in server.incoming_requests() {
+            let (status, body) = match self.handle(&mut request) {
+                Ok(body) => (200, body),
+                Err(failure) => (failure.status, json!({ "error": failure.message })),
+            };
+            let header = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).expect("valid header");
+            let response = Response::from_string(body.to_string()).with_status_code(status).with_header(header);
+            if let Err(e) = request.respond(response) {
+                eprintln!("failed to respond: {e}");
+            }
+        }
+    }
+
+    fn handle(&self, request: &mut Request) -> Result<Value, Failure> {
+        let path = request.url().split('?').next().unwrap_or_default().to_string();
+        match (request.method(), path.as_str()) {
+            (Method::Get, "/health") => Ok(json!({ "status": "ok" })),
+            (Method::Get, "/model") => self.metadata(),
+            (Method::Post, "/ner") => self.ner(body(request)?),
+            (Method::Post, "/relations") => self.relations(body(request)?),
+            (_, "/health" | "/model" | "/ner" | "/relations") => Err(Failure::new(405, "method not allowed")),
+            _ => Err(Failure::new(404, format!("not found: {path}"))),
+        }
+    }
+
+    fn metadata(&self) -> Result<Value, Failure> {
+        let (mode, params) = match &self.model {
+            ServedModel::Token(model) => ("token", model.params()),
+            ServedModel::Span(model) => ("span", model.params()),
+        };
+        Ok(json!({
+            "name": self.name,
+            "mode": mode,
+            "relations": mode == "token",
+            "parameters": serde_json::to_value(params).map_err(|e| Failure::new(500, e))?,
+        }))
+    }
+
+    fn ner(&self, request: NerRequest) -> Result<Value, Failure> {
+        let input = TextInput::new(request.texts, request.labels)?;
+        let output = match &self.model {
+            ServedModel::Token(model) => model.inference_with(model.pipeline(), &parameters(model.params(), request.parameters)?, input)?,
+            ServedModel::Span(model) => model.inference_with(model.pipeline(), &parameters(model.params(), request.parameters)?, input)?,
+        };
+        serde_json::to_value(output).map_err(|e| Failure::new(500, e))
+    }
+
+    fn relations(&self, request: RelationRequest) -> Result<Value, Failure> {
+        let ServedModel::Token(model) = &self.model else {
+            return Err(Failure::bad_request("relation extraction requires a token-mode model"));
+        };
+        let schema = schema(&request.relations)?;
+        let params = parameters(model.params(), request.parameters)?;
+        let entities = model.inference_with(model.pipeline(), &params, TextInput::new(request.texts, request.labels)?)?;
+        let pipeline = RelationPipeline::new(model.pipeline().by_ref(), &schema);
+        let output = model.inference_with(&pipeline, &params, entities)?;
+        serde_json::to_value(output).map_err(|e| Failure::new(500, e))
+    }
+}
+
+
+/// Parses the JSON body of the given request, rejecting bodies larger than `MAX_BODY_SIZE`
+fn body<T: for<'de> Deserialize<'de>>(request: &mut Request) -> Result<T, Failure> {
+    let too_large = || Failure::new(413, format!("request body larger than {MAX_BODY_SIZE} bytes"));
+    if request.body_length().is_some_and(|length| length > MAX_BODY_SIZE) {
+        return Err(too_large());
+    }
+    // the length is not known beforehand with chunked transfers
+    let mut body = String::new();
+    request.as_reader().take(MAX_BODY_SIZE as u64 + 1).read_to_string(&mut body).map_err(Failure::bad_request)?;
+    if body.len() > MAX_BODY_SIZE {
+        return Err(too_large());
+    }
+    serde_json::from_str(&body).map_err(|e| Failure::bad_request(format!("invalid request: {e}")))
+}
+
+
+/// Default parameters with the given overrides
+fn parameters(defaults: &Parameters, overrides: Map<String, Value>) -> Result<Parameters, Failure> {
+    let mut params = match serde_json::to_value(defaults) {
+        Ok(Value::Object(params)) => params,
+        _ => return Err(Failure::new(500, "cannot serialize the parameters")),
+    };
+    for (name, value) in overrides {
+        if !params.contains_key(&name) {
+            return Err(Failure::bad_request(format!("unknown parameter: {name}")));
+        }
+        params.insert(name, value);
+    }
+    serde_json::from_value(Value::Object(params)).map_err(|e| Failure::bad_request(format!("invalid parameters: {e}")))
+}
+
+
+fn labels(labels: &[String]) -> Vec<&str> {
+    labels.iter().map(String::as_str).collect()
+}
+
+
+fn schema(relations: &[RelationDefinition]) -> Result<RelationSchema, Failure> {
+    if relations.is_empty() {
+        return Err(Failure::bad_request("empty relations"));
+    }
+    let mut schema = RelationSchema::new();
+    for relation in relations {
+        match relation {
+            RelationDefinition::Label(relation) => schema.push(relation),
+            RelationDefinition::Spec { relation, subjects, objects } => {
+                let mut spec = RelationSpec::default();
+                if let Some(subjects) = subjects {
+                    spec = spec.with_allowed_subjects(&labels(subjects));
+                }
+                if let Some(objects) = objects {
+                    spec = spec.with_allowed_objects(&labels(objects));
+                }
+                schema.push_with_spec(relation, spec);
+            }
+        }
+    }
+    Ok(schema)
+}
+
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    // Silent some clippy warnings for unit tests
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use std::io::Write;
+    use std::net::{Shutdown, TcpStream};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use gliner::model::backend::{Tensors, mock::{MockBackend, ScriptedSpan, token_logits}};
+    use gliner::model::pipeline::token::TokenPipeline;
+    use gliner::text::{splitter::RegexSplitter, tokenizer::HFTokenizer};
+    use gliner::util::result::Result;
+
+    /// Service of a token-mode model (with the tiny tokenizer of the test data) running on the given mock
+    fn service<F: Fn(&Tensors) -> Result<Tensors>>(run: F) -> Result<Service<MockBackend<F>>> {
+        let pipeline = TokenPipeline::with_components(RegexSplitter::default(), HFTokenizer::from_file("data/tokenizer-tiny.json")?);
+        Ok(Service::new("tiny", ServedModel::Token(GLiNER::with_backend(Parameters::default(), pipeline, MockBackend::new(run)))))
+    }
+
+    /// Sends a request to the server and returns the status and JSON body of the response
+    fn send(address: &str, method: &str, path: &str, body: &str) -> Result<(u16, Value)> {
+        send_with_length(address, method, path, body, body.len())
+    }
+
+    /// Sends a request with the given `Content-Length` (the body being possibly shorter)
+    fn send_with_length(address: &str, method: &str, path: &str, body: &str, length: usize) -> Result<(u16, Value)> {
+        let mut stream = TcpStream::connect(address)?;
+        write!(stream, "{method} {path} HTTP/1.1\r\nHost: {address}\r\nContent-Type: application/json\r\nContent-Length: {length}\r\nConnection: close\r\n\r\n{body}")?;
+        stream.shutdown(Shutdown::Write)?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response)?;
+        let status = response.split_whitespace().nth(1).unwrap().parse()?;
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+        Ok((status, serde_json::from_str(body)?))
+    }
+
+    /// Unblocks the server when dropped, so that a failing test does not wait for it forever
+    struct Unblock<'a>(&'a Server);
+
+    impl Drop for Unblock<'_> {
+        fn drop(&mut self) {
+            self.0.unblock();
+        }
+    }
+
+    #[test]
+    fn test() -> Result<()> {
+        // "Bill Gates founded Microsoft": the first inference finds the entities, the second one the relation
+        // (the text being prefixed by the 8 words of the relation prompt)
+        let calls = AtomicUsize::new(0);
+        let service = service(move |inputs: &Tensors| match calls.fetch_add(1, Ordering::SeqCst) % 2 {
+            0 => token_logits(inputs, 2, &[ScriptedSpan::new(0, 0, 1, 0, 4.0), ScriptedSpan::new(0, 3, 3, 1, 4.0)]),
+            _ => token_logits(inputs, 1, &[ScriptedSpan::new(0, 11, 11, 0, 4.0)]),
+        })?;
+        let server = Server::http("127.0.0.1:0")?;
+        let address = server.server_addr().to_ip().unwrap().to_string();
+        std::thread::scope(|scope| -> Result<()> {
+            scope.spawn(|| service.serve(&server));
+            let _unblock = Unblock(&server);
+            // Processing
+            let health = send(&address, "GET", "/health", "")?;
+            let metadata = send(&address, "GET", "/model", "")?;
+            let relations = send(&address, "POST", "/relations", r#"{"texts": ["Bill Gates founded Microsoft"], "labels": ["person", "company"], "relations": [{"relation": "founded", "subjects": ["person"], "objects": ["company"]}]}"#)?;
+            let unknown = send(&address, "POST", "/ner", r#"{"texts": ["Bill Gates"], "labels": ["person"], "parameters": {"treshold": 0.2}}"#)?;
+            let invalid = send(&address, "POST", "/ner", r#"{"texts": [], "labels": ["person"]}"#)?;
+            let not_found = send(&address, "GET", "/nothing", "")?;
+            let too_large = send_with_length(&address, "POST", "/ner", "{}", MAX_BODY_SIZE + 1)?;
+            // Assertions
+            assert_eq!(health, (200, json!({ "status": "ok" })));
+            assert_eq!(metadata.1["mode"], "token");
+            assert_eq!(metadata.1["parameters"]["threshold"], 0.5);
+            assert_eq!(relations.0, 200);
+            assert_eq!(relations.1["relations"][0][0]["subject"], "Bill Gates");
+            assert_eq!(relations.1["relations"][0][0]["object"], "Microsoft");
+            assert_eq!(unknown, (400, json!({ "error": "unknown parameter: treshold" })));
+            assert_eq!(invalid.0, 400);
+            assert_eq!(not_found.0, 404);
+            assert_eq!(too_large.0, 413);
+            Ok(())
+        })?;
+        // Everything rules
+        Ok(())
+    }
+
+    #[test]
+    fn test_ner() -> Result<()> {
+        let service = service(|inputs: &Tensors| token_logits(inputs, 1, &[ScriptedSpan::new(0, 0, 1, 0, 1.0)]))?;
+        let request = |parameters: Value| serde_json::from_value(json!({ "texts": ["Bill Gates"], "labels": ["person"], "parameters": parameters }));
+        // Processing: the probability of the entity is about 0.73
+        let default = service.ner(request(json!({}))?).map_err(|f| f.message)?;
+        let strict = service.ner(request(json!({ "threshold": 0.9 }))?).map_err(|f| f.message)?;
+        // Assertions
+        assert_eq!(default["spans"][0][0]["text"], "Bill Gates");
+        assert_eq!(strict["spans"][0], json!([]));
+        assert_eq!(default["entities"], json!(["person"]));
+        // Everything rules
+        Ok(())
+    }
+}
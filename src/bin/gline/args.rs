@@ -0,0 +1,317 @@
+//! Command-line arguments
+
+use std::path::PathBuf;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use gliner::model::params::{Parameters, SpanSelection};
+use gliner::text::offsets::OffsetUnit;
+
+
+#[derive(Parser)]
+#[command(name = "gline", version, about = "Inference engine for GLiNER models")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Named Entity Recognition over files (or stdin)
+    Ner(NerArgs),
+    /// Evaluation of a model against gold annotations (precision, recall and F1)
+    Eval(EvalArgs),
+}
+
+
+/// Model-related arguments
+#[derive(Args)]
+pub struct ModelArgs {
+    /// Path to a model directory, with `gliner_config.json`, `tokenizer.json` and `onnx/model.onnx` (`--max-width` and `--max-length` are then taken from the configuration)
+    #[arg(long, conflicts_with_all = ["model", "tokenizer"], required_unless_present_all = ["model", "tokenizer"])]
+    pub model_dir: Option<PathBuf>,
+    /// Path to the ONNX model
+    #[arg(long, requires = "tokenizer")]
+    pub model: Option<PathBuf>,
+    /// Path to the tokenizer (`tokenizer.json`)
+    #[arg(long, requires = "model")]
+    pub tokenizer: Option<PathBuf>,
+    /// Model mode (detected from the model by default)
+    #[arg(long, value_enum, default_value_t = Mode::Auto)]
+    pub mode: Mode,
+    /// Number of threads used by the ONNX runtime
+    #[arg(long, default_value_t = 4)]
+    pub threads: usize,
+}
+
+/// Processing parameters (see `Parameters`)
+#[derive(Args)]
+pub struct ParamArgs {
+    /// Probability threshold
+    #[arg(long, default_value_t = 0.5)]
+    pub threshold: f32,
+    /// Probability threshold of a specific label, overriding `--threshold` (can be repeated)
+    #[arg(long = "class-threshold", value_name = "LABEL=THRESHOLD", value_parser = parse_class_threshold)]
+    pub class_thresholds: Vec<(String, f32)>,
+    /// Allow overlapping entities
+    #[arg(long)]
+    pub no_flat_ner: bool,
+    /// Allow overlapping entities with the same label (requires `--no-flat-ner`)
+    #[arg(long)]
+    pub dup_label: bool,
+    /// Allow overlapping entities with different labels (requires `--no-flat-ner`)
+    #[arg(long)]
+    pub multi_label: bool,
+    /// How entities are selected among overlapping candidates
+    #[arg(long, value_enum, default_value_t = Selection::Greedy)]
+    pub span_selection: Selection,
+    /// Unit of the entity offsets, in addition to byte offsets (JSONL output) or instead of them (CSV and TSV outputs, with columns such as `start_utf16`)
+    #[arg(long, value_enum, default_value_t = Unit::Byte)]
+    pub offset_unit: Unit,
+    /// Maximum span width (span mode)
+    #[arg(long, default_value_t = 12)]
+    pub max_width: usize,
+    /// Maximum number of words per text (0 for unlimited)
+    #[arg(long, default_value_t = 512)]
+    pub max_length: usize,
+    /// Process texts longer than `--max-length` as overlapping windows sharing this number of words (instead of truncating them)
+    #[arg(long)]
+    pub chunk_overlap: Option<usize>,
+}
+
+impl ParamArgs {
+    pub fn parameters(&self) -> Parameters {
+        Parameters::default()
+            .with_threshold(self.threshold)
+            .with_flat_ner(!self.no_flat_ner)
+            .with_dup_label(self.dup_label)
+            .with_multi_label(self.multi_label)
+            .with_max_width(self.max_width)
+            .with_max_length(if self.max_length == 0 { None } else { Some(self.max_length) })
+            .with_class_thresholds(self.class_thresholds.iter().cloned().collect())
+            .with_span_selection(self.span_selection.into())
+            .with_offset_unit(self.offset_unit.into())
+    }
+}
+
+fn parse_class_threshold(arg: &str) -> Result<(String, f32), String> {
+    let (label, threshold) = arg.rsplit_once('=').ok_or(format!("expected LABEL=THRESHOLD, got '{arg}'"))?;
+    let threshold = threshold.parse().map_err(|e| format!("invalid threshold '{threshold}': {e}"))?;
+    Ok((label.to_string(), threshold))
+}
+
+/// Input-related arguments
+#[derive(Args)]
+pub struct InputArgs {
+    /// Input files (`-` or nothing for stdin)
+    pub inputs: Vec<PathBuf>,
+    /// Input format
+    #[arg(long, value_enum, default_value_t = InputFormat::Lines)]
+    pub input_format: InputFormat,
+    /// Name of the column (CSV) or field (JSONL) containing the text
+    #[arg(long, default_value = "text")]
+    pub field: String,
+    /// Name of the column (CSV) or field (JSONL) containing the identifier of each text (input name and row or line number by default)
+    #[arg(long)]
+    pub id_field: Option<String>,
+    /// Number of texts processed at once
+    #[arg(long, default_value_t = 32)]
+    pub batch_size: usize,
+    /// Group the texts of each batch by length, with at most this number of (padded) sub-word tokens per group
+    /// (estimated on the whole texts: with --chunk-overlap, long texts give several chunks beyond this budget)
+    #[arg(long)]
+    pub token_budget: Option<usize>,
+}
+
+/// Output-related arguments
+#[derive(Args)]
+pub struct OutputArgs {
+    /// Output file (stdout if not provided)
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Jsonl)]
+    pub output_format: OutputFormat,
+}
+
+
+#[derive(Args)]
+pub struct NerArgs {
+    #[command(flatten)]
+    pub model: ModelArgs,
+    /// Entity labels (comma-separated)
+    #[arg(long, short, required = true, value_delimiter = ',')]
+    pub labels: Vec<String>,
+    #[command(flatten)]
+    pub params: ParamArgs,
+    #[command(flatten)]
+    pub input: InputArgs,
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+
+#[derive(Args)]
+pub struct EvalArgs {
+    #[command(flatten)]
+    pub model: ModelArgs,
+    /// Gold annotations
+    pub gold: PathBuf,
+    /// Format of the gold annotations
+    #[arg(long, value_enum, default_value_t = GoldFormat::Jsonl)]
+    pub gold_format: GoldFormat,
+    /// Entity labels (comma-separated, all the labels of the gold annotations by default)
+    #[arg(long, short, value_delimiter = ',')]
+    pub labels: Vec<String>,
+    #[command(flatten)]
+    pub params: ParamArgs,
+    /// Number of texts processed at once
+    #[arg(long, default_value_t = 32)]
+    pub batch_size: usize,
+    /// Output file (stdout if not provided)
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+    /// Write the results as JSON instead of a human-readable report
+    #[arg(long)]
+    pub json: bool,
+}
+
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Mode {
+    Auto,
+    Token,
+    Span,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Selection {
+    /// Fast, but may discard a confident entity because of a chain of overlaps
+    Greedy,
+    /// Exact, maximizing the sum of probabilities
+    MaxProbability,
+    /// Exact, maximizing the joint log-probability (ignores entities below 0.5)
+    MaxLogProbability,
+}
+
+impl From<Selection> for SpanSelection {
+    fn from(selection: Selection) -> Self {
+        match selection {
+            Selection::Greedy => Self::Greedy,
+            Selection::MaxProbability => Self::MaxProbability,
+            Selection::MaxLogProbability => Self::MaxLogProbability,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Unit {
+    /// UTF-8 bytes
+    Byte,
+    /// Unicode scalar values (as in Python)
+    Char,
+    /// UTF-16 code units (as in JavaScript)
+    Utf16,
+    /// User-perceived characters (extended grapheme clusters)
+    Grapheme,
+}
+
+impl From<Unit> for OffsetUnit {
+    fn from(unit: Unit) -> Self {
+        match unit {
+            Unit::Byte => Self::Byte,
+            Unit::Char => Self::Char,
+            Unit::Utf16 => Self::Utf16,
+            Unit::Grapheme => Self::Grapheme,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum InputFormat {
+    /// Whole file as one text
+    Text,
+    /// One text per (non-empty) line
+    Lines,
+    /// CSV with headers, text in the column given by `--field`
+    Csv,
+    /// One JSON object per line, text in the field given by `--field`
+    Jsonl,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum GoldFormat {
+    /// One token per line with its BIO tag in the last column, blank lines between sentences
+    Conll,
+    /// One JSON object per line, with `text` and `entities` (`start` and `end` character offsets, and `label`)
+    Jsonl,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum OutputFormat {
+    /// One JSON object per text
+    Jsonl,
+    /// One row per entity (with headers)
+    Csv,
+    /// One row per entity (with headers)
+    Tsv,
+}
+
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    // Silent some clippy warnings for unit tests
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    /// Parses the given parameter arguments
+    fn parse(args: &[&str]) -> ParamArgs {
+        #[derive(Parser)]
+        struct Test {
+            #[command(flatten)]
+            params: ParamArgs,
+        }
+        Test::try_parse_from(std::iter::once("gline").chain(args.iter().copied())).unwrap().params
+    }
+
+    #[test]
+    fn test_defaults() {
+        let params = parse(&[]).parameters();
+        let expected = Parameters::default();
+        assert_eq!(params.threshold, expected.threshold);
+        assert_eq!(params.flat_ner, expected.flat_ner);
+        assert_eq!(params.dup_label, expected.dup_label);
+        assert_eq!(params.multi_label, expected.multi_label);
+        assert_eq!(params.max_width, expected.max_width);
+        assert_eq!(params.max_length, expected.max_length);
+        assert!(params.class_thresholds.is_empty());
+        assert_eq!(params.span_selection, SpanSelection::Greedy);
+        assert_eq!(params.offset_unit, OffsetUnit::Byte);
+    }
+
+    #[test]
+    fn test_parameters() {
+        let params = parse(&[
+            "--threshold", "0.3", "--class-threshold", "person=0.8", "--class-threshold", "a=b=0.6",
+            "--no-flat-ner", "--multi-label", "--span-selection", "max-log-probability",
+            "--offset-unit", "utf16", "--max-width", "8", "--max-length", "0",
+        ]).parameters();
+        assert_eq!(params.threshold, 0.3);
+        assert!(!params.flat_ner);
+        assert!(!params.dup_label);
+        assert!(params.multi_label);
+        assert_eq!(params.max_width, 8);
+        assert_eq!(params.max_length, None);
+        assert_eq!(params.class_threshold("person"), 0.8);
+        assert_eq!(params.class_threshold("a=b"), 0.6);
+        assert_eq!(params.class_threshold("other"), 0.3);
+        assert_eq!(params.span_selection, SpanSelection::MaxLogProbability);
+        assert_eq!(params.offset_unit, OffsetUnit::Utf16);
+    }
+
+    #[test]
+    fn test_class_threshold() {
+        assert_eq!(parse_class_threshold("person=0.7"), Ok(("person".to_string(), 0.7)));
+        assert!(parse_class_threshold("person").is_err());
+        assert!(parse_class_threshold("person=high").is_err());
+    }
+}
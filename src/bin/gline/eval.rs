@@ -0,0 +1,52 @@
+//! `eval` command
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Write};
+use gliner::eval::{self, gold::{self, Document}};
+use gliner::model::backend::{Backend, TensorPipeline};
+use gliner::model::{GLiNER, input::text::TextInput, output::decoded::SpanOutput, params::Parameters};
+use gliner::model::auto::AutoGLiNER;
+use gliner::util::result::Result;
+use super::args::{EvalArgs, GoldFormat};
+use super::ner::load;
+
+
+pub fn run(args: EvalArgs) -> Result<()> {
+    let reader = BufReader::new(File::open(&args.gold).map_err(|e| format!("{}: {e}", args.gold.display()))?);
+    let documents = match args.gold_format {
+        GoldFormat::Conll => gold::read_conll(reader),
+        GoldFormat::Jsonl => gold::read_jsonl(reader),
+    }.map_err(|e| format!("{}: {e}", args.gold.display()))?;
+    let labels = if args.labels.is_empty() { gold::labels(&documents) } else { args.labels.clone() };
+    if labels.is_empty() {
+        return Err("no labels provided nor found in the gold annotations".into());
+    }
+    match (load(&args.model, args.params.parameters())?, args.params.chunk_overlap) {
+        (AutoGLiNER::Token(model), None) => process(&model, &documents, &labels, &args),
+        (AutoGLiNER::Token(model), Some(overlap)) => process(&model.chunked(overlap), &documents, &labels, &args),
+        (AutoGLiNER::Span(model), None) => process(&model, &documents, &labels, &args),
+        (AutoGLiNER::Span(model), Some(overlap)) => process(&model.chunked(overlap), &documents, &labels, &args),
+    }
+}
+
+
+/// Evaluates the model and writes the results
+fn process<P, B: Backend>(model: &GLiNER<P, B>, documents: &[Document], labels: &[String], args: &EvalArgs) -> Result<()>
+where
+    P: for<'a> TensorPipeline<'a, Input = TextInput, Output = SpanOutput, Parameters = Parameters>,
+{
+    let evaluation = eval::evaluate(model, documents, labels, args.batch_size)?;
+    let mut output: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(io::stdout())),
+    };
+    if args.json {
+        serde_json::to_writer_pretty(&mut output, &evaluation)?;
+        writeln!(output)?;
+    }
+    else {
+        write!(output, "{evaluation}")?;
+    }
+    output.flush()?;
+    Ok(())
+}
@@ -0,0 +1,79 @@
+//! Input readers
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+use gliner::model::input::reader::{Column, CsvReader, JsonlReader, Record};
+use gliner::util::result::Result;
+use super::args::{InputArgs, InputFormat};
+
+
+/// Iterator over the records of all the given inputs (stdin if empty)
+pub fn read_all(args: &InputArgs) -> impl Iterator<Item = Result<Record>> + '_ {
+    let stdin = [Path::new("-")];
+    let inputs: Vec<&Path> = if args.inputs.is_empty() { stdin.to_vec() } else { args.inputs.iter().map(|p| p.as_path()).collect() };
+    inputs.into_iter().flat_map(move |path| match read(path, args) {
+        Ok(records) => records,
+        Err(e) => Box::new(std::iter::once(Err(e))),
+    })
+}
+
+
+/// Iterator over the records of the given input (`-` for stdin)
+fn read<'a>(path: &Path, args: &InputArgs) -> Result<Box<dyn Iterator<Item = Result<Record>> + 'a>> {
+    let (name, reader): (String, Box<dyn Read>) = if path == Path::new("-") {
+        ("stdin".to_string(), Box::new(io::stdin()))
+    }
+    else {
+        (path.display().to_string(), Box::new(File::open(path).map_err(|e| format!("{}: {e}", path.display()))?))
+    };
+
+    let id = args.id_field.as_deref();
+    Ok(match args.input_format {
+        InputFormat::Text => Box::new(std::iter::once(read_text(name, reader))),
+        InputFormat::Lines => Box::new(read_lines(name, reader)),
+        InputFormat::Csv => Box::new(read_csv(name, reader, &args.field, id)?),
+        InputFormat::Jsonl => Box::new(read_jsonl(name, reader, &args.field, id)),
+    })
+}
+
+
+/// Whole input as one text
+fn read_text(name: String, mut reader: Box<dyn Read>) -> Result<Record> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    Ok(Record { id: name, text })
+}
+
+
+/// One text per non-empty line, identified by line number
+fn read_lines(name: String, reader: Box<dyn Read>) -> impl Iterator<Item = Result<Record>> {
+    BufReader::new(reader).lines().enumerate().filter_map(move |(i, line)| match line {
+        Ok(line) if line.trim().is_empty() => None,
+        Ok(line) => Some(Ok(Record { id: format!("{name}:{}", i + 1), text: line })),
+        Err(e) => Some(Err(e.into())),
+    })
+}
+
+
+/// One text per row in the column with the given header, identified by the given column or by row number
+fn read_csv<'a>(name: String, reader: Box<dyn Read>, column: &str, id: Option<&str>) -> Result<impl Iterator<Item = Result<Record>> + 'a> {
+    let records = CsvReader::new(reader, column, id.map(Column::from)).map_err(|e| format!("{name}: {e}"))?;
+    Ok(identify(name, records, id.is_none()))
+}
+
+
+/// One JSON object per non-empty line with the given string field, identified by the given field or by line number
+fn read_jsonl<'a>(name: String, reader: Box<dyn Read>, field: &str, id: Option<&str>) -> impl Iterator<Item = Result<Record>> + 'a {
+    identify(name, JsonlReader::new(reader, field, id), id.is_none())
+}
+
+
+/// Prefixes errors (and row or line numbers used as identifiers) with the input name
+fn identify<'a>(name: String, records: impl Iterator<Item = Result<Record>> + 'a, numbered: bool) -> impl Iterator<Item = Result<Record>> + 'a {
+    records.map(move |record| match record {
+        Ok(record) if numbered => Ok(Record { id: format!("{name}:{}", record.id), text: record.text }),
+        Ok(record) => Ok(record),
+        Err(e) => Err(format!("{name}: {e}").into()),
+    })
+}
@@ -0,0 +1,34 @@
+//! `gline`: command-line interface to `gline-rs`
+//!
+//! Example (token mode, one text per line read from stdin, JSONL output):
+//!
+//! ```console
+//! $ cat texts.txt | gline ner --model model.onnx --tokenizer tokenizer.json --labels person,location
+//! ```
+//!
+//! Evaluation against gold annotations (precision, recall and F1):
+//!
+//! ```console
+//! $ gline eval --model-dir models/gliner_small-v2.1 --gold-format conll test.conll
+//! ```
+//!
+//! Run `gline help` for the complete list of commands and options.
+
+mod args;
+mod input;
+mod output;
+mod ner;
+mod eval;
+
+use clap::Parser;
+use gliner::util::result::Result;
+use args::{Cli, Command};
+
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    match cli.command {
+        Command::Ner(args) => ner::run(args),
+        Command::Eval(args) => eval::run(args),
+    }
+}
@@ -0,0 +1,71 @@
+//! `ner` command
+
+use orp::params::RuntimeParameters;
+use ort::session::Session;
+use gliner::model::backend::{Backend, TensorPipeline};
+use gliner::model::{GLiNER, input::text::TextInput, output::decoded::SpanOutput, params::Parameters};
+use gliner::model::input::reader::{Batches, RecordBatch};
+use gliner::model::pipeline::{Components, span::SpanMode, token::TokenMode};
+use gliner::model::auto::AutoGLiNER;
+use gliner::util::result::Result;
+use super::args::{Mode, ModelArgs, NerArgs};
+use super::input;
+use super::output::Writer;
+
+
+pub fn run(args: NerArgs) -> Result<()> {
+    match (load(&args.model, args.params.parameters())?, args.params.chunk_overlap) {
+        (AutoGLiNER::Token(model), None) => process(&model, &args),
+        (AutoGLiNER::Token(model), Some(overlap)) => process(&model.chunked(overlap), &args),
+        (AutoGLiNER::Span(model), None) => process(&model, &args),
+        (AutoGLiNER::Span(model), Some(overlap)) => process(&model.chunked(overlap), &args),
+    }
+}
+
+
+/// Loads the model specified by the arguments
+pub fn load(args: &ModelArgs, params: Parameters) -> Result<AutoGLiNER> {
+    let runtime_params = RuntimeParameters::default().with_threads(args.threads);
+    Ok(match (&args.model_dir, &args.tokenizer, &args.model) {
+        (Some(dir), _, _) => match args.mode {
+            Mode::Auto => AutoGLiNER::from_dir(params, runtime_params, dir)?,
+            Mode::Token => AutoGLiNER::Token(GLiNER::<TokenMode, Session>::from_dir(params, runtime_params, dir)?),
+            Mode::Span => AutoGLiNER::Span(GLiNER::<SpanMode, Session>::from_dir(params, runtime_params, dir)?),
+        },
+        (None, Some(tokenizer), Some(model)) => match args.mode {
+            Mode::Auto => AutoGLiNER::new(params, runtime_params, tokenizer, model)?,
+            Mode::Token => AutoGLiNER::Token(GLiNER::<TokenMode, Session>::new(params, runtime_params, tokenizer, model)?),
+            Mode::Span => AutoGLiNER::Span(GLiNER::<SpanMode, Session>::new(params, runtime_params, tokenizer, model)?),
+        },
+        _ => return Err("either a model directory or both model and tokenizer paths must be provided".into()),
+    })
+}
+
+
+/// Reads the inputs by batches, and writes the results as they come
+fn process<P, B: Backend>(model: &GLiNER<P, B>, args: &NerArgs) -> Result<()>
+where
+    P: for<'a> TensorPipeline<'a, Input = TextInput, Output = SpanOutput, Parameters = Parameters> + Components,
+{
+    let mut writer = Writer::new(args.output.output.as_deref(), args.output.output_format, args.params.offset_unit.into())?;
+    writer.header()?;
+    for batch in Batches::new(input::read_all(&args.input), args.input.batch_size, args.labels.clone()) {
+        process_batch(model, batch?, args.input.token_budget, &mut writer)?;
+    }
+    writer.flush()
+}
+
+
+fn process_batch<P, B: Backend>(model: &GLiNER<P, B>, batch: RecordBatch, token_budget: Option<usize>, writer: &mut Writer) -> Result<()>
+where
+    P: for<'a> TensorPipeline<'a, Input = TextInput, Output = SpanOutput, Parameters = Parameters> + Components,
+{
+    let output = match token_budget {
+        Some(budget) => model.inference_bucketed(batch.input, budget)?,
+        None => model.inference(batch.input)?,
+    };
+    for (id, spans) in batch.ids.iter().zip(&output.spans) {
+        writer.write(id, spans)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,149 @@
+//! Output writers
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use gliner::text::{span::Span, offsets::OffsetUnit};
+use gliner::util::result::Result;
+use super::args::OutputFormat;
+
+
+/// Writes the spans found for each text
+pub enum Writer {
+    Jsonl(Box<dyn Write>),
+    /// Offsets are written in the given unit, which is part of their column names (e.g. `start_utf16`) unless
+    /// they are byte offsets
+    Csv(Box<csv::Writer<Box<dyn Write>>>, OffsetUnit),
+}
+
+
+impl Writer {
+    /// Creates a writer for the given file (stdout if `None`), offsets being in the given unit (see `Parameters::offset_unit`)
+    pub fn new(path: Option<&Path>, format: OutputFormat, unit: OffsetUnit) -> Result<Self> {
+        let output: Box<dyn Write> = match path {
+            Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+            None => Box::new(BufWriter::new(io::stdout())),
+        };
+        Ok(match format {
+            OutputFormat::Jsonl => Self::Jsonl(output),
+            OutputFormat::Csv => Self::csv(output, b',', unit),
+            OutputFormat::Tsv => Self::csv(output, b'\t', unit),
+        })
+    }
+
+    fn csv(output: Box<dyn Write>, delimiter: u8, unit: OffsetUnit) -> Self {
+        Self::Csv(Box::new(csv::WriterBuilder::new().delimiter(delimiter).from_writer(output)), unit)
+    }
+
+    /// Writes the header, if any
+    pub fn header(&mut self) -> Result<()> {
+        if let Self::Csv(csv, unit) = self {
+            let suffix = match unit {
+                OffsetUnit::Byte => "",
+                OffsetUnit::Char => "_char",
+                OffsetUnit::Utf16 => "_utf16",
+                OffsetUnit::Grapheme => "_grapheme",
+            };
+            csv.write_record(["id", &format!("start{suffix}"), &format!("end{suffix}"), "text", "class", "probability"])?;
+        }
+        Ok(())
+    }
+
+    /// Writes the spans of one text
+    pub fn write(&mut self, id: &str, spans: &[Span]) -> Result<()> {
+        match self {
+            Self::Jsonl(output) => {
+                serde_json::to_writer(&mut *output, &serde_json::json!({ "id": id, "spans": spans }))?;
+                writeln!(output)?;
+            }
+            Self::Csv(csv, unit) => {
+                for span in spans {
+                    let (start, end) = match span.unit_offsets() {
+                        _ if *unit == OffsetUnit::Byte => span.offsets(),
+                        Some(offsets) if offsets.unit == *unit => (offsets.start, offsets.end),
+                        _ => return Err(format!("{unit:?} offsets not computed for span '{}'", span.text()).into()),
+                    };
+                    csv.write_record([id, &start.to_string(), &end.to_string(), span.text(), span.class(), &span.probability().to_string()])?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Flushes the underlying output
+    pub fn flush(&mut self) -> Result<()> {
+        match self {
+            Self::Jsonl(output) => output.flush()?,
+            Self::Csv(csv, _) => csv.flush()?,
+        }
+        Ok(())
+    }
+}
+
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    // Silent some clippy warnings for unit tests
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use gliner::text::offsets::OffsetMap;
+
+    /// Writes the header and the given spans for two texts in the given format, and reads the result back
+    fn written(format: OutputFormat, unit: OffsetUnit, name: &str, spans: &[Span]) -> Result<String> {
+        let path = std::env::temp_dir().join(format!("gline-{}-{name}", std::process::id()));
+        let mut writer = Writer::new(Some(&path), format, unit)?;
+        writer.header()?;
+        writer.write("doc-1", spans)?;
+        writer.write("doc-2", &[])?;
+        writer.flush()?;
+        drop(writer);
+        let result = std::fs::read_to_string(&path)?;
+        std::fs::remove_file(&path)?;
+        Ok(result)
+    }
+
+    fn spans() -> Vec<Span> {
+        vec![
+            Span::new(0, 0, 11, "Émile Zola".to_string(), "person".to_string(), 0.5),
+            Span::new(0, 18, 26, "Germinal, a novel".to_string(), "book".to_string(), 0.25),
+        ]
+    }
+
+    #[test]
+    fn test_csv() -> Result<()> {
+        let csv = written(OutputFormat::Csv, OffsetUnit::Byte, "test.csv", &spans())?;
+        assert_eq!(csv, "id,start,end,text,class,probability\ndoc-1,0,11,Émile Zola,person,0.5\ndoc-1,18,26,\"Germinal, a novel\",book,0.25\n");
+        let tsv = written(OutputFormat::Tsv, OffsetUnit::Byte, "test.tsv", &spans())?;
+        assert_eq!(tsv, "id\tstart\tend\ttext\tclass\tprobability\ndoc-1\t0\t11\tÉmile Zola\tperson\t0.5\ndoc-1\t18\t26\tGerminal, a novel\tbook\t0.25\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_csv_unit() -> Result<()> {
+        let text = "Émile Zola wrote Germinal, a novel";
+        let offsets = OffsetMap::new(text, OffsetUnit::Utf16);
+        let spans = spans().into_iter()
+            .map(|span| Ok(span.clone().with_unit_offsets(offsets.span(&span)?)))
+            .collect::<Result<Vec<_>>>()?;
+        let csv = written(OutputFormat::Csv, OffsetUnit::Utf16, "test-unit.csv", &spans)?;
+        assert_eq!(csv, "id,start_utf16,end_utf16,text,class,probability\ndoc-1,0,10,Émile Zola,person,0.5\ndoc-1,17,25,\"Germinal, a novel\",book,0.25\n");
+        // offsets must have been computed in the expected unit
+        assert!(written(OutputFormat::Csv, OffsetUnit::Char, "test-missing.csv", &spans).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_jsonl() -> Result<()> {
+        let jsonl = written(OutputFormat::Jsonl, OffsetUnit::Byte, "test.jsonl", &spans())?;
+        let lines: Vec<serde_json::Value> = jsonl.lines().map(serde_json::from_str).collect::<std::result::Result<_, _>>()?;
+        assert_eq!(lines.len(), 2);
+        let first = lines.first().unwrap();
+        assert_eq!(first["id"], "doc-1");
+        assert_eq!(first["spans"][0]["text"], "Émile Zola");
+        assert_eq!(first["spans"][1]["start"], 18);
+        assert_eq!(first["spans"][1]["class"], "book");
+        assert_eq!(lines.get(1).unwrap(), &serde_json::json!({ "id": "doc-2", "spans": [] }));
+        Ok(())
+    }
+}
@@ -0,0 +1,211 @@
+//! Readers of gold annotations
+
+use std::io::BufRead;
+use crate::util::{error::GlinerError, result::{Result, GlinerResult}};
+use crate::text::span::Span;
+
+
+/// A text with its gold entities. Spans are given by byte offsets (like predicted ones), their
+/// `sequence` being the index of the document, and their probability being 1.
+#[derive(Debug, Clone)]
+pub struct Document {
+    pub text: String,
+    pub spans: Vec<Span>,
+}
+
+impl Document {
+    /// Creates a document, checking that the spans are valid byte offsets within the text
+    pub fn new(text: String, entities: &[(usize, usize, &str)], sequence: usize) -> Result<Self> {
+        let spans = entities.iter().map(|(start, end, label)| {
+            match text.get(*start..*end) {
+                Some(span_text) if end > start => Ok(Span::new(sequence, *start, *end, span_text.to_string(), label.to_string(), 1.0)),
+                _ => Err(GlinerError::InvalidInput { sequence: Some(sequence), message: format!("invalid entity offsets: {start}..{end}") }.into()),
+            }
+        }).collect::<Result<Vec<_>>>()?;
+        Ok(Self { text, spans })
+    }
+}
+
+
+/// Distinct labels of the given documents, in alphabetical order
+pub fn labels(documents: &[Document]) -> Vec<String> {
+    let mut labels: Vec<String> = documents.iter().flat_map(|d| d.spans.iter().map(|s| s.class().to_string())).collect();
+    labels.sort_unstable();
+    labels.dedup();
+    labels
+}
+
+
+/// Reads CoNLL-style BIO annotations: one token per line, with its tag in the last column (`O`, `B-<label>`
+/// or `I-<label>`), sentences being separated by blank lines (`-DOCSTART-` lines are ignored). Each sentence
+/// becomes a document, its text being the tokens separated by single spaces.
+///
+/// An `I-` tag which does not continue an entity of the same label starts a new one (IOB1-style). BIOES tags
+/// are supported as well (`E-<label>` ending an entity, `S-<label>` being a single-token entity).
+pub fn read_conll<R: BufRead>(reader: R) -> Result<Vec<Document>> {
+    let mut documents = Vec::new();
+    let mut sentence = Sentence::default();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        let columns: Vec<&str> = line.split_whitespace().collect();
+        match (columns.first(), columns.last()) {
+            (Some(token), _) if token.starts_with("-DOCSTART-") => sentence.flush(&mut documents)?,
+            (Some(token), Some(tag)) if columns.len() >= 2 => sentence.push(token, tag).map_err(|e| at_line(e.at_sequence(documents.len()), i + 1))?,
+            (Some(_), _) => return Err(at_line(GlinerError::InvalidInput { sequence: Some(documents.len()), message: "expected a token and a tag".to_string() }, i + 1).into()),
+            (None, _) => sentence.flush(&mut documents)?,
+        }
+    }
+    sentence.flush(&mut documents)?;
+    Ok(documents)
+}
+
+
+/// Sentence being read from CoNLL annotations
+#[derive(Default)]
+struct Sentence {
+    text: String,
+    /// Entities (byte offsets and label), the last one being extended by `I-` tags
+    entities: Vec<(usize, usize, String)>,
+    /// Whether the last token belongs to the last entity
+    inside: bool,
+}
+
+impl Sentence {
+    fn push(&mut self, token: &str, tag: &str) -> GlinerResult<()> {
+        if !self.text.is_empty() {
+            self.text.push(' ');
+        }
+        let (start, end) = (self.text.len(), self.text.len() + token.len());
+        self.text.push_str(token);
+        match tag.split_once('-') {
+            _ if tag == "O" => self.inside = false,
+            Some((prefix @ ("I" | "E"), label)) if self.inside && self.entities.last().is_some_and(|e| e.2 == label) => {
+                if let Some(entity) = self.entities.last_mut() {
+                    entity.1 = end;
+                }
+                self.inside = prefix == "I";
+            },
+            Some((prefix @ ("B" | "I" | "E" | "S"), label)) if !label.is_empty() => {
+                self.entities.push((start, end, label.to_string()));
+                self.inside = matches!(prefix, "B" | "I");
+            },
+            _ => return Err(GlinerError::invalid_input(&format!("unexpected tag: {tag}"))),
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self, documents: &mut Vec<Document>) -> Result<()> {
+        if !self.text.is_empty() {
+            let sentence = std::mem::take(self);
+            let entities: Vec<(usize, usize, &str)> = sentence.entities.iter().map(|(s, e, l)| (*s, *e, l.as_str())).collect();
+            documents.push(Document::new(sentence.text, &entities, documents.len())?);
+        }
+        Ok(())
+    }
+}
+
+
+/// Reads JSONL annotations: one JSON object per non-empty line, with the text and its entities given by
+/// *character* offsets (the end being exclusive):
+/// ```json
+/// { "text": "My name is James Bond", "entities": [{ "start": 11, "end": 21, "label": "person" }] }
+/// ```
+pub fn read_jsonl<R: BufRead>(reader: R) -> Result<Vec<Document>> {
+    let mut documents = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let document = parse_json(&line, documents.len()).map_err(|e| at_line(e.into(), i + 1))?;
+        documents.push(document);
+    }
+    Ok(documents)
+}
+
+
+fn parse_json(line: &str, sequence: usize) -> Result<Document> {
+    let invalid = |message: String| GlinerError::InvalidInput { sequence: Some(sequence), message };
+    let value: serde_json::Value = serde_json::from_str(line).map_err(|e| invalid(e.to_string()))?;
+    let text = value.get("text").and_then(|t| t.as_str()).ok_or_else(|| invalid("missing string field text".to_string()))?;
+    // byte offset of each character offset (including the end of the text)
+    let offsets: Vec<usize> = text.char_indices().map(|(i, _)| i).chain(std::iter::once(text.len())).collect();
+    let byte_offset = |value: &serde_json::Value, field: &str| -> Result<usize> {
+        let offset = value.get(field).and_then(|v| v.as_u64()).ok_or_else(|| invalid(format!("missing integer field {field}")))?;
+        Ok(*offsets.get(offset as usize).ok_or_else(|| invalid(format!("offset out of bounds: {offset}")))?)
+    };
+    let mut entities = Vec::new();
+    for entity in value.get("entities").and_then(|e| e.as_array()).map(Vec::as_slice).unwrap_or_default() {
+        let label = entity.get("label").and_then(|l| l.as_str()).ok_or_else(|| invalid("missing string field label".to_string()))?;
+        entities.push((byte_offset(entity, "start")?, byte_offset(entity, "end")?, label));
+    }
+    Document::new(text.to_string(), &entities, sequence)
+}
+
+
+/// Prefixes the message of an invalid input error with the given line number (other errors are kept as is)
+fn at_line(error: GlinerError, line: usize) -> GlinerError {
+    match error {
+        GlinerError::InvalidInput { sequence, message } => GlinerError::InvalidInput { sequence, message: format!("line {line}: {message}") },
+        e => e,
+    }
+}
+
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    // Silent some clippy warnings for unit tests
+    #![allow(clippy::get_first)]
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn test_conll() -> Result<()> {
+        let conll = "-DOCSTART- -X- O O\n\nMy O\nname O\nis O\nJames B-PER\nBond I-PER\n\nI O\nlike O\nAston B-VEH\nMartin I-VEH\nand O\nLondon I-LOC\n";
+        // Processing
+        let documents = read_conll(conll.as_bytes())?;
+        // Assertions
+        assert_eq!(documents.len(), 2);
+        let first = documents.get(0).unwrap();
+        assert_eq!(first.text, "My name is James Bond");
+        assert_eq!(first.spans.len(), 1);
+        assert_eq!(first.spans.get(0).unwrap().offsets(), (11, 21));
+        assert_eq!(first.spans.get(0).unwrap().text(), "James Bond");
+        assert_eq!(first.spans.get(0).unwrap().class(), "PER");
+        let second = documents.get(1).unwrap();
+        assert_eq!(second.spans.iter().map(|s| (s.text(), s.class(), s.sequence())).collect::<Vec<_>>(), vec![("Aston Martin", "VEH", 1), ("London", "LOC", 1)]);
+        assert_eq!(labels(&documents), vec!["LOC", "PER", "VEH"]);
+        // BIOES
+        let documents = read_conll("Paris S-LOC\nJames B-PER\nBond E-PER\nBond S-PER\n".as_bytes())?;
+        assert_eq!(documents.get(0).unwrap().spans.iter().map(|s| s.text()).collect::<Vec<_>>(), vec!["Paris", "James Bond", "Bond"]);
+        // invalid tag, in the second sentence
+        let result = read_conll("Paris S-LOC\n\nJames X-PER\n".as_bytes()).map_err(GlinerError::from);
+        assert!(matches!(result, Err(GlinerError::InvalidInput { sequence: Some(1), message }) if message == "line 3: unexpected tag: X-PER"));
+        // Everything rules
+        Ok(())
+    }
+
+    #[test]
+    fn test_jsonl() -> Result<()> {
+        let jsonl = r#"{"text": "Élodie lives in Paris", "entities": [{"start": 0, "end": 6, "label": "person"}, {"start": 16, "end": 21, "label": "location"}]}
+
+{"text": "Nothing here"}"#;
+        // Processing
+        let documents = read_jsonl(jsonl.as_bytes())?;
+        // Assertions: character offsets are converted to byte offsets
+        assert_eq!(documents.len(), 2);
+        let first = documents.get(0).unwrap();
+        assert_eq!(first.spans.get(0).unwrap().offsets(), (0, 7));
+        assert_eq!(first.spans.get(0).unwrap().text(), "Élodie");
+        assert_eq!(first.spans.get(1).unwrap().text(), "Paris");
+        assert!(documents.get(1).unwrap().spans.is_empty());
+        // invalid offsets
+        assert!(read_jsonl(r#"{"text": "abc", "entities": [{"start": 2, "end": 5, "label": "x"}]}"#.as_bytes()).is_err());
+        assert!(read_jsonl(r#"{"text": "abc", "entities": [{"start": 2, "end": 2, "label": "x"}]}"#.as_bytes()).is_err());
+        let result = read_jsonl("{\"text\": \"abc\"}\n\n{\"entities\": []}".as_bytes()).map_err(GlinerError::from);
+        assert!(matches!(result, Err(GlinerError::InvalidInput { sequence: Some(1), message }) if message == "line 3: missing string field text"));
+        // Everything rules
+        Ok(())
+    }
+}
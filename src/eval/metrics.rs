@@ -0,0 +1,307 @@
+//! Precision, recall and F1 scores
+
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use crate::text::span::Span;
+
+
+/// Label standing for "no entity" in the confusion matrix
+pub const NONE: &str = "O";
+
+
+/// Counts of matching (true positives), spurious (false positives) and missed (false negatives) entities
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Counts {
+    pub true_positives: usize,
+    pub false_positives: usize,
+    pub false_negatives: usize,
+}
+
+impl Counts {
+    pub fn precision(&self) -> f32 {
+        ratio(self.true_positives, self.true_positives + self.false_positives)
+    }
+
+    pub fn recall(&self) -> f32 {
+        ratio(self.true_positives, self.true_positives + self.false_negatives)
+    }
+
+    pub fn f1(&self) -> f32 {
+        harmonic_mean(self.precision(), self.recall())
+    }
+
+    /// Number of gold entities
+    pub fn support(&self) -> usize {
+        self.true_positives + self.false_negatives
+    }
+
+    pub fn scores(&self) -> Scores {
+        Scores { precision: self.precision(), recall: self.recall(), f1: self.f1() }
+    }
+
+    fn add(&mut self, other: &Counts) {
+        self.true_positives += other.true_positives;
+        self.false_positives += other.false_positives;
+        self.false_negatives += other.false_negatives;
+    }
+}
+
+
+/// Precision, recall and F1 (0 when undefined)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Scores {
+    pub precision: f32,
+    pub recall: f32,
+    pub f1: f32,
+}
+
+
+/// Counts by label, with micro and macro averages
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Metrics {
+    pub labels: BTreeMap<String, Counts>,
+}
+
+impl Metrics {
+    /// Sum of the counts of all labels
+    pub fn total(&self) -> Counts {
+        let mut total = Counts::default();
+        self.labels.values().for_each(|c| total.add(c));
+        total
+    }
+
+    /// Scores computed over the counts of all labels
+    pub fn micro_average(&self) -> Scores {
+        self.total().scores()
+    }
+
+    /// Average of the scores of each label (F1 being the mean of per-label F1 scores)
+    pub fn macro_average(&self) -> Scores {
+        let n = self.labels.len().max(1) as f32;
+        let sum = |f: fn(&Counts) -> f32| self.labels.values().map(f).sum::<f32>() / n;
+        Scores { precision: sum(Counts::precision), recall: sum(Counts::recall), f1: sum(Counts::f1) }
+    }
+
+    fn get(&mut self, label: &str) -> &mut Counts {
+        self.labels.entry(label.to_string()).or_default()
+    }
+}
+
+
+/// Accumulates the comparison of predicted and gold entities, document by document.
+///
+/// * With *strict* matching, a predicted entity is correct iff it has the same offsets and label as a gold one.
+/// * With *partial* matching, it is enough for it to overlap a gold entity with the same label (see `Span::overlaps`).
+///
+/// Matching is one-to-one: a gold entity can only match one predicted entity, exact matches being considered first.
+/// The confusion matrix counts, for each gold label, the labels predicted with the same offsets (or `NONE`), and
+/// conversely for predicted entities with no gold counterpart.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Evaluation {
+    pub strict: Metrics,
+    pub partial: Metrics,
+    /// Number of entities by gold label, then by predicted label
+    pub confusion: BTreeMap<String, BTreeMap<String, usize>>,
+}
+
+impl Evaluation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compares the entities predicted for one document to the gold ones
+    pub fn add(&mut self, gold: &[Span], predicted: &[Span]) {
+        let strict = |g: &Span, p: &Span| g.same_offsets(p);
+        let partial = |g: &Span, p: &Span| g.overlaps(p);
+        // strict matching
+        let mut matches = Matches::new(gold, predicted);
+        matches.find(strict);
+        matches.count(&mut self.strict);
+        // partial matching, exact matches first
+        matches.find(partial);
+        matches.count(&mut self.partial);
+        // confusion
+        for g in gold {
+            let label = predicted.iter().filter(|p| g.same_offsets(p))
+                .find(|p| p.class() == g.class())
+                .or_else(|| predicted.iter().find(|p| g.same_offsets(p)))
+                .map_or(NONE, |p| p.class());
+            self.confuse(g.class(), label);
+        }
+        for p in predicted.iter().filter(|p| !gold.iter().any(|g| g.same_offsets(p))) {
+            self.confuse(NONE, p.class());
+        }
+    }
+
+    fn confuse(&mut self, gold: &str, predicted: &str) {
+        *self.confusion.entry(gold.to_string()).or_default().entry(predicted.to_string()).or_default() += 1;
+    }
+}
+
+
+/// One-to-one matching of gold and predicted entities
+struct Matches<'a> {
+    gold: &'a [Span],
+    predicted: &'a [Span],
+    /// For each gold entity, whether it has been matched
+    gold_matched: Vec<bool>,
+    /// For each predicted entity, whether it has been matched
+    predicted_matched: Vec<bool>,
+}
+
+impl<'a> Matches<'a> {
+    fn new(gold: &'a [Span], predicted: &'a [Span]) -> Self {
+        Self { gold, predicted, gold_matched: vec![false; gold.len()], predicted_matched: vec![false; predicted.len()] }
+    }
+
+    /// Matches the remaining entities with the same label satisfying the given predicate
+    fn find(&mut self, matching: impl Fn(&Span, &Span) -> bool) {
+        for (p, p_matched) in self.predicted.iter().zip(self.predicted_matched.iter_mut()) {
+            if *p_matched {
+                continue;
+            }
+            let found = self.gold.iter().zip(self.gold_matched.iter_mut())
+                .find(|(g, g_matched)| !**g_matched && g.class() == p.class() && matching(g, p));
+            if let Some((_, g_matched)) = found {
+                *g_matched = true;
+                *p_matched = true;
+            }
+        }
+    }
+
+    /// Adds the counts of the current matching
+    fn count(&self, metrics: &mut Metrics) {
+        for (p, matched) in self.predicted.iter().zip(&self.predicted_matched) {
+            let counts = metrics.get(p.class());
+            match matched {
+                true => counts.true_positives += 1,
+                false => counts.false_positives += 1,
+            }
+        }
+        for (g, matched) in self.gold.iter().zip(&self.gold_matched) {
+            if !matched {
+                metrics.get(g.class()).false_negatives += 1;
+            }
+        }
+    }
+}
+
+
+/// Human-readable report
+impl Display for Evaluation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let width = self.strict.labels.keys().chain(self.confusion.keys()).map(|l| l.chars().count()).max().unwrap_or(0).max(9);
+        for (name, metrics) in [("strict", &self.strict), ("partial", &self.partial)] {
+            writeln!(f, "{name:width$}  precision     recall         f1    support")?;
+            for (label, counts) in &metrics.labels {
+                write_scores(f, label, &counts.scores(), Some(counts.support()), width)?;
+            }
+            write_scores(f, "micro avg", &metrics.micro_average(), Some(metrics.total().support()), width)?;
+            write_scores(f, "macro avg", &metrics.macro_average(), None, width)?;
+            writeln!(f)?;
+        }
+        // confusion matrix
+        let mut labels: Vec<&str> = self.confusion.values().flat_map(|row| row.keys()).chain(self.confusion.keys()).map(String::as_str).collect();
+        labels.sort_unstable();
+        labels.dedup();
+        write!(f, "{:width$}", "gold\\pred")?;
+        for label in &labels {
+            write!(f, "  {label:>width$}")?;
+        }
+        writeln!(f)?;
+        for (gold, row) in &self.confusion {
+            write!(f, "{gold:width$}")?;
+            for label in &labels {
+                write!(f, "  {:>width$}", row.get(*label).copied().unwrap_or(0))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+
+fn write_scores(f: &mut std::fmt::Formatter<'_>, label: &str, scores: &Scores, support: Option<usize>, width: usize) -> std::fmt::Result {
+    write!(f, "{label:width$}  {:>9.4}  {:>9.4}  {:>9.4}", scores.precision, scores.recall, scores.f1)?;
+    match support {
+        Some(support) => writeln!(f, "  {support:>9}"),
+        None => writeln!(f),
+    }
+}
+
+
+fn ratio(n: usize, d: usize) -> f32 {
+    if d == 0 { 0.0 } else { n as f32 / d as f32 }
+}
+
+
+fn harmonic_mean(a: f32, b: f32) -> f32 {
+    if a + b == 0.0 { 0.0 } else { 2.0 * a * b / (a + b) }
+}
+
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    // Silent some clippy warnings for unit tests
+    #![allow(clippy::get_first)]
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    fn span(start: usize, end: usize, class: &str) -> Span {
+        Span::new(0, start, end, String::new(), class.to_string(), 1.0)
+    }
+
+    #[test]
+    fn test_evaluation() {
+        let gold = [span(0, 10, "person"), span(20, 30, "location"), span(40, 50, "person"), span(60, 70, "vehicle")];
+        let predicted = [
+            span(0, 10, "person"),      // exact
+            span(22, 30, "location"),   // partial
+            span(40, 50, "location"),   // wrong label
+            span(80, 90, "person"),     // spurious
+        ];
+        // Processing
+        let mut evaluation = Evaluation::new();
+        evaluation.add(&gold, &predicted);
+        // Assertions
+        let person = evaluation.strict.labels.get("person").unwrap();
+        assert_eq!(*person, Counts { true_positives: 1, false_positives: 1, false_negatives: 1 });
+        let location = evaluation.strict.labels.get("location").unwrap();
+        assert_eq!(*location, Counts { true_positives: 0, false_positives: 2, false_negatives: 1 });
+        assert_eq!(evaluation.partial.labels.get("location").unwrap().true_positives, 1);
+        assert_eq!(evaluation.strict.total(), Counts { true_positives: 1, false_positives: 3, false_negatives: 3 });
+        assert_eq!(evaluation.partial.total(), Counts { true_positives: 2, false_positives: 2, false_negatives: 2 });
+        let micro = evaluation.strict.micro_average();
+        assert!((micro.precision - 0.25).abs() < 1e-6);
+        assert!((micro.recall - 0.25).abs() < 1e-6);
+        // macro: person 0.5, location 0, vehicle 0
+        assert!((evaluation.strict.macro_average().f1 - 0.5 / 3.0).abs() < 1e-6);
+        // confusion
+        assert_eq!(evaluation.confusion.get("person").unwrap().get("person"), Some(&1));
+        assert_eq!(evaluation.confusion.get("person").unwrap().get("location"), Some(&1));
+        assert_eq!(evaluation.confusion.get("location").unwrap().get(NONE), Some(&1));
+        assert_eq!(evaluation.confusion.get("vehicle").unwrap().get(NONE), Some(&1));
+        assert_eq!(evaluation.confusion.get(NONE).unwrap().get("location"), Some(&1));
+        assert_eq!(evaluation.confusion.get(NONE).unwrap().get("person"), Some(&1));
+        // report
+        let report = evaluation.to_string();
+        assert!(report.contains("micro avg"));
+        assert!(report.contains("gold\\pred"));
+    }
+
+    #[test]
+    fn test_one_to_one() {
+        // two predictions overlapping the same gold entity: only one of them is correct
+        let gold = [span(0, 10, "person")];
+        let predicted = [span(0, 4, "person"), span(0, 10, "person")];
+        let mut evaluation = Evaluation::new();
+        evaluation.add(&gold, &predicted);
+        assert_eq!(evaluation.strict.total(), Counts { true_positives: 1, false_positives: 1, false_negatives: 0 });
+        assert_eq!(evaluation.partial.total(), Counts { true_positives: 1, false_positives: 1, false_negatives: 0 });
+    }
+}
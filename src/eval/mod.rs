@@ -0,0 +1,71 @@
+//! Evaluation of models against gold annotations
+//!
+//! Gold documents are read from CoNLL (BIO) or JSONL files (see `gold`), processed by a model, and the
+//! predicted entities are compared to the gold ones (see `metrics::Evaluation`):
+//!
+//! ```ignore
+//! let documents = gold::read_conll(BufReader::new(File::open("test.conll")?))?;
+//! let evaluation = eval::evaluate(&model, &documents, &gold::labels(&documents), 32)?;
+//! println!("{evaluation}");
+//! ```
+
+pub mod gold;
+pub mod metrics;
+
+use crate::util::{error::{GlinerError, IndexError}, result::GlinerResult};
+use crate::model::{GLiNER, backend::{Backend, TensorPipeline}, input::text::TextInput, output::decoded::SpanOutput, params::Parameters};
+use gold::Document;
+use metrics::Evaluation;
+
+
+/// Runs the model on the given documents (by batches of `batch_size` texts), looking for the given labels,
+/// and compares the results to the gold entities
+pub fn evaluate<'a, P, B>(model: &'a GLiNER<P, B>, documents: &[Document], labels: &[String], batch_size: usize) -> GlinerResult<Evaluation>
+where
+    P: TensorPipeline<'a, Input = TextInput, Output = SpanOutput, Parameters = Parameters>,
+    B: Backend,
+{
+    let mut evaluation = Evaluation::new();
+    for batch in documents.chunks(batch_size.max(1)) {
+        let input = TextInput::new(batch.iter().map(|d| d.text.clone()).collect(), labels.to_vec())?;
+        let output = model.inference(input)?;
+        if output.spans.len() != batch.len() {
+            return Err(GlinerError::Index(IndexError::with("unexpected number of outputs")));
+        }
+        for (document, predicted) in batch.iter().zip(&output.spans) {
+            evaluation.add(&document.spans, predicted);
+        }
+    }
+    Ok(evaluation)
+}
+
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    // Silent some clippy warnings for unit tests
+    #![allow(clippy::get_first)]
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use crate::util::result::Result;
+    use crate::model::backend::mock::{ScriptedSpan, token_gliner};
+    use metrics::Counts;
+
+    #[test]
+    fn test_evaluate() -> Result<()> {
+        let documents = gold::read_jsonl(r#"{"text": "My name is James Bond", "entities": [{"start": 11, "end": 21, "label": "person"}]}
+{"text": "I like to drive my Aston Martin", "entities": [{"start": 19, "end": 31, "label": "vehicle"}]}"#.as_bytes())?;
+        let labels = gold::labels(&documents);
+        assert_eq!(labels, vec!["person", "vehicle"]);
+        // finds "James Bond" and "Martin" only (as a person)
+        let model = token_gliner(2, &[ScriptedSpan::new(0, 3, 4, 0, 4.0), ScriptedSpan::new(1, 6, 6, 0, 4.0)])?;
+        // Processing
+        let evaluation = evaluate(&model, &documents, &labels, 2)?;
+        // Assertions
+        assert_eq!(evaluation.strict.total(), Counts { true_positives: 1, false_positives: 1, false_negatives: 1 });
+        assert_eq!(evaluation.partial.labels.get("person").unwrap().false_positives, 1);
+        assert_eq!(evaluation.confusion.get("vehicle").unwrap().get(metrics::NONE), Some(&1));
+        // Everything rules
+        Ok(())
+    }
+}
@@ -35,5 +35,10 @@ fn main() -> Result<()> {
     let inference_time = inference_start.elapsed();
     println!("Inference took {} seconds on {} samples ({:.2} samples/sec)", inference_time.as_secs(), nb_samples, nb_samples as f32 / inference_time.as_secs() as f32);
 
+    // Per-stage timings (requires the `tracing` feature)
+    if cfg!(feature = "tracing") {
+        println!("\n{}", gliner::util::profile::report());
+    }
+
     Ok(())
 }
@@ -10,4 +10,5 @@
 pub mod model;
 pub mod text;
 pub mod util;
+pub mod eval;
 
@@ -0,0 +1,174 @@
+//! Asynchronous inferences, for tokio applications (requires the `async` feature)
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use orp::model::Model;
+use tokio::sync::{Semaphore, OwnedSemaphorePermit, TryAcquireError};
+use crate::util::{error::GlinerError, result::{Result, GlinerResult}};
+use super::GLiNER;
+use super::params::Parameters;
+use super::backend::{Backend, TensorPipeline};
+
+
+impl<P, B, I, O> GLiNER<P, B>
+where
+    P: for<'a> TensorPipeline<'a, Input = I, Output = O, Parameters = Parameters> + Send + Sync + 'static,
+    B: Backend + Send + Sync + 'static,
+    I: Send + 'static,
+    O: Send + 'static,
+{
+    /// Asynchronous counterpart of `inference()`: the whole processing (pre-processing, inference
+    /// and post-processing) is offloaded to tokio's blocking pool, so that it does not stall the
+    /// async executor. Must be called from within a tokio runtime.
+    ///
+    /// Dropping the returned future (for example on timeout) cancels the inference if it has not
+    /// started yet. Note that an inference cannot be interrupted once started. See `AsyncGLiNER`
+    /// to bound the number of pending inferences.
+    pub async fn inference_async(self: &Arc<Self>, input: I) -> GlinerResult<O> {
+        let model = Arc::clone(self);
+        Ok(offload(move || Ok(model.inference(input)?), None).await?)
+    }
+}
+
+
+/// Shares a `GLiNER` between async tasks, with a bounded queue: at most `capacity` inferences are
+/// pending or running at the same time, further requests waiting for a slot to be released (or
+/// being rejected, see `try_inference()`).
+pub struct AsyncGLiNER<P, B = Model> {
+    model: Arc<GLiNER<P, B>>,
+    queue: Arc<Semaphore>,
+}
+
+
+impl<P, B> AsyncGLiNER<P, B> {
+    pub fn new(model: GLiNER<P, B>, capacity: usize) -> Self {
+        Self::from_arc(Arc::new(model), capacity)
+    }
+
+    /// Same as `new()`, for a model which is already shared
+    pub fn from_arc(model: Arc<GLiNER<P, B>>, capacity: usize) -> Self {
+        Self {
+            model,
+            queue: Arc::new(Semaphore::new(capacity)),
+        }
+    }
+
+    /// The underlying model (for synchronous inferences)
+    pub fn model(&self) -> &Arc<GLiNER<P, B>> {
+        &self.model
+    }
+
+    /// Number of inferences that can be submitted right now without waiting
+    pub fn available(&self) -> usize {
+        self.queue.available_permits()
+    }
+}
+
+
+impl<P, B, I, O> AsyncGLiNER<P, B>
+where
+    P: for<'a> TensorPipeline<'a, Input = I, Output = O, Parameters = Parameters> + Send + Sync + 'static,
+    B: Backend + Send + Sync + 'static,
+    I: Send + 'static,
+    O: Send + 'static,
+{
+    /// Waits for a slot in the queue, then performs the inference like `GLiNER::inference_async()`
+    pub async fn inference(&self, input: I) -> GlinerResult<O> {
+        let permit = Arc::clone(&self.queue).acquire_owned().await.map_err(|_| GlinerError::Execution("inference queue is closed".to_string()))?;
+        let model = Arc::clone(&self.model);
+        Ok(offload(move || Ok(model.inference(input)?), Some(permit)).await?)
+    }
+
+    /// Same as `inference()`, but fails immediately if the queue is full
+    pub async fn try_inference(&self, input: I) -> GlinerResult<O> {
+        let permit = Arc::clone(&self.queue).try_acquire_owned().map_err(|e| match e {
+            TryAcquireError::NoPermits => GlinerError::QueueFull,
+            TryAcquireError::Closed => GlinerError::Execution("inference queue is closed".to_string()),
+        })?;
+        let model = Arc::clone(&self.model);
+        Ok(offload(move || Ok(model.inference(input)?), Some(permit)).await?)
+    }
+}
+
+
+/// Runs the given job in the blocking pool, unless the future gets dropped before it starts.
+/// The permit (if any) is held until the job actually ends, even if the future was dropped in between.
+async fn offload<T, F>(job: F, permit: Option<OwnedSemaphorePermit>) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let _guard = CancelOnDrop(Arc::clone(&cancelled));
+    let handle = tokio::task::spawn_blocking(move || {
+        let _permit = permit;
+        if cancelled.load(Ordering::Acquire) {
+            return Err(GlinerError::Cancelled.into());
+        }
+        job()
+    });
+    handle.await.map_err(|e| GlinerError::Execution(format!("inference task failed: {e}")))?
+}
+
+/// Raises the cancellation flag when dropped
+struct CancelOnDrop(Arc<AtomicBool>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Release);
+    }
+}
+
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use std::sync::atomic::AtomicUsize;
+
+    fn runtime() -> Result<tokio::runtime::Runtime> {
+        Ok(tokio::runtime::Builder::new_current_thread().max_blocking_threads(1).enable_time().build()?)
+    }
+
+    #[test]
+    fn test_offload() -> Result<()> {
+        let result = runtime()?.block_on(offload(|| Ok(42), None))?;
+        assert_eq!(result, 42);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cancellation() -> Result<()> {
+        runtime()?.block_on(async {
+            let runs = Arc::new(AtomicUsize::new(0));
+            // occupy the (only) blocking thread until released
+            let (tx, rx) = std::sync::mpsc::channel::<()>();
+            let first = tokio::task::spawn_blocking(move || rx.recv());
+            // this one is queued, and gets dropped on timeout before it starts
+            let counter = Arc::clone(&runs);
+            let second = tokio::time::timeout(Duration::from_millis(50), offload(move || Ok(counter.fetch_add(1, Ordering::SeqCst)), None)).await;
+            assert!(second.is_err());
+            // release the first job, then run a third one which must be executed after the second one was skipped
+            tx.send(())?;
+            first.await??;
+            let counter = Arc::clone(&runs);
+            offload(move || Ok(counter.fetch_add(1, Ordering::SeqCst)), None).await?;
+            assert_eq!(runs.load(Ordering::SeqCst), 1);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_permit() -> Result<()> {
+        runtime()?.block_on(async {
+            let queue = Arc::new(Semaphore::new(1));
+            let permit = Arc::clone(&queue).try_acquire_owned()?;
+            assert!(Arc::clone(&queue).try_acquire_owned().is_err());
+            // the permit is released once the job is done
+            offload(|| Ok(()), Some(permit)).await?;
+            assert_eq!(queue.available_permits(), 1);
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,134 @@
+//! Automatic detection of the model mode (token or span)
+
+use std::path::Path;
+use orp::params::RuntimeParameters;
+use ort::session::{Session, builder::GraphOptimizationLevel};
+use crate::util::{error::GlinerError, result::{Result, GlinerResult}};
+use super::GLiNER;
+use super::params::Parameters;
+use super::config::ModelDir;
+use super::input::text::TextInput;
+use super::output::decoded::SpanOutput;
+use super::pipeline::{token::{TokenMode, TokenPipeline}, span::{SpanMode, SpanPipeline}};
+use super::backend::onnxruntime::load_session;
+
+
+/// Inputs expected in both modes
+const COMMON_INPUTS: [&str; 4] = ["input_ids", "attention_mask", "words_mask", "text_lengths"];
+/// Additional inputs expected in span mode
+const SPAN_INPUTS: [&str; 2] = ["span_idx", "span_mask"];
+
+
+/// Mode of a GLiNER model (see `doc/Model.md`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Token,
+    Span,
+}
+
+
+impl Mode {
+    /// Detects the mode of the given ONNX model by inspecting its inputs and outputs.
+    ///
+    /// Note that this requires the model to be loaded (without any optimization), so this
+    /// should not be called more than necessary. When the model is to be run afterwards, rather
+    /// load the session once and call `from_session()` (as `AutoGLiNER` does).
+    pub fn detect<P: AsRef<Path>>(model_path: P) -> GlinerResult<Self> {
+        let session = || Session::builder()?
+            .with_optimization_level(GraphOptimizationLevel::Disable)?
+            .commit_from_file(model_path);
+        Ok(Self::from_session(&session().map_err(GlinerError::Load)?)?)
+    }
+
+    /// Detects the mode of an already loaded ONNX session
+    pub fn from_session(session: &Session) -> Result<Self> {
+        let inputs: Vec<&str> = session.inputs.iter().map(|i| i.name.as_str()).collect();
+        let logits = session.outputs.iter().find(|o| o.name == "logits").ok_or_else(|| GlinerError::Schema("logits not found in model outputs".to_string()))?;
+        let logits_rank = logits.output_type.tensor_dimensions().map(|d| d.len());
+        Self::from_schema(&inputs, logits_rank)
+    }
+
+    /// Both modes expect the same common inputs and a four-dimensional `logits` output.
+    /// The span mode is recognized by the presence of the `span_idx` and `span_mask` inputs.
+    fn from_schema(inputs: &[&str], logits_rank: Option<usize>) -> Result<Self> {
+        if let Some(missing) = COMMON_INPUTS.iter().find(|i| !inputs.contains(i)) {
+            return Err(GlinerError::Schema(format!("missing input {missing}")).into());
+        }
+        if logits_rank != Some(4) {
+            return Err(GlinerError::Schema(format!("logits should be four-dimensional (found {logits_rank:?})")).into());
+        }
+        match SPAN_INPUTS.iter().filter(|i| inputs.contains(i)).count() {
+            0 => Ok(Self::Token),
+            2 => Ok(Self::Span),
+            _ => Err(GlinerError::Schema("span_idx and span_mask must be both present or both absent".to_string()).into()),
+        }
+    }
+}
+
+
+/// GLiNER handle which mode is detected when the model is loaded, and which dispatches
+/// inferences to the appropriate pipeline.
+///
+/// The model is run by the ONNX Runtime session that was used to detect its mode.
+pub enum AutoGLiNER {
+    Token(GLiNER<TokenMode, Session>),
+    Span(GLiNER<SpanMode, Session>),
+}
+
+
+impl AutoGLiNER {
+    /// Loads the model, detects its mode (see `Mode::from_session()`) and builds the appropriate pipeline
+    pub fn new<P: AsRef<Path>>(params: Parameters, runtime_params: RuntimeParameters, tokenizer_path: P, model_path: P) -> GlinerResult<Self> {
+        let session = load_session(model_path, runtime_params)?;
+        Ok(match Mode::from_session(&session)? {
+            Mode::Token => Self::Token(GLiNER::with_backend(params, TokenPipeline::new(tokenizer_path)?, session)),
+            Mode::Span => Self::Span(GLiNER::with_backend(params, SpanPipeline::new(tokenizer_path)?, session)),
+        })
+    }
+
+    /// Loads a model directory (see `ModelDir`), the mode being given by its configuration (see `GLiNER::from_dir()`)
+    pub fn from_dir<P: AsRef<Path>>(params: Parameters, runtime_params: RuntimeParameters, path: P) -> GlinerResult<Self> {
+        let dir = ModelDir::new(path)?;
+        let config = dir.config()?;
+        let session = load_session(&dir.model, runtime_params)?;
+        Ok(match config.mode() {
+            Mode::Token => Self::Token(GLiNER::<TokenMode, _>::from_config(params, &dir, &config, session)?),
+            Mode::Span => Self::Span(GLiNER::<SpanMode, _>::from_config(params, &dir, &config, session)?),
+        })
+    }
+
+    /// The detected mode
+    pub fn mode(&self) -> Mode {
+        match self {
+            Self::Token(_) => Mode::Token,
+            Self::Span(_) => Mode::Span,
+        }
+    }
+
+    pub fn inference(&self, input: TextInput) -> GlinerResult<SpanOutput> {
+        match self {
+            Self::Token(model) => model.inference(input),
+            Self::Span(model) => model.inference(input),
+        }
+    }
+}
+
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_schema() -> Result<()> {
+        let token = ["input_ids", "attention_mask", "words_mask", "text_lengths"];
+        let span = ["input_ids", "attention_mask", "words_mask", "text_lengths", "span_idx", "span_mask"];
+        assert_eq!(Mode::from_schema(&token, Some(4))?, Mode::Token);
+        assert_eq!(Mode::from_schema(&span, Some(4))?, Mode::Span);
+        assert!(Mode::from_schema(&span, Some(3)).is_err());
+        assert!(Mode::from_schema(&span, None).is_err());
+        assert!(Mode::from_schema(&span[1..], Some(4)).is_err());
+        assert!(Mode::from_schema(&span[..5], Some(4)).is_err());
+        Ok(())
+    }
+}
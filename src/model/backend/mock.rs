@@ -0,0 +1,263 @@
+//! Deterministic in-process backend, to drive whole pipelines in tests without an actual model
+//!
+//! The `MockBackend` simply returns the tensors computed by a closure, which is typically one of the
+//! helpers below, producing logits for a given set of scripted entities:
+//!
+//! ```ignore
+//! let spans = [ScriptedSpan::new(0, 3, 4, 0, 5.0)];
+//! let backend = MockBackend::new(move |inputs| token_logits(inputs, 2, &spans));
+//! let model = GLiNER::with_backend(Parameters::default(), pipeline, backend);
+//! ```
+//!
+//! Within the unit tests of the crate, `token_gliner()` builds such a model in token mode, along with the tiny
+//! tokenizer of the test data.
+
+use ndarray::{Array, Array4};
+use crate::util::{error::{GlinerError, IndexError}, result::Result};
+use super::{Backend, Tensors};
+#[cfg(test)]
+use crate::{model::{GLiNER, params::Parameters, pipeline::token::TokenPipeline}, text::{splitter::RegexSplitter, tokenizer::HFTokenizer}};
+
+/// Logit of anything that is not scripted (far below any sensible threshold once through a sigmoid)
+const LOW_LOGIT: f32 = -10.0;
+
+
+/// Backend returning the output of the given function, whatever the model
+pub struct MockBackend<F> {
+    run: F,
+}
+
+impl<F: Fn(&Tensors) -> Result<Tensors>> MockBackend<F> {
+    pub fn new(run: F) -> Self {
+        Self { run }
+    }
+}
+
+impl<F: Fn(&Tensors) -> Result<Tensors>> Backend for MockBackend<F> {
+    fn run(&self, inputs: Tensors) -> Result<Tensors> {
+        (self.run)(&inputs)
+    }
+}
+
+
+/// Entity to be found by the model: word-level offsets (inclusive) within a sequence, class index, and logit
+#[derive(Debug, Clone)]
+pub struct ScriptedSpan {
+    pub sequence: usize,
+    pub start: usize,
+    pub end: usize,
+    pub class: usize,
+    pub logit: f32,
+}
+
+impl ScriptedSpan {
+    pub fn new(sequence: usize, start: usize, end: usize, class: usize, logit: f32) -> Self {
+        Self { sequence, start, end, class, logit }
+    }
+}
+
+
+/// Span-mode logits for the given inputs, with shape `(batch_size, num_words, max_width, num_classes)`
+pub fn span_logits(inputs: &Tensors, num_classes: usize, max_width: usize, spans: &[ScriptedSpan]) -> Result<Tensors> {
+    let (batch_size, num_words) = dimensions(inputs)?;
+    let mut logits = Array::from_elem((batch_size, num_words, max_width, num_classes), LOW_LOGIT);
+    for span in spans {
+        let width = span.end.checked_sub(span.start).ok_or(GlinerError::invalid_input("scripted span ends before it starts"))?;
+        set(&mut logits, (span.sequence, span.start, width, span.class), span.logit)?;
+    }
+    Ok(Tensors::new().with("logits", logits))
+}
+
+
+/// Token-mode logits for the given inputs, with shape `(3, batch_size, num_words, num_classes)`
+/// (start, end and inside scores)
+pub fn token_logits(inputs: &Tensors, num_classes: usize, spans: &[ScriptedSpan]) -> Result<Tensors> {
+    let (batch_size, num_words) = dimensions(inputs)?;
+    let mut logits = Array::from_elem((3, batch_size, num_words, num_classes), LOW_LOGIT);
+    for span in spans {
+        set(&mut logits, (0, span.sequence, span.start, span.class), span.logit)?;
+        set(&mut logits, (1, span.sequence, span.end, span.class), span.logit)?;
+        for word in span.start..=span.end {
+            set(&mut logits, (2, span.sequence, word, span.class), span.logit)?;
+        }
+    }
+    Ok(Tensors::new().with("logits", logits))
+}
+
+
+/// Batch size and number of words (that is the length of the longest text) of the given inputs
+fn dimensions(inputs: &Tensors) -> Result<(usize, usize)> {
+    let text_lengths = inputs.get("text_lengths").ok_or(GlinerError::Schema("text_lengths not found in model input".to_string()))?;
+    let text_lengths = text_lengths.as_i64()?;
+    let batch_size = text_lengths.shape().first().copied().unwrap_or(0);
+    let num_words = text_lengths.iter().copied().max().unwrap_or(0).max(0) as usize;
+    Ok((batch_size, num_words))
+}
+
+
+fn set(logits: &mut Array4<f32>, index: (usize, usize, usize, usize), logit: f32) -> Result<()> {
+    let cell = logits.get_mut(index).ok_or(IndexError::with(&format!("scripted span out of bounds: {index:?}")))?;
+    *cell = logit;
+    Ok(())
+}
+
+
+/// Token-mode model (with the tiny tokenizer of the test data) finding the given entities among `num_classes` ones
+#[cfg(test)]
+pub(crate) fn token_gliner(num_classes: usize, spans: &[ScriptedSpan]) -> Result<GLiNER<TokenPipeline<RegexSplitter, HFTokenizer>, impl Backend>> {
+    let pipeline = TokenPipeline::with_components(RegexSplitter::default(), HFTokenizer::from_file("data/tokenizer-tiny.json")?);
+    let spans = spans.to_vec();
+    let backend = MockBackend::new(move |inputs: &Tensors| token_logits(inputs, num_classes, &spans));
+    Ok(GLiNER::with_backend(Parameters::default(), pipeline, backend))
+}
+
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    // Silent some clippy warnings for unit tests
+    #![allow(clippy::get_first)]
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use crate::model::{GLiNER, params::Parameters, input::text::TextInput, output::decoded::SpanOutput};
+    use crate::model::pipeline::{token::TokenPipeline, span::SpanPipeline, chunked::ChunkedPipeline};
+    use crate::model::output::{tensors::TensorOutput, decoded::{token, token_flat}};
+    use crate::model::backend::TensorPipeline;
+    use crate::text::{splitter::RegexSplitter, tokenizer::HFTokenizer};
+    use composable::Composable;
+
+    const TOKENIZER: &str = "data/tokenizer-tiny.json";
+
+    fn input() -> Result<TextInput> {
+        TextInput::from_str(
+            &["My name is James Bond", "I like to drive my Aston Martin"],
+            &["person", "vehicle"],
+        )
+    }
+
+    fn scripted() -> Vec<ScriptedSpan> {
+        vec![
+            ScriptedSpan::new(0, 3, 4, 0, 4.0),
+            ScriptedSpan::new(1, 5, 6, 1, 3.0),
+        ]
+    }
+
+    fn check(output: &SpanOutput) {
+        assert_eq!(output.spans.len(), 2);
+        let bond = output.spans.get(0).unwrap();
+        assert_eq!(bond.len(), 1);
+        assert_eq!(bond.get(0).unwrap().text(), "James Bond");
+        assert_eq!(bond.get(0).unwrap().class(), "person");
+        assert_eq!(bond.get(0).unwrap().offsets(), (11, 21));
+        assert!((bond.get(0).unwrap().probability() - crate::util::math::sigmoid(4.0)).abs() < 1e-6);
+        let aston = output.spans.get(1).unwrap();
+        assert_eq!(aston.len(), 1);
+        assert_eq!(aston.get(0).unwrap().text(), "Aston Martin");
+        assert_eq!(aston.get(0).unwrap().class(), "vehicle");
+    }
+
+    #[test]
+    fn test_token_mode() -> Result<()> {
+        let pipeline = TokenPipeline::with_components(RegexSplitter::default(), HFTokenizer::from_file(TOKENIZER)?);
+        let spans = scripted();
+        let backend = MockBackend::new(move |inputs: &Tensors| {
+            // the actual model inputs are provided
+            assert_eq!(inputs.names().collect::<Vec<_>>(), vec!["input_ids", "attention_mask", "words_mask", "text_lengths"]);
+            token_logits(inputs, 2, &spans)
+        });
+        let model = GLiNER::with_backend(Parameters::default(), pipeline, backend);
+        // Processing
+        let output = model.inference(input()?)?;
+        // Assertions
+        check(&output);
+        // Everything rules
+        Ok(())
+    }
+
+    #[test]
+    fn test_span_mode() -> Result<()> {
+        let params = Parameters::default();
+        let max_width = params.max_width;
+        let pipeline = SpanPipeline::with_components(RegexSplitter::default(), HFTokenizer::from_file(TOKENIZER)?);
+        let spans = scripted();
+        let backend = MockBackend::new(move |inputs: &Tensors| span_logits(inputs, 2, max_width, &spans));
+        let model = GLiNER::with_backend(params, pipeline, backend);
+        // Processing
+        let output = model.inference(input()?)?;
+        // Assertions
+        check(&output);
+        // Everything rules
+        Ok(())
+    }
+
+    #[test]
+    fn test_chunked() -> Result<()> {
+        // windows of 3 words sharing 1 word: "My name is", "is James Bond"
+        let params = Parameters::default().with_max_length(Some(3));
+        let pipeline = ChunkedPipeline::new(TokenPipeline::with_components(RegexSplitter::default(), HFTokenizer::from_file(TOKENIZER)?), 1);
+        let backend = MockBackend::new(|inputs: &Tensors| token_logits(inputs, 1, &[ScriptedSpan::new(1, 1, 2, 0, 4.0)]));
+        let model = GLiNER::with_backend(params, pipeline, backend);
+        // Processing
+        let output = model.inference(TextInput::from_str(&["My name is James Bond"], &["person"])?)?;
+        // Assertions
+        assert_eq!(output.spans.len(), 1);
+        let spans = output.spans.get(0).unwrap();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans.get(0).unwrap().text(), "James Bond");
+        assert_eq!(spans.get(0).unwrap().offsets(), (11, 21));
+        // Everything rules
+        Ok(())
+    }
+
+    #[test]
+    fn test_class_thresholds() -> Result<()> {
+        // both spans have a probability of ~0.73, "vehicle" requires 0.8
+        let spans = vec![ScriptedSpan::new(0, 3, 4, 0, 1.0), ScriptedSpan::new(1, 5, 6, 1, 1.0)];
+        let params = Parameters::default().with_class_threshold("vehicle", 0.8);
+        let check = |output: &SpanOutput| {
+            assert_eq!(output.spans.get(0).unwrap().len(), 1);
+            assert_eq!(output.spans.get(1).unwrap().len(), 0);
+        };
+        // Span mode
+        let max_width = params.max_width;
+        let pipeline = SpanPipeline::with_components(RegexSplitter::default(), HFTokenizer::from_file(TOKENIZER)?);
+        let script = spans.clone();
+        let backend = MockBackend::new(move |inputs: &Tensors| span_logits(inputs, 2, max_width, &script));
+        check(&GLiNER::with_backend(params, pipeline, backend).inference(input()?)?);
+        // Token mode, with both decoders
+        let params = Parameters::default().with_class_threshold("vehicle", 0.8);
+        let pipeline = TokenPipeline::with_components(RegexSplitter::default(), HFTokenizer::from_file(TOKENIZER)?);
+        let decode = |flat: bool| -> Result<SpanOutput> {
+            let (inputs, context) = pipeline.tensor_pre_processor(&params).apply(input()?)?;
+            let output = TensorOutput::from(token_logits(&inputs, 2, &spans)?, context);
+            match flat {
+                false => token::TensorsToDecoded::new(params.threshold).with_class_thresholds(params.class_thresholds.clone()).apply(output),
+                true => token_flat::TensorsToDecoded::new(params.threshold).with_class_thresholds(params.class_thresholds.clone()).apply(output),
+            }
+        };
+        check(&decode(false)?);
+        check(&decode(true)?);
+        // the global threshold still applies to other classes
+        let params = Parameters::default().with_threshold(0.8).with_class_threshold("person", 0.7);
+        let (inputs, context) = pipeline.tensor_pre_processor(&params).apply(input()?)?;
+        let output = token::TensorsToDecoded::new(params.threshold).with_class_thresholds(params.class_thresholds.clone())
+            .apply(TensorOutput::from(token_logits(&inputs, 2, &spans)?, context))?;
+        check(&output);
+        // Everything rules
+        Ok(())
+    }
+
+    #[test]
+    fn test_shape_mismatch() -> Result<()> {
+        let pipeline = TokenPipeline::with_components(RegexSplitter::default(), HFTokenizer::from_file(TOKENIZER)?);
+        // wrong number of classes
+        let backend = MockBackend::new(|inputs: &Tensors| token_logits(inputs, 3, &[]));
+        let model = GLiNER::with_backend(Parameters::default(), pipeline, backend);
+        // Processing
+        let result = model.inference(input()?);
+        // Assertions
+        assert!(matches!(result, Err(GlinerError::ShapeMismatch { .. })));
+        // Everything rules
+        Ok(())
+    }
+}
@@ -0,0 +1,193 @@
+//! Inference backends, which actually run the model between pre- and post-processing.
+//!
+//! Pre-processing produces backend-agnostic tensors (see `Tensors`), which are passed to a `Backend`,
+//! which output tensors are then post-processed. The default backend is ONNX Runtime (see `onnxruntime`),
+//! but pipelines can be run by any backend, including a deterministic fake for testing (see `mock`), or a
+//! pure-Rust implementation (see `tract`, requires the `tract` feature).
+
+pub mod onnxruntime;
+pub mod mock;
+#[cfg(feature = "tract")]
+pub mod tract;
+
+use std::path::Path;
+use composable::Composable;
+use orp::params::RuntimeParameters;
+use ndarray::{Array, ArrayD, ArrayViewD, Dimension};
+use crate::util::{error::GlinerError, result::Result, profile::Traced};
+
+
+/// A tensor exchanged with a backend
+#[derive(Debug)]
+pub enum Tensor {
+    Int64(ArrayD<i64>),
+    Bool(ArrayD<bool>),
+    Float32(ArrayD<f32>),
+    /// `f32` tensor owned by ONNX Runtime, so that the outputs of the model (typically the logits, which are
+    /// the largest tensors) are decoded without being copied
+    OnnxFloat32(ort::value::Tensor<f32>),
+}
+
+impl Tensor {
+    pub fn shape(&self) -> Vec<i64> {
+        let shape = match self {
+            Self::Int64(a) => a.shape(),
+            Self::Bool(a) => a.shape(),
+            Self::Float32(a) => a.shape(),
+            Self::OnnxFloat32(t) => return t.extract_raw_tensor().0.to_vec(),
+        };
+        shape.iter().map(|d| *d as i64).collect()
+    }
+
+    /// View of a `i64` tensor
+    pub fn as_i64(&self) -> Result<ArrayViewD<'_, i64>> {
+        match self {
+            Self::Int64(a) => Ok(a.view()),
+            _ => Err(GlinerError::Schema("expected a i64 tensor".to_string()).into()),
+        }
+    }
+
+    /// View of a `f32` tensor
+    pub fn as_f32(&self) -> Result<ArrayViewD<'_, f32>> {
+        match self {
+            Self::Float32(a) => Ok(a.view()),
+            Self::OnnxFloat32(t) => Ok(t.extract_tensor()),
+            _ => Err(GlinerError::Schema("expected a f32 tensor".to_string()).into()),
+        }
+    }
+
+    /// Flat (row-major) view of a `f32` tensor
+    pub fn as_f32_slice(&self) -> Result<&[f32]> {
+        match self {
+            Self::Float32(a) => a.as_slice().ok_or(GlinerError::Schema("expected a contiguous tensor".to_string()).into()),
+            Self::OnnxFloat32(t) => Ok(t.extract_raw_tensor().1),
+            _ => Err(GlinerError::Schema("expected a f32 tensor".to_string()).into()),
+        }
+    }
+}
+
+/// Tensors owned by ONNX Runtime are copied
+impl Clone for Tensor {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Int64(a) => Self::Int64(a.clone()),
+            Self::Bool(a) => Self::Bool(a.clone()),
+            Self::Float32(a) => Self::Float32(a.clone()),
+            Self::OnnxFloat32(t) => Self::Float32(t.extract_tensor().to_owned()),
+        }
+    }
+}
+
+impl<D: Dimension> From<Array<i64, D>> for Tensor {
+    fn from(array: Array<i64, D>) -> Self {
+        Self::Int64(array.into_dyn())
+    }
+}
+
+impl<D: Dimension> From<Array<bool, D>> for Tensor {
+    fn from(array: Array<bool, D>) -> Self {
+        Self::Bool(array.into_dyn())
+    }
+}
+
+impl<D: Dimension> From<Array<f32, D>> for Tensor {
+    fn from(array: Array<f32, D>) -> Self {
+        Self::Float32(array.into_dyn())
+    }
+}
+
+
+/// Named tensors, in order
+#[derive(Debug, Clone, Default)]
+pub struct Tensors {
+    entries: Vec<(String, Tensor)>,
+}
+
+impl Tensors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds (or replaces) a tensor, builder-style
+    pub fn with(mut self, name: &str, tensor: impl Into<Tensor>) -> Self {
+        self.insert(name, tensor);
+        self
+    }
+
+    /// Adds (or replaces) a tensor
+    pub fn insert(&mut self, name: &str, tensor: impl Into<Tensor>) {
+        let tensor = tensor.into();
+        match self.entries.iter_mut().find(|(n, _)| n == name) {
+            Some(entry) => entry.1 = tensor,
+            None => self.entries.push((name.to_string(), tensor)),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Tensor> {
+        self.entries.iter().find(|(n, _)| n == name).map(|(_, t)| t)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|(n, _)| n.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl IntoIterator for Tensors {
+    type Item = (String, Tensor);
+    type IntoIter = std::vec::IntoIter<(String, Tensor)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+
+/// Runs a model on backend-agnostic tensors
+pub trait Backend {
+    fn run(&self, inputs: Tensors) -> Result<Tensors>;
+}
+
+
+/// Backend loading ONNX models from files, which can thus be selected when using the `GLiNER` constructors:
+///
+/// ```ignore
+/// let model = GLiNER::<TokenMode, TractBackend>::new(params, runtime_params, "tokenizer.json", "model.onnx")?;
+/// ```
+pub trait OnnxBackend: Backend + Sized {
+    fn load(path: &Path, runtime_params: RuntimeParameters) -> Result<Self>;
+}
+
+
+/// Backend-agnostic counterpart of `orp::pipeline::Pipeline`: pre-processing produces plain tensors to be
+/// passed to a `Backend`, and post-processing consumes the tensors it returns.
+///
+/// The pre-defined pipelines implement both traits, the `orp` one being derived from this one (see `onnxruntime`).
+pub trait TensorPipeline<'a> {
+    type Input;
+    type Output;
+    type Context;
+    type Parameters;
+
+    fn tensor_pre_processor(&self, params: &Self::Parameters) -> impl Composable<Self::Input, (Tensors, Self::Context)>;
+
+    fn tensor_post_processor(&self, params: &Self::Parameters) -> impl Composable<(Tensors, Self::Context), Self::Output>;
+}
+
+
+/// Performs inferences using the provided backend, pipeline and parameters
+pub fn inference<'a, P: TensorPipeline<'a>, B: Backend + ?Sized>(backend: &B, pipeline: &P, params: &P::Parameters, input: P::Input) -> Result<P::Output> {
+    // pre-process
+    let (tensors, context) = pipeline.tensor_pre_processor(params).apply(input)?;
+    // inference
+    let tensors = Traced::with_name("Backend::run", |tensors| backend.run(tensors)).apply(tensors)?;
+    // post-process
+    pipeline.tensor_post_processor(params).apply((tensors, context))
+}
@@ -0,0 +1,147 @@
+//! ONNX Runtime backend (through `orp`), and conversions between `Tensors` and `ort` values
+
+use std::borrow::Cow;
+use std::path::Path;
+use composable::Composable;
+use ort::session::{Session, SessionInputs, SessionInputValue, SessionOutputs, builder::GraphOptimizationLevel};
+use ort::tensor::TensorElementType;
+use ort::value::ValueType;
+use orp::model::Model;
+use orp::params::RuntimeParameters;
+use orp::pipeline::{Pipeline, PreProcessor, PostProcessor};
+use crate::util::{error::GlinerError, result::Result};
+use super::{Backend, OnnxBackend, Tensor, Tensors};
+
+
+/// Converts tensors to session inputs (the values are moved to ONNX Runtime)
+pub fn to_session_inputs<'a>(tensors: Tensors) -> Result<SessionInputs<'a, 'a>> {
+    let mut inputs: Vec<(Cow<'a, str>, SessionInputValue<'a>)> = Vec::with_capacity(tensors.len());
+    for (name, tensor) in tensors {
+        let value: SessionInputValue = match tensor {
+            Tensor::Int64(a) => ort::value::Tensor::from_array(a)?.into(),
+            Tensor::Bool(a) => ort::value::Tensor::from_array(a)?.into(),
+            Tensor::Float32(a) => ort::value::Tensor::from_array(a)?.into(),
+            Tensor::OnnxFloat32(t) => t.into(),
+        };
+        inputs.push((Cow::Owned(name), value));
+    }
+    Ok(inputs.into())
+}
+
+
+/// Converts the (tensor) session outputs: `f32` tensors are kept as is (see `Tensor::OnnxFloat32`), other ones
+/// are copied. Outputs of unsupported types are ignored.
+pub fn from_session_outputs(outputs: SessionOutputs) -> Result<Tensors> {
+    let mut tensors = Tensors::new();
+    for (name, value) in outputs {
+        match value.dtype() {
+            ValueType::Tensor { ty: TensorElementType::Float32, .. } => tensors.insert(name, Tensor::OnnxFloat32(value.downcast()?)),
+            ValueType::Tensor { ty: TensorElementType::Int64, .. } => tensors.insert(name, value.try_extract_tensor::<i64>()?.to_owned()),
+            ValueType::Tensor { ty: TensorElementType::Bool, .. } => tensors.insert(name, value.try_extract_tensor::<bool>()?.to_owned()),
+            _ => (),
+        }
+    }
+    Ok(tensors)
+}
+
+
+/// Composable: (Tensors, Context) => (SessionInputs, Context)
+#[derive(Default)]
+pub struct TensorsToSessionInputs { }
+
+impl<'a, C> Composable<(Tensors, C), (SessionInputs<'a, 'a>, C)> for TensorsToSessionInputs {
+    fn apply(&self, input: (Tensors, C)) -> Result<(SessionInputs<'a, 'a>, C)> {
+        Ok((to_session_inputs(input.0)?, input.1))
+    }
+}
+
+
+/// Composable: (SessionOutputs, Context) => (Tensors, Context)
+#[derive(Default)]
+pub struct SessionOutputsToTensors { }
+
+impl<'a, C> Composable<(SessionOutputs<'a, 'a>, C), (Tensors, C)> for SessionOutputsToTensors {
+    fn apply(&self, input: (SessionOutputs<'a, 'a>, C)) -> Result<(Tensors, C)> {
+        Ok((from_session_outputs(input.0)?, input.1))
+    }
+}
+
+
+/// Runs an `orp` model on plain tensors
+impl Backend for Model {
+    fn run(&self, inputs: Tensors) -> Result<Tensors> {
+        self.inference(inputs, &Passthrough, &())
+    }
+}
+
+impl OnnxBackend for Model {
+    fn load(path: &Path, runtime_params: RuntimeParameters) -> Result<Self> {
+        Model::new(path, runtime_params).map_err(load_error)
+    }
+}
+
+
+/// Runs a bare ONNX Runtime session, for example the one which was used to detect the mode of the model
+/// (see `AutoGLiNER`)
+impl Backend for Session {
+    fn run(&self, inputs: Tensors) -> Result<Tensors> {
+        let outputs = Session::run(self, to_session_inputs(inputs)?)?;
+        from_session_outputs(outputs)
+    }
+}
+
+impl OnnxBackend for Session {
+    fn load(path: &Path, runtime_params: RuntimeParameters) -> Result<Self> {
+        load_session(path, runtime_params)
+    }
+}
+
+
+/// Loads an ONNX Runtime session with the same options as `orp` models
+pub fn load_session<P: AsRef<Path>>(path: P, runtime_params: RuntimeParameters) -> Result<Session> {
+    let session = || Session::builder()?
+        .with_intra_threads(runtime_params.threads())?
+        .with_execution_providers(runtime_params.execution_providers().to_vec())?
+        .with_optimization_level(GraphOptimizationLevel::Level3)?
+        .commit_from_file(path);
+    Ok(session().map_err(GlinerError::Load)?)
+}
+
+
+/// Attributes the runtime failures of `orp` to the loading of the model (see `GlinerError::Load`)
+pub(crate) fn load_error(e: Box<dyn std::error::Error + Send + Sync>) -> Box<dyn std::error::Error + Send + Sync> {
+    GlinerError::from(e).at_load().into()
+}
+
+
+/// Trivial `orp` pipeline, only converting tensors
+struct Passthrough;
+
+impl<'a> Pipeline<'a> for Passthrough {
+    type Input = Tensors;
+    type Output = Tensors;
+    type Context = ();
+    type Parameters = ();
+
+    fn pre_processor(&self, _params: &()) -> impl PreProcessor<'a, Self::Input, Self::Context> {
+        PassthroughStep
+    }
+
+    fn post_processor(&self, _params: &()) -> impl PostProcessor<'a, Self::Output, Self::Context> {
+        PassthroughStep
+    }
+}
+
+struct PassthroughStep;
+
+impl<'a> Composable<Tensors, (SessionInputs<'a, 'a>, ())> for PassthroughStep {
+    fn apply(&self, input: Tensors) -> Result<(SessionInputs<'a, 'a>, ())> {
+        Ok((to_session_inputs(input)?, ()))
+    }
+}
+
+impl<'a> Composable<(SessionOutputs<'a, 'a>, ()), Tensors> for PassthroughStep {
+    fn apply(&self, input: (SessionOutputs<'a, 'a>, ())) -> Result<Tensors> {
+        from_session_outputs(input.0)
+    }
+}
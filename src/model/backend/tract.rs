@@ -0,0 +1,231 @@
+//! Pure-Rust backend based on [tract](https://github.com/sonos/tract) (requires the `tract` feature)
+//!
+//! It runs the same ONNX graphs as ONNX Runtime (see `doc/Model.md`), on CPU, without any native library.
+//! The graph is optimized once, when loaded: the dimensions of the inputs which are not fixed by the model
+//! (batch size, sequence length, number of spans...) are kept symbolic, and resolved at each run.
+
+use std::path::Path;
+use ndarray::{ArrayD, IxDyn};
+use orp::params::RuntimeParameters;
+use tract_onnx::prelude::{Framework, InferenceFact, InferenceModel, InferenceModelExt, TValue, TVec, TypedModel, TypedRunnableModel, DatumType, TDim, ToDim};
+use tract_onnx::tract_hir::infer::Factoid;
+use crate::util::{error::GlinerError, result::Result};
+use super::{Backend, OnnxBackend, Tensor, Tensors};
+
+
+/// Runs ONNX models using tract
+pub struct TractBackend {
+    plan: TypedRunnableModel<TypedModel>,
+    inputs: Vec<String>,
+    outputs: Vec<String>,
+}
+
+
+impl TractBackend {
+    /// Loads the given ONNX model
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_model(tract_onnx::onnx().model_for_path(path)?)
+    }
+
+    /// Uses an already loaded model
+    pub fn from_model(mut model: InferenceModel) -> Result<Self> {
+        // declared output shapes are inferred from the input ones
+        for outlet in model.output_outlets()?.to_vec() {
+            model.set_outlet_fact(outlet, InferenceFact::default())?;
+        }
+        let inputs: Vec<String> = model.input_outlets()?.iter()
+            .map(|outlet| model.node(outlet.node).name.clone())
+            .collect();
+        // input dimensions which are not declared (neither fixed nor named) get a symbol of their own
+        for (i, name) in inputs.iter().enumerate() {
+            let fact = model.input_fact(i)?.clone();
+            let datum_type = fact.datum_type.concretize()
+                .ok_or_else(|| GlinerError::Schema(format!("undeclared type for input '{name}'")))?;
+            if fact.shape.is_open() {
+                return Err(GlinerError::Schema(format!("undeclared rank for input '{name}'")).into());
+            }
+            let shape = fact.shape.dims()
+                .map(|dim| dim.concretize().unwrap_or_else(|| model.symbol_table.new_with_prefix("d").to_dim()))
+                .collect::<Vec<TDim>>();
+            model.set_input_fact(i, InferenceFact::dt_shape(datum_type, shape))?;
+        }
+        let outputs = model.output_outlets()?.iter()
+            .map(|outlet| model.outlet_label(*outlet).map(str::to_string).unwrap_or_else(|| model.node(outlet.node).name.clone()))
+            .collect();
+        let plan = model.into_optimized()?.into_runnable()?;
+        Ok(Self { plan, inputs, outputs })
+    }
+
+    /// Names of the model inputs
+    pub fn inputs(&self) -> &[String] {
+        &self.inputs
+    }
+
+    /// Names of the model outputs
+    pub fn outputs(&self) -> &[String] {
+        &self.outputs
+    }
+}
+
+
+impl Backend for TractBackend {
+    fn run(&self, inputs: Tensors) -> Result<Tensors> {
+        // gather the inputs expected by the model, in order (others are ignored)
+        let inputs = self.inputs.iter()
+            .map(|name| inputs.get(name).ok_or_else(|| GlinerError::Schema(format!("input '{name}' expected by the model is missing")).into()).and_then(to_tract))
+            .collect::<Result<Vec<_>>>()?;
+        let outputs = self.plan.run(inputs.into_iter().map(TValue::from).collect::<TVec<_>>())?;
+        let mut tensors = Tensors::new();
+        for (name, value) in self.outputs.iter().zip(outputs) {
+            if let Some(tensor) = from_tract(&value)? {
+                tensors.insert(name, tensor);
+            }
+        }
+        Ok(tensors)
+    }
+}
+
+
+/// Runtime parameters are ignored: tract runs the model within the calling thread, on CPU
+impl OnnxBackend for TractBackend {
+    fn load(path: &Path, _runtime_params: RuntimeParameters) -> Result<Self> {
+        Self::new(path)
+    }
+}
+
+
+fn to_tract(tensor: &Tensor) -> Result<tract_onnx::prelude::Tensor> {
+    Ok(match tensor {
+        Tensor::Int64(a) => tract_onnx::prelude::Tensor::from_shape(a.shape(), &a.as_standard_layout().iter().copied().collect::<Vec<_>>())?,
+        Tensor::Bool(a) => tract_onnx::prelude::Tensor::from_shape(a.shape(), &a.as_standard_layout().iter().copied().collect::<Vec<_>>())?,
+        Tensor::Float32(_) | Tensor::OnnxFloat32(_) => {
+            let a = tensor.as_f32()?;
+            tract_onnx::prelude::Tensor::from_shape(a.shape(), &a.as_standard_layout().iter().copied().collect::<Vec<_>>())?
+        },
+    })
+}
+
+
+/// Copies a tract tensor. Tensors of unsupported types are ignored.
+fn from_tract(tensor: &tract_onnx::prelude::Tensor) -> Result<Option<Tensor>> {
+    let shape = IxDyn(tensor.shape());
+    Ok(match tensor.datum_type() {
+        DatumType::F32 => Some(ArrayD::from_shape_vec(shape, tensor.as_slice::<f32>()?.to_vec())?.into()),
+        DatumType::I64 => Some(ArrayD::from_shape_vec(shape, tensor.as_slice::<i64>()?.to_vec())?.into()),
+        DatumType::Bool => Some(ArrayD::from_shape_vec(shape, tensor.as_slice::<bool>()?.to_vec())?.into()),
+        _ => None,
+    })
+}
+
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    // Silent some clippy warnings for unit tests
+    #![allow(clippy::get_first)]
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use tract_onnx::pb;
+    use crate::model::{GLiNER, params::Parameters, input::text::TextInput};
+    use crate::model::pipeline::{span::SpanMode, token::TokenMode};
+
+    /// ONNX graph casting `input_ids` (with symbolic dimensions) to `f32`, as `logits`
+    fn cast_model() -> Result<InferenceModel> {
+        let value_info = |name: &str, elem_type: pb::tensor_proto::DataType| pb::ValueInfoProto {
+            name: name.to_string(),
+            r#type: Some(pb::TypeProto {
+                value: Some(pb::type_proto::Value::TensorType(pb::type_proto::Tensor {
+                    elem_type: elem_type as i32,
+                    shape: Some(pb::TensorShapeProto {
+                        dim: ["batch_size", "sequence_length"].iter().map(|d| pb::tensor_shape_proto::Dimension {
+                            value: Some(pb::tensor_shape_proto::dimension::Value::DimParam(d.to_string())),
+                            ..Default::default()
+                        }).collect(),
+                    }),
+                })),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let proto = pb::ModelProto {
+            ir_version: 8,
+            opset_import: vec![pb::OperatorSetIdProto { domain: String::new(), version: 17 }],
+            graph: Some(pb::GraphProto {
+                name: "cast".to_string(),
+                node: vec![pb::NodeProto {
+                    name: "cast".to_string(),
+                    op_type: "Cast".to_string(),
+                    input: vec!["input_ids".to_string()],
+                    output: vec!["logits".to_string()],
+                    attribute: vec![pb::AttributeProto {
+                        name: "to".to_string(),
+                        r#type: pb::attribute_proto::AttributeType::Int as i32,
+                        i: pb::tensor_proto::DataType::Float as i64,
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                }],
+                input: vec![value_info("input_ids", pb::tensor_proto::DataType::Int64)],
+                output: vec![value_info("logits", pb::tensor_proto::DataType::Float)],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        Ok(tract_onnx::onnx().model_for_proto_model(&proto)?)
+    }
+
+    #[test]
+    fn test_run() -> Result<()> {
+        let backend = TractBackend::from_model(cast_model()?)?;
+        assert_eq!(backend.inputs(), &["input_ids"]);
+        assert_eq!(backend.outputs(), &["logits"]);
+        for (batch_size, length) in [(2, 3), (1, 5), (2, 3)] {
+            // Processing (inputs which are not expected by the model are ignored)
+            let input_ids = ndarray::Array::from_shape_fn((batch_size, length), |(i, j)| (i * 10 + j) as i64);
+            let inputs = Tensors::new()
+                .with("input_ids", input_ids)
+                .with("span_mask", ndarray::Array::from_elem((batch_size, 1), true));
+            let outputs = backend.run(inputs)?;
+            // Assertions
+            let logits = outputs.get("logits").unwrap();
+            assert_eq!(logits.shape(), vec![batch_size as i64, length as i64]);
+            assert_eq!(logits.as_f32()?[[batch_size - 1, length - 1]], ((batch_size - 1) * 10 + length - 1) as f32);
+        }
+        // Everything rules
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_input() -> Result<()> {
+        let backend = TractBackend::from_model(cast_model()?)?;
+        let result = backend.run(Tensors::new());
+        assert!(matches!(result.map_err(GlinerError::from), Err(GlinerError::Schema(_))));
+        Ok(())
+    }
+
+    /// Outputs of the tract backend must match the ONNX Runtime ones (requires ONNX Runtime and the models)
+    #[test]
+    #[ignore]
+    fn test_parity() -> Result<()> {
+        let input = || TextInput::from_str(
+            &["I am James Bond", "This is James and I live in Chelsea, London.", "My name is Bond, James Bond.", "I like to drive my Aston Martin.", "The villain in the movie is Auric Goldfinger."],
+            &["person", "location", "vehicle"],
+        );
+        let span_ort = GLiNER::<SpanMode>::new(Parameters::default(), RuntimeParameters::default(), "models/gliner_small-v2.1/tokenizer.json", "models/gliner_small-v2.1/onnx/model.onnx")?;
+        let span_tract = GLiNER::<SpanMode, TractBackend>::new(Parameters::default(), RuntimeParameters::default(), "models/gliner_small-v2.1/tokenizer.json", "models/gliner_small-v2.1/onnx/model.onnx")?;
+        let token_ort = GLiNER::<TokenMode>::new(Parameters::default(), RuntimeParameters::default(), "models/gliner-multitask-large-v0.5/tokenizer.json", "models/gliner-multitask-large-v0.5/onnx/model.onnx")?;
+        let token_tract = GLiNER::<TokenMode, TractBackend>::new(Parameters::default(), RuntimeParameters::default(), "models/gliner-multitask-large-v0.5/tokenizer.json", "models/gliner-multitask-large-v0.5/onnx/model.onnx")?;
+        for (expected, actual) in [(span_ort.inference(input()?)?, span_tract.inference(input()?)?), (token_ort.inference(input()?)?, token_tract.inference(input()?)?)] {
+            assert_eq!(expected.spans.len(), actual.spans.len());
+            for (expected, actual) in expected.spans.iter().zip(&actual.spans) {
+                assert_eq!(expected.len(), actual.len());
+                for (expected, actual) in expected.iter().zip(actual) {
+                    assert_eq!(expected.offsets(), actual.offsets());
+                    assert_eq!(expected.class(), actual.class());
+                    assert!((expected.probability() - actual.probability()).abs() < 1e-4);
+                }
+            }
+        }
+        Ok(())
+    }
+}
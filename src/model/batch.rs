@@ -0,0 +1,236 @@
+//! Length-bucketed micro-batching
+
+use std::collections::{HashMap, HashSet};
+use crate::util::{error::IndexError, result::{Result, GlinerResult}};
+use crate::text::{span::Span, splitter::Splitter, tokenizer::Tokenizer};
+use super::GLiNER;
+use super::backend::{Backend, TensorPipeline};
+use super::params::Parameters;
+use super::pipeline::Components;
+use super::input::text::TextInput;
+use super::output::decoded::SpanOutput;
+
+
+/// Micro-batching for NER pipelines (including chunked ones)
+impl<'a, P, B> GLiNER<P, B>
+where
+    P: TensorPipeline<'a, Input = TextInput, Output = SpanOutput, Parameters = Parameters> + Components,
+    B: Backend,
+{
+    /// Same as `inference()`, but the texts are first sorted by length and grouped into buckets, each bucket
+    /// being processed as a distinct batch. Since tensors are padded to the longest prompt of each batch,
+    /// this avoids a few long texts blowing up the memory and time required to process many short ones.
+    ///
+    /// The `budget` bounds the number of sub-word tokens of each bucket once padded, that is the number
+    /// of texts times the length of the longest prompt (see `prompt_lengths()` and `buckets()`). Results are
+    /// reassembled in the original order, so the output is the same as with `inference()`.
+    ///
+    /// Lengths are measured on the raw texts, so the budget is only an estimate for wrapped pipelines: a chunked
+    /// pipeline processes several chunks for each long text, and a normalized one processes the texts once
+    /// normalized (e.g. the visible text of HTML documents).
+    pub fn inference_bucketed(&'a self, input: TextInput, budget: usize) -> GlinerResult<SpanOutput> {
+        let lengths = prompt_lengths(&input, &self.pipeline, self.params.max_length)?;
+        let buckets = buckets(&lengths, budget);
+        let mut outputs = Vec::with_capacity(buckets.len());
+        for bucket in &buckets {
+            let texts = bucket.iter()
+                .map(|i| input.texts.get(*i).cloned().ok_or(IndexError::new("input.texts", *i)))
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            outputs.push(self.inference(TextInput::new(texts, input.entities.clone())?)?);
+        }
+        Ok(reassemble(input, &buckets, outputs)?)
+    }
+}
+
+
+/// Number of sub-word tokens of the prompt built by the given pipeline for each text: the start and end
+/// tokens, the entity labels with their markers (see `PromptInput`), and the words of the text (up to
+/// `max_length`), each word being encoded on its own (see `EncodedInput`).
+///
+/// Note that the raw texts are measured: for a chunked pipeline, this is the length of the first chunk of each
+/// text, and for a normalized pipeline, the length of the texts before normalization.
+pub fn prompt_lengths(input: &TextInput, pipeline: &impl Components, max_length: Option<usize>) -> Result<Vec<usize>> {
+    let format = pipeline.prompt_format();
+    let words = input.texts.iter()
+        .map(|text| pipeline.splitter().split(text, max_length))
+        .collect::<Result<Vec<_>>>()?;
+
+    // encode each distinct word once
+    let mut distinct: HashSet<&str> = HashSet::from([format.ent_token.as_str(), format.sep_token.as_str()]);
+    distinct.extend(input.entities.iter().map(String::as_str));
+    distinct.extend(words.iter().flatten().map(|token| token.text()));
+    let distinct: Vec<&str> = distinct.into_iter().collect();
+    let encodings = pipeline.tokenizer().encode_batch(&distinct)?;
+    let lengths: HashMap<&str, usize> = distinct.into_iter().zip(encodings.iter().map(Vec::len)).collect();
+    let length = |word: &str| lengths.get(word).copied().ok_or(IndexError::with("missing word encoding"));
+
+    // the entities part is the same for all prompts
+    let mut entities_length = 2 + length(&format.sep_token)?;
+    for entity in &input.entities {
+        entities_length += length(&format.ent_token)? + length(entity)?;
+    }
+    words.iter()
+        .map(|tokens| tokens.iter().try_fold(entities_length, |total, token| Ok(total + length(token.text())?)))
+        .collect()
+}
+
+
+/// Groups the indexes of the given lengths into buckets of similar lengths, so that for each bucket the
+/// number of elements times the maximum length does not exceed `budget`. An element which is longer than
+/// the budget by itself gets a bucket of its own. Buckets are given by increasing lengths.
+pub fn buckets(lengths: &[usize], budget: usize) -> Vec<Vec<usize>> {
+    let mut sorted: Vec<(usize, usize)> = lengths.iter().copied().enumerate().map(|(i, l)| (l, i)).collect();
+    sorted.sort_unstable();
+    let mut result = Vec::new();
+    let mut current: Vec<usize> = Vec::new();
+    for (length, index) in sorted {
+        // lengths are increasing, so the current one is the maximum of the bucket
+        if !current.is_empty() && (current.len() + 1) * length > budget {
+            result.push(std::mem::take(&mut current));
+        }
+        current.push(index);
+    }
+    if !current.is_empty() {
+        result.push(current);
+    }
+    result
+}
+
+
+/// Puts the spans of each bucket back in the original order, with the original sequence indexes
+fn reassemble(input: TextInput, buckets: &[Vec<usize>], outputs: Vec<SpanOutput>) -> Result<SpanOutput> {
+    let mut spans: Vec<Option<Vec<Span>>> = std::iter::repeat_with(|| None).take(input.texts.len()).collect();
+    for (bucket, output) in buckets.iter().zip(outputs) {
+        for (k, bucket_spans) in output.spans.into_iter().enumerate() {
+            let sequence = *bucket.get(k).ok_or(IndexError::new("bucket", k))?;
+            let slot = spans.get_mut(sequence).ok_or(IndexError::new("spans", sequence))?;
+            *slot = Some(bucket_spans.into_iter().map(|span| span.with_sequence(sequence)).collect());
+        }
+    }
+    let spans = spans.into_iter().enumerate()
+        .map(|(i, s)| s.ok_or(IndexError::new("bucketed outputs", i)))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(SpanOutput::new(input.texts, input.entities, spans))
+}
+
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    // Silent some clippy warnings for unit tests
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use composable::Composable;
+    use crate::model::backend::{Tensors, mock::{MockBackend, ScriptedSpan, token_logits}};
+    use crate::model::pipeline::token::TokenPipeline;
+    use crate::text::{splitter::RegexSplitter, tokenizer::HFTokenizer, offsets::OffsetUnit};
+
+    #[test]
+    fn test_buckets() -> Result<()> {
+        let lengths = [5, 50, 3, 4, 6, 200, 5];
+        // Processing
+        let buckets = buckets(&lengths, 20);
+        // Assertions
+        assert_eq!(buckets, vec![vec![2, 3, 0, 6], vec![4], vec![1], vec![5]]);
+        // Everything rules
+        Ok(())
+    }
+
+    #[test]
+    fn test_prompt_lengths() -> Result<()> {
+        let pipeline = TokenPipeline::with_components(RegexSplitter::default(), HFTokenizer::from_file("data/tokenizer-tiny.json")?);
+        // "Aston-Martin" is a single word, but three sub-word tokens, as well as the "movie character" label with its marker
+        let input = TextInput::from_str(&["I like Aston-Martin", "Bond"], &["person", "movie character"])?;
+        // Processing
+        let lengths = prompt_lengths(&input, &pipeline, None)?;
+        // Assertions
+        assert_eq!(lengths, vec![13, 9]);
+        assert_eq!(prompt_lengths(&input, &pipeline, Some(2))?, vec![10, 9]);
+        for (text, length) in input.texts.iter().zip(lengths) {
+            let (tensors, _) = pipeline.tensor_pre_processor(&Parameters::default()).apply(TextInput::new(vec![text.clone()], input.entities.clone())?)?;
+            assert_eq!(tensors.get("input_ids").unwrap().as_i64()?.shape(), &[1, length]);
+        }
+        // Everything rules
+        Ok(())
+    }
+
+    #[test]
+    fn test_inference_bucketed() -> Result<()> {
+        // the first word of each text is an entity, whatever the bucket it belongs to
+        let pipeline = TokenPipeline::with_components(RegexSplitter::default(), HFTokenizer::from_file("data/tokenizer-tiny.json")?);
+        let backend = MockBackend::new(|inputs: &Tensors| {
+            let batch_size = inputs.get("input_ids").ok_or(IndexError::with("input_ids"))?.as_i64()?.shape().first().copied().unwrap_or(0);
+            let spans: Vec<ScriptedSpan> = (0..batch_size).map(|i| ScriptedSpan::new(i, 0, 0, 0, 5.0)).collect();
+            token_logits(inputs, 1, &spans)
+        });
+        let model = GLiNER::with_backend(Parameters::default(), pipeline, backend);
+        let texts = ["My name is James Bond", "Bond", "I like to drive the Aston Martin in London", "James", "The name of James"];
+        let input = || TextInput::from_str(&texts, &["person"]);
+        // Processing
+        let output = model.inference_bucketed(input()?, 24)?;
+        // Assertions
+        assert_eq!(buckets(&prompt_lengths(&input()?, model.pipeline(), None)?, 24).len(), 3);
+        assert_eq!(output.texts, texts);
+        assert_eq!(output.spans.len(), texts.len());
+        for (i, (spans, text)) in output.spans.iter().zip(texts).enumerate() {
+            assert_eq!(spans.len(), 1);
+            assert_eq!(spans.first().unwrap().sequence(), i);
+            assert_eq!(spans.first().unwrap().text(), text.split(' ').next().unwrap());
+        }
+        let expected = model.inference(input()?)?;
+        for (spans, expected) in output.spans.iter().zip(&expected.spans) {
+            assert_eq!(spans.first().unwrap().offsets(), expected.first().unwrap().offsets());
+        }
+        // Everything rules
+        Ok(())
+    }
+
+    #[test]
+    fn test_inference_bucketed_unit() -> Result<()> {
+        // offsets in other units are kept along the reassembly
+        let pipeline = TokenPipeline::with_components(RegexSplitter::default(), HFTokenizer::from_file("data/tokenizer-tiny.json")?);
+        let backend = MockBackend::new(|inputs: &Tensors| token_logits(inputs, 1, &[ScriptedSpan::new(0, 1, 1, 0, 5.0)]));
+        let model = GLiNER::with_backend(Parameters::default().with_offset_unit(OffsetUnit::Utf16), pipeline, backend);
+        let texts = ["🎬 Bond", "🎬 James and Bond"];
+        // Processing
+        let output = model.inference_bucketed(TextInput::from_str(&texts, &["person"])?, 8)?;
+        // Assertions
+        let expected = [(3, 7), (3, 8)];
+        for (spans, (start, end)) in output.spans.iter().zip(expected) {
+            let offsets = spans.first().unwrap().unit_offsets().unwrap();
+            assert_eq!((offsets.unit, offsets.start, offsets.end), (OffsetUnit::Utf16, start, end));
+        }
+        // Everything rules
+        Ok(())
+    }
+
+    #[test]
+    fn test_reassemble() -> Result<()> {
+        #![allow(clippy::get_first)]
+        let input = TextInput::from_str(&["a long text about Bond", "Bond", "M"], &["person"])?;
+        let buckets = vec![vec![1, 2], vec![0]];
+        let person = || "person".to_string();
+        let outputs = vec![
+            SpanOutput::new(vec!["Bond".into(), "M".into()], vec![person()], vec![
+                vec![Span::new(0, 0, 4, "Bond".into(), person(), 0.9)],
+                vec![Span::new(1, 0, 1, "M".into(), person(), 0.8)],
+            ]),
+            SpanOutput::new(vec!["a long text about Bond".into()], vec![person()], vec![
+                vec![Span::new(0, 18, 22, "Bond".into(), person(), 0.7)],
+            ]),
+        ];
+        // Processing
+        let output = reassemble(input, &buckets, outputs)?;
+        // Assertions
+        assert_eq!(output.texts.len(), 3);
+        assert_eq!(output.texts.get(0).unwrap(), "a long text about Bond");
+        for (i, spans) in output.spans.iter().enumerate() {
+            assert_eq!(spans.len(), 1);
+            assert_eq!(spans.get(0).unwrap().sequence(), i);
+        }
+        assert_eq!(output.spans.get(0).unwrap().get(0).unwrap().offsets(), (18, 22));
+        assert_eq!(output.spans.get(2).unwrap().get(0).unwrap().text(), "M");
+        // Everything rules
+        Ok(())
+    }
+}
@@ -0,0 +1,178 @@
+//! Model configuration (`gliner_config.json`) and model directories
+
+use std::path::{Path, PathBuf};
+use crate::util::{error::GlinerError, result::Result};
+use crate::text::{splitter::RegexSplitter, prompt::PromptFormat};
+use super::auto::Mode;
+use super::params::Parameters;
+
+
+/// Name of the configuration file in a model directory
+pub const CONFIG_FILE: &str = "gliner_config.json";
+/// Name of the tokenizer file in a model directory
+pub const TOKENIZER_FILE: &str = "tokenizer.json";
+/// Candidate locations of the ONNX model in a model directory (in order of preference)
+pub const MODEL_FILES: [&str; 2] = ["onnx/model.onnx", "model.onnx"];
+
+
+/// Subset of the GLiNER configuration (as found in `gliner_config.json`) which is relevant for inference
+#[derive(Debug, Clone)]
+pub struct ModelConfig {
+    /// Maximum span width (`max_width`)
+    pub max_width: usize,
+    /// Maximum number of words (`max_len`)
+    pub max_length: usize,
+    /// Span mode (`span_mode`), `token_level` for token mode
+    pub span_mode: String,
+    /// Word-level splitter (`words_splitter_type`)
+    pub words_splitter_type: String,
+    /// Entity marker (`ent_token`)
+    pub ent_token: String,
+    /// Separator marker (`sep_token`)
+    pub sep_token: String,
+}
+
+
+impl ModelConfig {
+
+    /// Reads the configuration from the given file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let json = std::fs::read_to_string(&path).map_err(|e| std::io::Error::new(e.kind(), format!("{}: {e}", path.as_ref().display())))?;
+        Self::from_json(&json)
+    }
+
+    /// Reads the configuration from a JSON string. Missing entries are set to the defaults of the original implementation.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let value: serde_json::Value = serde_json::from_str(json).map_err(|e| GlinerError::InvalidConfiguration(e.to_string()))?;
+        let usize_or = |key: &str, default: usize| -> Result<usize> {
+            match value.get(key) {
+                None | Some(serde_json::Value::Null) => Ok(default),
+                Some(v) => v.as_u64().map(|v| v as usize).ok_or(GlinerError::InvalidConfiguration(format!("{key} should be an unsigned integer")).into()),
+            }
+        };
+        let string_or = |key: &str, default: &str| -> Result<String> {
+            match value.get(key) {
+                None | Some(serde_json::Value::Null) => Ok(default.to_string()),
+                Some(v) => v.as_str().map(str::to_string).ok_or(GlinerError::InvalidConfiguration(format!("{key} should be a string")).into()),
+            }
+        };
+        Ok(Self {
+            max_width: usize_or("max_width", 12)?,
+            max_length: usize_or("max_len", 384)?,
+            span_mode: string_or("span_mode", "markerV0")?,
+            words_splitter_type: string_or("words_splitter_type", "whitespace")?,
+            ent_token: string_or("ent_token", "<<ENT>>")?,
+            sep_token: string_or("sep_token", "<<SEP>>")?,
+        })
+    }
+
+    /// Mode of the model: token mode for `token_level`, span mode otherwise
+    pub fn mode(&self) -> Mode {
+        if self.span_mode == "token_level" { Mode::Token } else { Mode::Span }
+    }
+
+    /// Checks that the configured mode is the expected one
+    pub fn check_mode(&self, expected: Mode) -> Result<()> {
+        if self.mode() != expected {
+            Err(GlinerError::InvalidConfiguration(format!("expected {expected:?} mode, but span_mode is {}", self.span_mode)).into())
+        }
+        else {
+            Ok(())
+        }
+    }
+
+    /// Builds the word-level splitter corresponding to `words_splitter_type` (only `whitespace` is supported so far)
+    pub fn splitter(&self) -> Result<RegexSplitter> {
+        match self.words_splitter_type.as_str() {
+            "whitespace" => Ok(RegexSplitter::default()),
+            other => Err(GlinerError::InvalidConfiguration(format!("unsupported words splitter type: {other}")).into()),
+        }
+    }
+
+    /// Markers used to build the prompts
+    pub fn prompt_format(&self) -> PromptFormat {
+        PromptFormat::new(&self.ent_token, &self.sep_token)
+    }
+
+    /// Returns the given parameters, with the model-dependent ones (`max_width` and `max_length`) set according to this configuration
+    pub fn parameters(&self, params: Parameters) -> Parameters {
+        params
+            .with_max_width(self.max_width)
+            .with_max_length(Some(self.max_length))
+    }
+}
+
+
+/// Locations of the files of a model directory, as found in Hugging Face GLiNER repositories:
+///
+/// ```text
+/// gliner_config.json
+/// tokenizer.json
+/// onnx/model.onnx (or model.onnx)
+/// ```
+pub struct ModelDir {
+    pub config: PathBuf,
+    pub tokenizer: PathBuf,
+    pub model: PathBuf,
+}
+
+
+impl ModelDir {
+    /// Locates the expected files in the given directory
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let existing = |file: &str| -> Result<PathBuf> {
+            let file = path.join(file);
+            if file.is_file() { Ok(file) } else { Err(std::io::Error::new(std::io::ErrorKind::NotFound, format!("file not found: {}", file.display())).into()) }
+        };
+        Ok(Self {
+            config: existing(CONFIG_FILE)?,
+            tokenizer: existing(TOKENIZER_FILE)?,
+            model: MODEL_FILES.iter().find_map(|f| existing(f).ok()).ok_or(std::io::Error::new(std::io::ErrorKind::NotFound, format!("no ONNX model found in {}", path.display())))?,
+        })
+    }
+
+    /// Reads the configuration
+    pub fn config(&self) -> Result<ModelConfig> {
+        ModelConfig::from_file(&self.config)
+    }
+}
+
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() -> Result<()> {
+        let config = ModelConfig::from_json(r#"{
+            "model_name": "microsoft/deberta-v3-small",
+            "max_width": 12,
+            "max_len": 384,
+            "span_mode": "markerV0",
+            "words_splitter_type": "whitespace",
+            "ent_token": "<<ENT>>",
+            "sep_token": "<<SEP>>"
+        }"#)?;
+        assert_eq!(config.mode(), Mode::Span);
+        assert!(config.check_mode(Mode::Token).is_err());
+        assert!(config.splitter().is_ok());
+        let params = config.parameters(Parameters::default().with_threshold(0.3));
+        assert_eq!(params.max_width, 12);
+        assert_eq!(params.max_length, Some(384));
+        assert_eq!(params.threshold, 0.3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_token_level() -> Result<()> {
+        let config = ModelConfig::from_json(r#"{ "span_mode": "token_level", "max_len": 512, "words_splitter_type": "spacy" }"#)?;
+        assert_eq!(config.mode(), Mode::Token);
+        assert_eq!(config.max_length, 512);
+        assert_eq!(config.max_width, 12);
+        assert!(config.splitter().is_err());
+        assert!(ModelConfig::from_json(r#"{ "max_len": "foo" }"#).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,154 @@
+use composable::Composable;
+use crate::util::{error::{IndexError, GlinerError}, result::Result};
+use crate::text::splitter::Splitter;
+use crate::model::pipeline::context::ChunkContext;
+use super::text::TextInput;
+
+
+/// Represents the input once long texts have been split into overlapping chunks (aka windows).
+///
+/// The chunks are provided as a regular `TextInput` so that they can be processed by any NER pipeline,
+/// while the context keeps track of where each chunk comes from.
+pub struct ChunkedInput {
+    /// Chunks, as a regular text input
+    pub input: TextInput,
+    /// Mapping from chunks to original texts
+    pub context: ChunkContext,
+}
+
+
+impl ChunkedInput {
+
+    /// Splits each text into chunks of at most `window` words, two consecutive chunks of
+    /// the same text sharing `overlap` words. Texts that do not exceed the window size are
+    /// kept as-is, and no chunking is performed at all if `window` is `None`.
+    pub fn from(input: TextInput, splitter: &impl Splitter, window: Option<usize>, overlap: usize) -> Result<Self> {
+        // no window: each text is its own chunk
+        let window = match window {
+            Some(window) => window,
+            None => return Ok(Self::identity(input)),
+        };
+
+        // check that the windows are actually moving forward
+        if overlap >= window {
+            return Err(GlinerError::InvalidConfiguration(format!("chunk overlap ({overlap}) must be smaller than window ({window})")).into());
+        }
+        let stride = window - overlap;
+
+        let mut texts = Vec::with_capacity(input.texts.len());
+        let mut chunks = Vec::with_capacity(input.texts.len());
+
+        for (text_id, text) in input.texts.iter().enumerate() {
+            // split the whole text into words (without limit)
+            let tokens = splitter.split(text, None)?;
+
+            // short enough: keep the text as a single chunk
+            if tokens.len() <= window {
+                texts.push(text.clone());
+                chunks.push((text_id, 0));
+                continue;
+            }
+
+            // slide the window over the words
+            let mut first = 0;
+            loop {
+                let last = std::cmp::min(first + window, tokens.len()) - 1;
+                let start = tokens.get(first).ok_or(IndexError::new("tokens", first))?.start();
+                let end = tokens.get(last).ok_or(IndexError::new("tokens", last))?.end();
+                texts.push(text[start..end].to_string());
+                chunks.push((text_id, start));
+                if last + 1 >= tokens.len() {
+                    break;
+                }
+                first += stride;
+            }
+        }
+
+        Ok(Self {
+            input: TextInput::new(texts, input.entities)?,
+            context: ChunkContext { texts: input.texts, chunks },
+        })
+    }
+
+    /// One chunk per text
+    fn identity(input: TextInput) -> Self {
+        let chunks = (0..input.texts.len()).map(|text_id| (text_id, 0)).collect();
+        Self {
+            context: ChunkContext { texts: input.texts.clone(), chunks },
+            input,
+        }
+    }
+}
+
+
+/// Composable: Text => (Text, ChunkContext)
+pub struct RawToChunked<'a, S> {
+    splitter: &'a S,
+    window: Option<usize>,
+    overlap: usize,
+}
+
+impl<'a, S> RawToChunked<'a, S> {
+    pub fn new(splitter: &'a S, window: Option<usize>, overlap: usize) -> Self {
+        Self {
+            splitter,
+            window,
+            overlap,
+        }
+    }
+}
+
+impl<S: Splitter> Composable<TextInput, (TextInput, ChunkContext)> for RawToChunked<'_, S> {
+    fn apply(&self, input: TextInput) -> Result<(TextInput, ChunkContext)> {
+        let chunked = ChunkedInput::from(input, self.splitter, self.window, self.overlap)?;
+        Ok((chunked.input, chunked.context))
+    }
+}
+
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() -> Result<()> {
+        // Silent some clippy warnings for unit tests
+        #![allow(clippy::get_first)]
+        #![allow(clippy::unwrap_used)]
+        // Processing
+        let splitter = crate::text::splitter::RegexSplitter::default();
+        let batch = [ "w1 w2 w3 w4 w5 w6 w7 w8 w9 w10", "short one" ];
+        let entities = [ "person" ];
+        let input = TextInput::from_str(&batch, &entities)?;
+        let chunked = ChunkedInput::from(input, &splitter, Some(4), 1)?;
+        // Assertions
+        assert_eq!(chunked.input.texts, vec!["w1 w2 w3 w4", "w4 w5 w6 w7", "w7 w8 w9 w10", "short one"]);
+        assert_eq!(chunked.context.chunks, vec![(0, 0), (0, 9), (0, 18), (1, 0)]);
+        assert_eq!(chunked.context.texts.len(), 2);
+        for (chunk, (text_id, offset)) in chunked.input.texts.iter().zip(&chunked.context.chunks) {
+            let text = chunked.context.texts.get(*text_id).unwrap();
+            assert_eq!(&text[*offset..*offset + chunk.len()], chunk);
+        }
+        // Everything rules
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_window() -> Result<()> {
+        let splitter = crate::text::splitter::RegexSplitter::default();
+        let input = TextInput::from_str(&[ "w1 w2 w3 w4 w5 w6" ], &[ "person" ])?;
+        let chunked = ChunkedInput::from(input, &splitter, None, 1)?;
+        assert_eq!(chunked.input.texts, vec!["w1 w2 w3 w4 w5 w6"]);
+        assert_eq!(chunked.context.chunks, vec![(0, 0)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_overlap() -> Result<()> {
+        let splitter = crate::text::splitter::RegexSplitter::default();
+        let input = TextInput::from_str(&[ "w1 w2 w3 w4 w5 w6" ], &[ "person" ])?;
+        assert!(ChunkedInput::from(input, &splitter, Some(3), 3).is_err());
+        Ok(())
+    }
+}
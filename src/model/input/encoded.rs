@@ -1,6 +1,8 @@
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
 use composable::Composable;
-use crate::util::result::Result;
-use crate::text::{token::Token, tokenizer::Tokenizer};
+use crate::util::{error::{GlinerError, IndexError}, result::Result};
+use crate::text::{token::Token, tokenizer::Tokenizer, prompt::Prompt};
 use super::prompt::PromptInput;
 use ndarray::{Array, Array2, ArrayView};
 
@@ -18,9 +20,9 @@ pub struct EncodedInput {
 }
 
 /// Utility struct
-struct EncodedPrompt {
+struct EncodedPrompt<'a> {
     /// encodings of each word
-    encoding: Vec<Vec<u32>>,
+    encoding: Vec<&'a [u32]>,
     /// offset of the first token of the actual text (beside entity labels)
     text_offset: usize,
 }
@@ -28,9 +30,11 @@ struct EncodedPrompt {
 impl EncodedInput {
 
     // Each word of each prompt is encoded *one by one*. So each word generates an encoding as 
-    // a Vec<u32> (sub-word tokenization). So for each prompt we get a Vec<Vec<u32>> (which is 
-    // stored in the 'encoding' field).
+    // a Vec<u32> (sub-word tokenization). So for each prompt we get a sequence of encodings (which 
+    // is stored in the 'encoding' field).
     pub fn from(input: PromptInput, tokenizer: &impl Tokenizer) -> Result<Self> {
+        // encode each distinct word once (see below)
+        let words = Self::encode_words(&input.prompts, tokenizer)?;
         // prepare the result vector
         let mut encodings: Vec<EncodedPrompt> = Vec::with_capacity(input.prompts.len());
         // maximum number of sub-word tokens found in one prompt (will be the width of the input tensor)
@@ -38,15 +42,15 @@ impl EncodedInput {
         // process each prompt
         for prompt in &input.prompts {
             // resulting sequence of encodings for each word of the current prompt
-            let mut prompt_tokens: Vec<Vec<u32>> = Vec::with_capacity(prompt.tokens().len());
+            let mut prompt_tokens: Vec<&[u32]> = Vec::with_capacity(prompt.tokens().len());
             // total number of sub-word tokens for the current prompt (adding 2 for initial and terminal tokens)
             let mut total_tokens: usize = 2;
             // number of sub-word tokens for the entities part only (before the actual text)
             let mut total_entity_tokens = 0;
-            // encode each token of the current prompt
+            // retrieve the encoding of each token of the current prompt
             for (pos, word) in prompt.tokens().iter().enumerate() {
-                // actually encode the word
-                let encoding = tokenizer.encode(word)?;
+                // get the encoding of the word
+                let encoding = words.get(word.as_str()).ok_or(IndexError::with("missing word encoding"))?;
                 // increment the number of sub-word tokens accordingly
                 total_tokens += encoding.len();
                 // increment the number of sub-word tokens in the entity part (will be used to start the word masks at the right place)
@@ -80,7 +84,7 @@ impl EncodedInput {
             let mut word_id: i64 = 0;
 
             // add initial token
-            input_id[idx] = 1;
+            input_id[idx] = tokenizer.start_token_id() as i64;
             attn_mask[idx] = 1;
             idx += 1;
 
@@ -104,7 +108,7 @@ impl EncodedInput {
             }
 
             // add terminal token
-            input_id[idx] = 2;
+            input_id[idx] = tokenizer.end_token_id() as i64;
             attn_mask[idx] = 1;
 
             // update final results
@@ -133,6 +137,35 @@ impl EncodedInput {
         })
     }
 
+    /// Encodes each distinct word of the prompts, in one batch. The entities part (which is the same for 
+    /// every prompt), as well as the words occurring several times in the batch, are thus encoded only once.
+    fn encode_words<'a>(prompts: &'a [Prompt], tokenizer: &impl Tokenizer) -> Result<HashMap<&'a str, Vec<u32>>> {
+        let mut seen = HashSet::new();
+        let words: Vec<&str> = prompts.iter()
+            .flat_map(|prompt| prompt.tokens())
+            .map(String::as_str)
+            .filter(|word| seen.insert(*word))
+            .collect();
+        let encodings = tokenizer.encode_batch(&words).map_err(|e| Self::locate(e, prompts, tokenizer))?;
+        if encodings.len() != words.len() {
+            return Err(IndexError::with("unexpected number of encodings").into());
+        }
+        Ok(words.into_iter().zip(encodings).collect())
+    }
+
+    /// Attributes a failure of the batch encoding to the first prompt having a word of its text which cannot be
+    /// encoded on its own. The error is reported as is otherwise (for example if an entity label is concerned).
+    fn locate(error: Box<dyn Error + Send + Sync>, prompts: &[Prompt], tokenizer: &impl Tokenizer) -> Box<dyn Error + Send + Sync> {
+        for (sequence, prompt) in prompts.iter().enumerate() {
+            for word in prompt.tokens().iter().skip(prompt.entities_len()) {
+                if let Err(e) = tokenizer.encode(word) {
+                    return GlinerError::InvalidInput { sequence: Some(sequence), message: format!("cannot encode '{word}': {e}") }.into();
+                }
+            }
+        }
+        error
+    }
+
 }
 
 
@@ -162,6 +195,140 @@ mod tests {
 
     #[test]
     fn test() -> Result<()> {
+        let splitter = crate::text::splitter::RegexSplitter::default();
+        let tokenizer = crate::text::tokenizer::HFTokenizer::from_file("data/tokenizer-tiny.json")?;
+        let batch = [ "Short text", "This is a longer one, to test padding and gloubiboulga."];
+        let entities = [ "Person", "Place" ];
+        let input = super::super::text::TextInput::from_str(&batch, &entities)?;
+        let tokenized = super::super::tokenized::TokenizedInput::from(input, &splitter, None)?;
+        let prepared = PromptInput::from(tokenized);
+        let encoded = EncodedInput::from(prepared, &tokenizer)?;
+        // Some prints
+        if false {
+            println!("### {:?}", encoded.num_tokens);
+            println!("Tokens: {:?}", encoded.input_ids);
+            println!("Attn Masks: {:?}", encoded.attention_masks);
+            println!("Word masks: {:?}", encoded.word_masks);
+        }
+        // Assertions on input ids (each word being a single token, unknown ones included)
+        const ENT_ID: i64 = 4;
+        const SEP_ID: i64 = 5;
+        assert_eq!(encoded.num_tokens, 19);
+        let ids1 = encoded.input_ids.row(0);
+        let ids2 = encoded.input_ids.row(1);
+        assert_eq!(ids1.len(), encoded.num_tokens);
+        assert_eq!(ids2.len(), encoded.num_tokens);
+        assert_eq!(ids1.iter().filter(|id| **id == 0).count(), 10);
+        assert_eq!(ids1.iter().filter(|id| **id == ENT_ID).count(), 2);
+        assert_eq!(ids1.iter().filter(|id| **id == SEP_ID).count(), 1);
+        assert_eq!(ids2.iter().filter(|id| **id == 0).count(), 0);
+        assert_eq!(ids2.iter().filter(|id| **id == ENT_ID).count(), 2);
+        assert_eq!(ids2.iter().filter(|id| **id == SEP_ID).count(), 1);
+        // Assertions on attention mask
+        let attn1 = encoded.attention_masks.row(0);
+        let attn2 = encoded.attention_masks.row(1);
+        assert_eq!(attn1.iter().filter(|id| **id == 1).count(), 9);
+        assert_eq!(attn2.iter().filter(|id| **id == 1).count(), 19);
+        // Everything rules
+        Ok(())
+    }
+
+    #[test]
+    fn test2() -> Result<()> {
+        let splitter = crate::text::splitter::RegexSplitter::default();
+        let tokenizer = crate::text::tokenizer::HFTokenizer::from_file("data/tokenizer-tiny.json")?;
+        let batch = [ "My name is James Bond", "I like to drive my Aston Martin", "The villain in the movie is Auric Goldfinger"];
+        let entities = [ "movie character", "vehicle" ];
+        let input = super::super::text::TextInput::from_str(&batch, &entities)?;
+        let tokenized = super::super::tokenized::TokenizedInput::from(input, &splitter, None)?;
+        let prepared = PromptInput::from(tokenized);
+        let encoded = EncodedInput::from(prepared, &tokenizer)?;
+        // Some prints
+        if false {
+            println!("### {:?}", encoded.num_tokens);
+            println!("Tokens: {:?}", encoded.input_ids);
+            println!("Attn Masks: {:?}", encoded.attention_masks);
+            println!("Word masks: {:?}", encoded.word_masks);
+            println!("Text length: {:?}", encoded.text_lengths);
+        }
+        // Assertions on first sequence
+        let ids1 = encoded.input_ids.row(0);
+        let attn1 = encoded.attention_masks.row(0);
+        let word1 = encoded.word_masks.row(0);
+        let len1 = encoded.text_lengths.row(0);
+        assert_eq!(ids1.to_vec(), vec![1, 4, 9, 10, 4, 7, 5, 11, 12, 13, 14, 15, 2, 0, 0, 0, 0]);
+        assert_eq!(attn1.to_vec(), vec![1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0]);
+        assert_eq!(word1.to_vec(), vec![0, 0, 0, 0, 0, 0, 0, 1, 2, 3, 4, 5, 0, 0, 0, 0, 0]);
+        assert_eq!(len1.to_vec(), vec![5]);
+        // Assertions on second sequence
+        let ids2 = encoded.input_ids.row(1);
+        let attn2 = encoded.attention_masks.row(1);
+        let word2 = encoded.word_masks.row(1);
+        let len2 = encoded.text_lengths.row(1);
+        assert_eq!(ids2.to_vec(), vec![1, 4, 9, 10, 4, 7, 5, 16, 17, 18, 19, 11, 20, 21, 2, 0, 0]);
+        assert_eq!(attn2.to_vec(), vec![1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0]);
+        assert_eq!(word2.to_vec(), vec![0, 0, 0, 0, 0, 0, 0, 1, 2, 3, 4, 5, 6, 7, 0, 0, 0]);
+        assert_eq!(len2.to_vec(), vec![7]);
+        // Assertions on third sequence ("Auric" is encoded with 2 tokens)
+        let ids3 = encoded.input_ids.row(2);
+        let attn3 = encoded.attention_masks.row(2);
+        let word3 = encoded.word_masks.row(2);
+        let len3 = encoded.text_lengths.row(2);
+        assert_eq!(ids3.to_vec(), vec![1, 4, 9, 10, 4, 7, 5, 22, 3, 23, 22, 9, 13, 29, 30, 31, 2]);
+        assert_eq!(attn3.to_vec(), vec![1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1]);
+        assert_eq!(word3.to_vec(), vec![0, 0, 0, 0, 0, 0, 0, 1, 2, 3, 4, 5, 6, 7, 0, 8, 0]);
+        assert_eq!(len3.to_vec(), vec![8]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_multiword_entity_label() -> Result<()> {
+        let splitter = crate::text::splitter::RegexSplitter::default();
+        let tokenizer = crate::text::tokenizer::HFTokenizer::from_file("data/tokenizer-tiny.json")?;
+        let batch = [ "this is a test"];
+        let entities = [ "multi label" ];
+        let input = super::super::text::TextInput::from_str(&batch, &entities)?;
+        let tokenized = super::super::tokenized::TokenizedInput::from(input, &splitter, None)?;
+        let prepared = PromptInput::from(tokenized);
+        let encoded = EncodedInput::from(prepared, &tokenizer)?;
+        // Some prints
+        if false {
+            println!("### {:?}", encoded.num_tokens);
+            println!("Tokens: {:?}", encoded.input_ids);
+            println!("Attn Masks: {:?}", encoded.attention_masks);
+            println!("Word masks: {:?}", encoded.word_masks);
+        }
+        // Assertions
+        let ids = encoded.input_ids.row(0);
+        assert_eq!(ids.len(), 10);
+        let word_masks = encoded.word_masks.row(0);
+        assert_eq!(word_masks.to_vec(), vec![0, 0, 0, 0, 0, 1, 2, 3, 4, 0]);
+        // Everything rules
+        Ok(())
+    }
+
+    #[test]
+    fn test_words_mask_multi_token_first_word() -> Result<()> {
+        let splitter = crate::text::splitter::RegexSplitter::default();
+        let tokenizer = crate::text::tokenizer::HFTokenizer::from_file("data/tokenizer-tiny.json")?;
+        // "1a" is encoded with 2 tokens, the rest are 1
+        let batch = [ "1a John Doe"];
+        let entities = ["name"];
+        let input = super::super::text::TextInput::from_str(&batch, &entities)?;
+        let tokenized = super::super::tokenized::TokenizedInput::from(input, &splitter, None)?;
+        let prepared = PromptInput::from(tokenized);
+        let encoded = EncodedInput::from(prepared, &tokenizer)?;
+
+        assert_eq!(encoded.input_ids.row(0).len(), 9);
+        assert_eq!(encoded.word_masks.row(0).to_vec(), vec![0, 0, 0, 0, 1, 0, 2, 3, 0]);
+
+        Ok(())
+    }
+
+    /// Same as `test`, with the tokenizer of an actual model (requires the models)
+    #[test]
+    #[ignore]
+    fn test_model_tokenizer() -> Result<()> {
         let splitter = crate::text::splitter::RegexSplitter::default();
         let tokenizer = crate::text::tokenizer::HFTokenizer::from_file("models/gliner_small-v2.1/tokenizer.json")?;
         let batch = [ "Short text", "This is a longer one, to test padding and gloubiboulga."];
@@ -200,8 +367,10 @@ mod tests {
         Ok(())
     }
 
+    /// Same as `test2`, with the tokenizer of an actual model (requires the models)
     #[test]
-    fn test2() -> Result<()> {
+    #[ignore]
+    fn test2_model_tokenizer() -> Result<()> {
         let splitter = crate::text::splitter::RegexSplitter::default();
         let tokenizer = crate::text::tokenizer::HFTokenizer::from_file(std::path::Path::new("models/gliner_small-v2.1/tokenizer.json"))?;
         let batch = [ "My name is James Bond", "I like to drive my Aston Martin", "The villain in the movie is Auric Goldfinger"];
@@ -248,8 +417,10 @@ mod tests {
         Ok(())
     }
 
+    /// Same as `test_multiword_entity_label`, with the tokenizer of an actual model (requires the models)
     #[test]
-    fn test_multiword_entity_label() -> Result<()> {
+    #[ignore]
+    fn test_multiword_entity_label_model_tokenizer() -> Result<()> {
         let splitter = crate::text::splitter::RegexSplitter::default();
         let tokenizer = crate::text::tokenizer::HFTokenizer::from_file("models/gliner_small-v2.1/tokenizer.json")?;
         let batch = [ "this is a test"];
@@ -274,8 +445,10 @@ mod tests {
         Ok(())
     }
 
+    /// Same as `test_words_mask_multi_token_first_word`, with the tokenizer of an actual model (requires the models)
     #[test]
-    fn test_words_mask_multi_token_first_word() -> Result<()> {
+    #[ignore]
+    fn test_words_mask_multi_token_first_word_model_tokenizer() -> Result<()> {
         let splitter = crate::text::splitter::RegexSplitter::default();
         let tokenizer = crate::text::tokenizer::HFTokenizer::from_file("models/gliner_small-v2.1/tokenizer.json")?;
         // "1a" is encoded with 2 tokens, the rest are 1
@@ -292,4 +465,69 @@ mod tests {
         Ok(())
     }
 
-}
\ No newline at end of file
+    /// Dummy tokenizer encoding each character as a token, and counting the encoded inputs
+    struct CharTokenizer {
+        count: std::cell::Cell<usize>,
+    }
+
+    impl Tokenizer for CharTokenizer {
+        fn encode(&self, input: &str) -> Result<Vec<u32>> {
+            self.count.set(self.count.get() + 1);
+            Ok(input.chars().map(|c| c as u32).collect())
+        }
+    }
+
+    #[test]
+    fn test_encode_once() -> Result<()> {
+        // Silent some clippy warnings for unit tests
+        #![allow(clippy::unwrap_used)]
+        let splitter = crate::text::splitter::RegexSplitter::default();
+        let tokenizer = CharTokenizer { count: std::cell::Cell::new(0) };
+        let batch = [ "ab cd", "cd ab ef" ];
+        let entities = [ "Person", "Place" ];
+        let input = super::super::text::TextInput::from_str(&batch, &entities)?;
+        let tokenized = super::super::tokenized::TokenizedInput::from(input, &splitter, None)?;
+        let prepared = PromptInput::from(tokenized);
+        // Processing
+        let encoded = EncodedInput::from(prepared, &tokenizer)?;
+        // Assertions: only distinct words are encoded (<<ENT>>, Person, Place, <<SEP>>, ab, cd, ef)
+        assert_eq!(tokenizer.count.get(), 7);
+        let ids: Vec<i64> = encoded.input_ids.row(1).iter().copied().collect();
+        let expected: Vec<i64> = std::iter::once(1)
+            .chain("<<ENT>>Person<<ENT>>Place<<SEP>>cdabef".chars().map(|c| c as i64))
+            .chain(std::iter::once(2))
+            .collect();
+        assert_eq!(ids, expected);
+        let words: Vec<i64> = encoded.word_masks.row(1).iter().copied().filter(|w| *w != 0).collect();
+        assert_eq!(words, vec![1, 2, 3]);
+        // Everything rules
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_word() -> Result<()> {
+        /// Tokenizer failing on words with control characters
+        struct Strict;
+        impl Tokenizer for Strict {
+            fn encode(&self, input: &str) -> Result<Vec<u32>> {
+                match input.contains(char::is_control) {
+                    true => Err(GlinerError::Tokenizer("control character".to_string()).into()),
+                    false => Ok(input.chars().map(u32::from).collect()),
+                }
+            }
+        }
+        let splitter = crate::text::splitter::RegexSplitter::default();
+        let prompts = |texts: &[&str], entities: &[&str]| -> Result<PromptInput> {
+            let input = super::super::text::TextInput::from_str(texts, entities)?;
+            Ok(PromptInput::from(super::super::tokenized::TokenizedInput::from(input, &splitter, None)?))
+        };
+        // Processing
+        let text = GlinerError::from(EncodedInput::from(prompts(&["Some text", "Some\u{0007}text"], &["person"])?, &Strict).err().ok_or("expected an error")?);
+        let label = GlinerError::from(EncodedInput::from(prompts(&["Some text"], &["pers\u{0007}on"])?, &Strict).err().ok_or("expected an error")?);
+        // Assertions
+        assert!(matches!(text, GlinerError::InvalidInput { sequence: Some(1), .. }));
+        assert!(matches!(label, GlinerError::Tokenizer(_)));
+        // Everything rules
+        Ok(())
+    }
+}
@@ -9,6 +9,9 @@
 //! 
 //! Other steps are for use in pipelines for other applications:
 //! - Input for relation extraction
+//! - Chunked input for long texts
+//! - Normalized input for noisy texts
+//! - Streaming readers of texts from CSV and JSONL files
 //! - ...
 
 pub mod text;
@@ -17,3 +20,6 @@ pub mod prompt;
 pub mod encoded;
 pub mod tensors;
 pub mod relation;
+pub mod chunked;
+pub mod normalized;
+pub mod reader;
@@ -0,0 +1,56 @@
+use composable::Composable;
+use crate::util::result::Result;
+use crate::text::normalizer::Normalizer;
+use crate::model::pipeline::context::NormalizationContext;
+use super::text::TextInput;
+
+
+/// Composable: Text => (Text, NormalizationContext)
+///
+/// Normalizes the texts (see `Normalizer`), the context keeping the original texts along with the
+/// alignments allowing to map the resulting spans back onto them (see `NormalizedToSpanOutput`).
+pub struct RawToNormalized<'a, N> {
+    normalizer: &'a N,
+}
+
+impl<'a, N> RawToNormalized<'a, N> {
+    pub fn new(normalizer: &'a N) -> Self {
+        Self { normalizer }
+    }
+}
+
+impl<N: Normalizer> Composable<TextInput, (TextInput, NormalizationContext)> for RawToNormalized<'_, N> {
+    fn apply(&self, input: TextInput) -> Result<(TextInput, NormalizationContext)> {
+        let mut texts = Vec::with_capacity(input.texts.len());
+        let mut alignments = Vec::with_capacity(input.texts.len());
+        for text in &input.texts {
+            let normalized = self.normalizer.normalize(text);
+            texts.push(normalized.text);
+            alignments.push(normalized.alignment);
+        }
+        Ok((TextInput::new(texts, input.entities)?, NormalizationContext { texts: input.texts, alignments }))
+    }
+}
+
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text::normalizer::TextNormalizer;
+
+    #[test]
+    fn test() -> Result<()> {
+        let input = TextInput::from_str(&["Hello  Zoë\u{00A0}Smith", "l\u{2019}ami"], &["person"])?;
+        // Processing
+        let normalizer = TextNormalizer::default();
+        let (normalized, context) = RawToNormalized::new(&normalizer).apply(input)?;
+        // Assertions
+        assert_eq!(normalized.texts, vec!["Hello Zoë Smith", "l'ami"]);
+        assert_eq!(normalized.entities, vec!["person"]);
+        assert_eq!(context.texts, vec!["Hello  Zoë\u{00A0}Smith", "l\u{2019}ami"]);
+        assert_eq!(context.alignments.len(), 2);
+        // Everything rules
+        Ok(())
+    }
+}
@@ -1,6 +1,6 @@
 use composable::Composable;
 use crate::util::result::Result;
-use crate::text::{prompt::Prompt, token::Token};
+use crate::text::{prompt::{Prompt, PromptFormat}, token::Token};
 use super::tokenized::TokenizedInput;
 
 
@@ -10,6 +10,8 @@ use super::tokenized::TokenizedInput;
 /// ```text
 /// [<<ENT>>, type1, <<ENT>>, type2, ..., <<ENT>>, typeK, <<SEP>>, token1, token2, ..., tokenN]
 /// ```
+/// 
+/// (the actual markers are given by a `PromptFormat`)
 pub struct PromptInput {
     /// Texts (moved from input)
     pub texts: Vec<String>,
@@ -28,9 +30,15 @@ pub struct PromptInput {
 
 impl PromptInput {
 
+    /// Builds the prompts using the default format
     pub fn from(input: TokenizedInput) -> Self {
+        Self::from_format(input, &PromptFormat::default())
+    }
+
+    /// Builds the prompts using the given format
+    pub fn from_format(input: TokenizedInput, format: &PromptFormat) -> Self {
         // prepare the entities part of the prompt (will be copied into each actual prompt)
-        let entities_prompt = Self::entities_prompt(&input.entities);        
+        let entities_prompt = Self::entities_prompt(&input.entities, format);        
         // the text lengths for each sequence (number of actual tokens beside the entities part)
         let mut text_lengths = Vec::<usize>::new();
         // the maximum number of words in a prompt excluding entities (number of tokens in the largest sequence in the batch)
@@ -67,17 +75,14 @@ impl PromptInput {
 
 
     /// Create the entities part of the prompt.
-    fn entities_prompt(entities: &Vec<String>) -> Vec<String> {
-        const ENTITY_TOKEN: &str = "<<ENT>>";
-        const SEP_TOKEN: &str = "<<SEP>>";
-
+    fn entities_prompt(entities: &Vec<String>, format: &PromptFormat) -> Vec<String> {
         let mut result = Vec::with_capacity(entities.len() * 2 + 1);
         for entity in entities {
-            result.push(ENTITY_TOKEN.to_string());
+            result.push(format.ent_token.clone());
             result.push(entity.clone());
         }
 
-        result.push(SEP_TOKEN.to_string());
+        result.push(format.sep_token.clone());
         result
     }
 
@@ -87,12 +92,19 @@ impl PromptInput {
 /// Composable: Tokenized => Prompt
 #[derive(Default)]
 pub struct TokenizedToPrompt { 
+    format: PromptFormat,
+}
+
+impl TokenizedToPrompt {
+    pub fn new(format: PromptFormat) -> Self {
+        Self { format }
+    }
 }
 
 
 impl Composable<TokenizedInput, PromptInput> for TokenizedToPrompt {
     fn apply(&self, input: TokenizedInput) -> Result<PromptInput> {
-        Ok(PromptInput::from(input))
+        Ok(PromptInput::from_format(input, &self.format))
     }
 }
 
@@ -133,4 +145,17 @@ mod tests {
         // Everything rules
         Ok(())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_format() -> Result<()> {
+        #![allow(clippy::unwrap_used)]
+        let splitter = crate::text::splitter::RegexSplitter::default();
+        let input = super::super::text::TextInput::from_str(&[ "This is a text" ], &[ "Person" ])?;
+        let tokenized = super::super::tokenized::TokenizedInput::from(input, &splitter, None)?;
+        let prepared = PromptInput::from_format(tokenized, &PromptFormat::new("[E]", "[S]"));
+        let prompt = prepared.prompts.first().unwrap();
+        assert_eq!(prompt.tokens()[..3], ["[E]", "Person", "[S]"]);
+        assert_eq!(prompt.entities_len(), 3);
+        Ok(())
+    }
+}
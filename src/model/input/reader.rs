@@ -0,0 +1,249 @@
+//! Streaming readers of texts from CSV and JSONL files
+//!
+//! Records are read one at a time, so that arbitrarily large files can be processed by fixed-size
+//! batches (see `Batches`). Malformed records are reported as errors (with their location), after
+//! which reading can go on.
+//!
+//! ```ignore
+//! let records = CsvReader::new(File::open("texts.csv")?, "text", Some("id".into()))?;
+//! for batch in Batches::new(records, 32, vec!["person".to_string()]) {
+//!     let batch = batch?;
+//!     let output = model.inference(batch.input)?;
+//!     // batch.ids[i] identifies output.spans[i]
+//! }
+//! ```
+
+use std::io::{BufRead, BufReader, Lines, Read};
+use crate::util::{error::GlinerError, result::Result};
+use super::text::TextInput;
+
+
+/// A text with its identifier
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub id: String,
+    pub text: String,
+}
+
+
+/// A CSV column, given by header name or by index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Column {
+    Name(String),
+    Index(usize),
+}
+
+impl From<&str> for Column {
+    fn from(name: &str) -> Self {
+        Self::Name(name.to_string())
+    }
+}
+
+impl From<usize> for Column {
+    fn from(index: usize) -> Self {
+        Self::Index(index)
+    }
+}
+
+
+/// Reads one text per row of a CSV file with headers, with an optional identifier column (the row
+/// number, starting from 1 after the headers, being used otherwise)
+pub struct CsvReader<R> {
+    records: csv::StringRecordsIntoIter<R>,
+    text: usize,
+    id: Option<usize>,
+    row: usize,
+}
+
+impl<R: Read> CsvReader<R> {
+    pub fn new(reader: R, text: impl Into<Column>, id: Option<Column>) -> Result<Self> {
+        let mut csv = csv::Reader::from_reader(reader);
+        let headers = csv.headers().map_err(|e| GlinerError::invalid_input(&format!("invalid CSV headers: {e}")))?.clone();
+        let index = |column: Column| match column {
+            Column::Index(index) if index < headers.len() => Ok(index),
+            Column::Name(ref name) => headers.iter().position(|h| h == name)
+                .ok_or_else(|| GlinerError::invalid_input(&format!("CSV column not found: {name}"))),
+            Column::Index(index) => Err(GlinerError::invalid_input(&format!("CSV column not found: {index}"))),
+        };
+        let text = index(text.into())?;
+        let id = id.map(index).transpose()?;
+        Ok(Self { records: csv.into_records(), text, id, row: 0 })
+    }
+
+    fn record(&self, record: csv::Result<csv::StringRecord>) -> Result<Record> {
+        let record = record.map_err(|e| GlinerError::invalid_input(&format!("row {}: {e}", self.row)))?;
+        let field = |index: usize| record.get(index).ok_or_else(|| GlinerError::invalid_input(&format!("row {}: missing column {index}", self.row)));
+        let text = field(self.text)?.to_string();
+        let id = match self.id {
+            Some(index) => field(index)?.to_string(),
+            None => self.row.to_string(),
+        };
+        Ok(Record { id, text })
+    }
+}
+
+impl<R: Read> Iterator for CsvReader<R> {
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = self.records.next()?;
+        self.row += 1;
+        Some(self.record(record))
+    }
+}
+
+
+/// Reads one text per non-empty line of a JSONL file, from the given string field, with an optional
+/// identifier field (string or number, the line number being used otherwise)
+pub struct JsonlReader<R> {
+    lines: Lines<BufReader<R>>,
+    text: String,
+    id: Option<String>,
+    line: usize,
+}
+
+impl<R: Read> JsonlReader<R> {
+    pub fn new(reader: R, text: &str, id: Option<&str>) -> Self {
+        Self { lines: BufReader::new(reader).lines(), text: text.to_string(), id: id.map(str::to_string), line: 0 }
+    }
+
+    fn record(&self, line: &str) -> Result<Record> {
+        let error = |message: &str| GlinerError::invalid_input(&format!("line {}: {message}", self.line));
+        let value: serde_json::Value = serde_json::from_str(line).map_err(|e| error(&e.to_string()))?;
+        let text = value.get(&self.text).and_then(|v| v.as_str()).ok_or_else(|| error(&format!("missing string field {}", self.text)))?;
+        let id = match &self.id {
+            Some(field) => match value.get(field) {
+                Some(serde_json::Value::String(id)) => id.clone(),
+                Some(serde_json::Value::Number(id)) => id.to_string(),
+                _ => return Err(error(&format!("missing string or number field {field}")).into()),
+            },
+            None => self.line.to_string(),
+        };
+        Ok(Record { id, text: text.to_string() })
+    }
+}
+
+impl<R: Read> Iterator for JsonlReader<R> {
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = self.lines.next()?;
+            self.line += 1;
+            match line {
+                Ok(line) if line.trim().is_empty() => continue,
+                Ok(line) => return Some(self.record(&line)),
+                Err(e) => return Some(Err(e.into())),
+            }
+        }
+    }
+}
+
+
+/// Texts of a batch, ready for inference, with their identifiers
+pub struct RecordBatch {
+    pub ids: Vec<String>,
+    pub input: TextInput,
+}
+
+
+/// Groups records by batches of (at most) the given size, looking for the given entities.
+///
+/// Errors are returned as soon as they occur, the records read so far being kept for the next batch.
+pub struct Batches<I> {
+    records: I,
+    size: usize,
+    entities: Vec<String>,
+    pending: Vec<Record>,
+}
+
+impl<I: Iterator<Item = Result<Record>>> Batches<I> {
+    pub fn new(records: I, size: usize, entities: Vec<String>) -> Self {
+        let size = size.max(1);
+        Self { records, size, entities, pending: Vec::with_capacity(size) }
+    }
+}
+
+impl<I: Iterator<Item = Result<Record>>> Iterator for Batches<I> {
+    type Item = Result<RecordBatch>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.len() < self.size {
+            match self.records.next() {
+                Some(Ok(record)) => self.pending.push(record),
+                Some(Err(e)) => return Some(Err(e)),
+                None => break,
+            }
+        }
+        if self.pending.is_empty() {
+            return None;
+        }
+        let (ids, texts) = std::mem::take(&mut self.pending).into_iter().map(|r| (r.id, r.text)).unzip();
+        Some(TextInput::new(texts, self.entities.clone()).map(|input| RecordBatch { ids, input }))
+    }
+}
+
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    // Silent some clippy warnings for unit tests
+    #![allow(clippy::get_first)]
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn test_csv() -> Result<()> {
+        let csv = "id,title,text\nd1,First,My name is James Bond\nd2,Second\nd3,Third,\"I like my \"\"Aston Martin\"\"\"\n";
+        // Processing
+        let records: Vec<Result<Record>> = CsvReader::new(csv.as_bytes(), "text", Some("id".into()))?.collect();
+        // Assertions: the malformed row is an error, reading goes on
+        assert_eq!(records.len(), 3);
+        assert_eq!(records.get(0).unwrap().as_ref().unwrap(), &Record { id: "d1".into(), text: "My name is James Bond".into() });
+        assert!(records.get(1).unwrap().as_ref().unwrap_err().to_string().contains("row 2"));
+        assert_eq!(records.get(2).unwrap().as_ref().unwrap().text, "I like my \"Aston Martin\"");
+        // by index, with row numbers as identifiers
+        let record = CsvReader::new(csv.as_bytes(), 1, None)?.next().unwrap()?;
+        assert_eq!(record, Record { id: "1".into(), text: "First".into() });
+        // unknown columns
+        assert!(CsvReader::new(csv.as_bytes(), "body", None).is_err());
+        assert!(CsvReader::new(csv.as_bytes(), 3, None).is_err());
+        // Everything rules
+        Ok(())
+    }
+
+    #[test]
+    fn test_jsonl() -> Result<()> {
+        let jsonl = "{\"id\": 7, \"text\": \"My name is James Bond\"}\n\n{\"text\": 42}\nnot json\n{\"id\": \"x\", \"text\": \"Hello\"}\n";
+        // Processing
+        let records: Vec<Result<Record>> = JsonlReader::new(jsonl.as_bytes(), "text", Some("id")).collect();
+        // Assertions
+        assert_eq!(records.len(), 4);
+        assert_eq!(records.get(0).unwrap().as_ref().unwrap().id, "7");
+        assert!(records.get(1).unwrap().as_ref().unwrap_err().to_string().contains("line 3"));
+        assert!(records.get(2).unwrap().as_ref().unwrap_err().to_string().contains("line 4"));
+        assert_eq!(records.get(3).unwrap().as_ref().unwrap(), &Record { id: "x".into(), text: "Hello".into() });
+        // line numbers as identifiers
+        let ids: Vec<String> = JsonlReader::new(jsonl.as_bytes(), "text", None).filter_map(|r| r.ok()).map(|r| r.id).collect();
+        assert_eq!(ids, vec!["1", "5"]);
+        // Everything rules
+        Ok(())
+    }
+
+    #[test]
+    fn test_batches() -> Result<()> {
+        let jsonl = "{\"text\": \"a\"}\n{\"text\": \"b\"}\n{}\n{\"text\": \"c\"}\n{\"text\": \"d\"}\n";
+        let records = JsonlReader::new(jsonl.as_bytes(), "text", None);
+        // Processing
+        let batches: Vec<Result<RecordBatch>> = Batches::new(records, 3, vec!["person".to_string()]).collect();
+        // Assertions: the error comes first, and the records read before are not lost
+        assert_eq!(batches.len(), 3);
+        assert!(batches.get(0).unwrap().is_err());
+        let first = batches.get(1).unwrap().as_ref().unwrap();
+        assert_eq!(first.ids, vec!["1", "2", "4"]);
+        assert_eq!(first.input.texts, vec!["a", "b", "c"]);
+        assert_eq!(batches.get(2).unwrap().as_ref().unwrap().input.texts, vec!["d"]);
+        // Everything rules
+        Ok(())
+    }
+}
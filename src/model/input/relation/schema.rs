@@ -10,6 +10,7 @@ impl RelationSchema {
         Self { relations: HashMap::new() }
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(relations: &[&str]) -> Self {
         Self {
             relations: relations.iter().map(|r| (r.to_string(), RelationSpec::default())).collect()
@@ -38,6 +39,7 @@ impl Default for RelationSchema {
     fn default() -> Self { Self::new() }
 }
 
+#[derive(Default)]
 pub struct RelationSpec {
     allowed_subjects: Option<HashSet<String>>,
     allowed_objects: Option<HashSet<String>>,
@@ -51,6 +53,18 @@ impl RelationSpec {
         }
     }
 
+    /// Restricts the labels of the subjects (any by default)
+    pub fn with_allowed_subjects(mut self, allowed_subjects: &[&str]) -> Self {
+        self.allowed_subjects = Some(allowed_subjects.iter().map(|x| x.to_string()).collect());
+        self
+    }
+
+    /// Restricts the labels of the objects (any by default)
+    pub fn with_allowed_objects(mut self, allowed_objects: &[&str]) -> Self {
+        self.allowed_objects = Some(allowed_objects.iter().map(|x| x.to_string()).collect());
+        self
+    }
+
     pub fn allows_subject(&self, label: &str) -> bool {
         match &self.allowed_subjects { None => true, Some(hs) => hs.contains(label) }
     }
@@ -67,12 +81,3 @@ impl RelationSpec {
         match &self.allowed_objects { None => true, Some(hs) => !hs.is_disjoint(labels) }
     }
 }
-
-impl Default for RelationSpec {
-    fn default() -> Self {
-        Self {
-            allowed_subjects: None,
-            allowed_objects: None,
-        }
-    }
-}
\ No newline at end of file
@@ -1,31 +1,31 @@
-use ort::session::SessionInputs;
 use composable::Composable;
 use crate::util::result::Result;
+use ort::session::SessionInputs;
+use super::super::super::backend::{Tensors, onnxruntime::to_session_inputs};
 use super::super::encoded::EncodedInput;
 use super::super::super::pipeline::context::EntityContext;
 
 
 
 /// Ready-for-inference tensors (span mode)
-pub struct SpanTensors<'a> {
-    pub tensors: SessionInputs<'a, 'a>,
+pub struct SpanTensors {
+    pub tensors: Tensors,
     pub context: EntityContext,    
 }
 
-impl SpanTensors<'_> {
+impl SpanTensors {
 
     pub fn from(encoded: EncodedInput, max_width: usize) -> Result<Self> {
         let (span_idx, span_mask) = Self::make_spans_tensors(&encoded, max_width);
-        let inputs = ort::inputs!{
-            "input_ids" => encoded.input_ids,
-            "attention_mask" => encoded.attention_masks,
-            "words_mask" => encoded.word_masks,
-            "text_lengths" => encoded.text_lengths,
-            "span_idx" => span_idx,
-            "span_mask" => span_mask,
-        }?;
+        let tensors = Tensors::new()
+            .with("input_ids", encoded.input_ids)
+            .with("attention_mask", encoded.attention_masks)
+            .with("words_mask", encoded.word_masks)
+            .with("text_lengths", encoded.text_lengths)
+            .with("span_idx", span_idx)
+            .with("span_mask", span_mask);
         Ok(Self {
-            tensors: inputs.into(),
+            tensors,
             context: EntityContext { 
                 texts: encoded.texts, 
                 tokens: encoded.tokens, 
@@ -106,30 +106,43 @@ impl EncodedToTensors {
     }
 }
 
-impl<'a> Composable<EncodedInput, SpanTensors<'a>> for EncodedToTensors {
-    fn apply(&self, input: EncodedInput) -> Result<SpanTensors<'a>> {
+impl Composable<EncodedInput, SpanTensors> for EncodedToTensors {
+    fn apply(&self, input: EncodedInput) -> Result<SpanTensors> {
         SpanTensors::from(input, self.max_width)
     }
 }
 
 
-/// Composable: SpanTensors => (SessionInput, EntityContext) 
+/// Composable: SpanTensors => (Tensors, EntityContext) 
 #[derive(Default)]
-pub struct TensorsToSessionInput { 
+pub struct TensorsToBackendInput { 
 }
 
 
-impl<'a> Composable<SpanTensors<'a>, (SessionInputs<'a, 'a>, EntityContext)> for TensorsToSessionInput {
-    fn apply(&self, input: SpanTensors<'a>) -> Result<(SessionInputs<'a, 'a>, EntityContext)> {
+impl Composable<SpanTensors, (Tensors, EntityContext)> for TensorsToBackendInput {
+    fn apply(&self, input: SpanTensors) -> Result<(Tensors, EntityContext)> {
         Ok((input.tensors, input.context))
     }
 }
 
 
+/// Composable: SpanTensors => (SessionInputs, EntityContext)
+#[derive(Default)]
+#[deprecated(since = "0.9.4", note = "use `TensorsToBackendInput` followed by `backend::onnxruntime::TensorsToSessionInputs`")]
+pub struct TensorsToSessionInput { }
+
+
+#[allow(deprecated)]
+impl<'a> Composable<SpanTensors, (SessionInputs<'a, 'a>, EntityContext)> for TensorsToSessionInput {
+    fn apply(&self, input: SpanTensors) -> Result<(SessionInputs<'a, 'a>, EntityContext)> {
+        Ok((to_session_inputs(input.tensors)?, input.context))
+    }
+}
+
+
 /// Unit tests
 #[cfg(test)]
 mod tests {
-    use ort::session::SessionInputValue;
     use super::*;
 
     #[test]
@@ -139,7 +152,7 @@ mod tests {
         #![allow(clippy::unwrap_used)]
         // Processing
         let splitter = crate::text::splitter::RegexSplitter::default();        
-        let tokenizer = crate::text::tokenizer::HFTokenizer::from_file(std::path::Path::new("models/gliner_small-v2.1/tokenizer.json"))?;
+        let tokenizer = crate::text::tokenizer::HFTokenizer::from_file("data/tokenizer-tiny.json")?;
         let batch = [ "My name is James Bond", "I like to drive my Aston Martin"];
         let entities = [ "movie character", "vehicle" ];
         let input = super::super::super::text::TextInput::from_str(&batch, &entities)?;
@@ -147,10 +160,8 @@ mod tests {
         let prepared = super::super::super::prompt::PromptInput::from(tokenized);
         let encoded = EncodedInput::from(prepared, &tokenizer)?;
         let spans = SpanTensors::from(encoded, 12)?;
-        let span_idx = get_tensor("span_idx", &spans.tensors)?;
-        let span_idx = span_idx.try_extract_tensor::<i64>()?;        
-        let span_masks = get_tensor("span_mask", &spans.tensors)?;
-        let span_masks = span_masks.try_extract_tensor::<bool>()?;        
+        let span_idx = spans.tensors.get("span_idx").ok_or("cannot extract expected tensor")?;
+        let span_masks = spans.tensors.get("span_mask").ok_or("cannot extract expected tensor")?;
         // Some prints
         if false {
             println!("Spans: {:?}", &span_idx);
@@ -163,15 +174,4 @@ mod tests {
         Ok(())
     }
 
-    fn get_tensor<'a>(key: &str, si: &'a SessionInputs<'a, 'a>) -> Result<&'a SessionInputValue<'a>> {
-        if let SessionInputs::ValueMap(map) = si {
-            for (k, v) in map {
-                if k.eq(key) {
-                    return Ok(v);
-                }
-            }
-        }
-        Err("cannot extract expected tensor".into())
-    }
-
 }
\ No newline at end of file
@@ -1,26 +1,26 @@
-use ort::session::SessionInputs;
 use composable::Composable;
 use crate::util::result::Result;
+use ort::session::SessionInputs;
+use super::super::super::backend::{Tensors, onnxruntime::to_session_inputs};
 use super::super::encoded::EncodedInput;
 use super::super::super::pipeline::context::EntityContext;
 
 /// Ready-for-inference tensors (token mode)
-pub struct TokenTensors<'a> {
-    pub tensors: SessionInputs<'a, 'a>,
+pub struct TokenTensors {
+    pub tensors: Tensors,
     pub context: EntityContext,    
 }
 
-impl TokenTensors<'_> {
+impl TokenTensors {
 
     pub fn from(encoded: EncodedInput) -> Result<Self> {
-        let inputs = ort::inputs!{
-            "input_ids" => encoded.input_ids,
-            "attention_mask" => encoded.attention_masks,
-            "words_mask" => encoded.word_masks,
-            "text_lengths" => encoded.text_lengths,
-        }?;
+        let tensors = Tensors::new()
+            .with("input_ids", encoded.input_ids)
+            .with("attention_mask", encoded.attention_masks)
+            .with("words_mask", encoded.word_masks)
+            .with("text_lengths", encoded.text_lengths);
         Ok(Self {
-            tensors: inputs.into(),
+            tensors,
             context: EntityContext { 
                 texts: encoded.texts, 
                 tokens: encoded.tokens, 
@@ -38,20 +38,34 @@ impl TokenTensors<'_> {
 pub struct EncodedToTensors { }
 
 
-impl<'a> Composable<EncodedInput, TokenTensors<'a>> for EncodedToTensors {
-    fn apply(&self, input: EncodedInput) -> Result<TokenTensors<'a>> {
+impl Composable<EncodedInput, TokenTensors> for EncodedToTensors {
+    fn apply(&self, input: EncodedInput) -> Result<TokenTensors> {
         TokenTensors::from(input)
     }
 }
 
 
-/// Composable: TokenTensors => (SessionInput, TensorsMeta) 
+/// Composable: TokenTensors => (Tensors, EntityContext) 
 #[derive(Default)]
-pub struct TensorsToSessionInput { }
+pub struct TensorsToBackendInput { }
 
 
-impl<'a> Composable<TokenTensors<'a>, (SessionInputs<'a, 'a>, EntityContext)> for TensorsToSessionInput {
-    fn apply(&self, input: TokenTensors<'a>) -> Result<(SessionInputs<'a, 'a>, EntityContext)> {
+impl Composable<TokenTensors, (Tensors, EntityContext)> for TensorsToBackendInput {
+    fn apply(&self, input: TokenTensors) -> Result<(Tensors, EntityContext)> {
         Ok((input.tensors, input.context))
     }
 }
+
+
+/// Composable: TokenTensors => (SessionInputs, EntityContext)
+#[derive(Default)]
+#[deprecated(since = "0.9.4", note = "use `TensorsToBackendInput` followed by `backend::onnxruntime::TensorsToSessionInputs`")]
+pub struct TensorsToSessionInput { }
+
+
+#[allow(deprecated)]
+impl<'a> Composable<TokenTensors, (SessionInputs<'a, 'a>, EntityContext)> for TensorsToSessionInput {
+    fn apply(&self, input: TokenTensors) -> Result<(SessionInputs<'a, 'a>, EntityContext)> {
+        Ok((to_session_inputs(input.tensors)?, input.context))
+    }
+}
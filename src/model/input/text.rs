@@ -1,5 +1,7 @@
+use std::fs::File;
 use std::path::Path;
-use crate::util::result::Result;
+use crate::util::{error::GlinerError, result::Result};
+use super::reader::CsvReader;
 
 /// Represents the raw text input, as a list of text chunks and a list of entity classes
 pub struct TextInput {
@@ -14,7 +16,7 @@ impl TextInput {
     /// sequences to be analyzed, and a vector of entity classes.
     pub fn new(texts: Vec<String>, entities: Vec<String>) -> Result<Self> {
         if texts.is_empty() || entities.is_empty() {
-            Err("invalid input: empty texts and/or entities".into())
+            Err(GlinerError::invalid_input("empty texts and/or entities").into())
         }
         else {
             Ok(Self { texts, entities })
@@ -29,14 +31,13 @@ impl TextInput {
         )
     }
 
-    /// For testing purposes. 
-    /// Panics if the specified column does not exist
+    /// For testing purposes: loads (at most `limit`) texts from the given column of a CSV file with headers.
+    /// See `CsvReader` and `Batches` to stream large files.
     pub fn new_from_csv<P: AsRef<Path>>(path: P, column: usize, limit: usize, entities: Vec<String>) -> Result<Self> {
-        let mut csv = csv::Reader::from_path(path)?;
-        let texts: Vec<String> = csv.records()
+        let texts = CsvReader::new(File::open(path)?, column, None)?
             .take(limit)
-            .map(|r| r.unwrap().get(column).unwrap().to_string())
-            .collect();
+            .map(|r| r.map(|r| r.text))
+            .collect::<Result<Vec<String>>>()?;
         Self::new(texts, entities)
     }
 
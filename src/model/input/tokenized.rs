@@ -1,5 +1,5 @@
 use composable::Composable;
-use crate::util::result::Result;
+use crate::util::{error::GlinerError, result::Result};
 use crate::text::token::Token;
 use crate::text::splitter::Splitter;
 use super::text::TextInput;
@@ -19,10 +19,10 @@ pub struct TokenizedInput {
 impl TokenizedInput {
 
     pub fn from(input: TextInput, splitter: &impl Splitter, max_length: Option<usize>) -> Result<Self> {
-        // leverage the given `Splitter` to tokenize each input sequence
+        // leverage the given `Splitter` to tokenize each input sequence (invalid texts being reported as such)
         let mut tokens = Vec::with_capacity(input.texts.len());
-        for s in &input.texts {
-            tokens.push(splitter.split(s, max_length)?);
+        for (sequence, s) in input.texts.iter().enumerate() {
+            tokens.push(splitter.split(s, max_length).map_err(|e| GlinerError::from(e).at_sequence(sequence))?);
         }
 
         Ok(Self {
@@ -87,4 +87,25 @@ mod tests {
         // Everything rules
         Ok(())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_invalid_text() -> Result<()> {
+        /// Splitter rejecting texts with control characters
+        struct Strict;
+        impl Splitter for Strict {
+            fn split(&self, input: &str, limit: Option<usize>) -> Result<Vec<Token>> {
+                match input.contains(char::is_control) {
+                    true => Err(GlinerError::invalid_input("control character").into()),
+                    false => crate::text::splitter::RegexSplitter::default().split(input, limit),
+                }
+            }
+        }
+        let input = TextInput::from_str(&["This is a text", "This is\u{0007} another one"], &["person"])?;
+        // Processing
+        let error = GlinerError::from(TokenizedInput::from(input, &Strict, None).err().ok_or("expected an error")?);
+        // Assertions
+        assert!(matches!(error, GlinerError::InvalidInput { sequence: Some(1), .. }));
+        // Everything rules
+        Ok(())
+    }
+}
@@ -4,25 +4,83 @@ pub mod params;
 pub mod pipeline;
 pub mod input;
 pub mod output;
+pub mod auto;
+pub mod config;
+pub mod pool;
+pub mod batch;
+pub mod backend;
+#[cfg(feature = "async")]
+pub mod asynchronous;
 
-use crate::util::result::Result;
-use orp::pipeline::Pipeline;
+use crate::util::{error::GlinerError, result::GlinerResult, profile::Traced};
+use composable::Composable;
+use backend::{Backend, TensorPipeline};
+use output::scores::{ScoreOutput, ScorePipeline};
 use params::Parameters;
 use orp::model::Model;
 
 
 /// Basic GLiNER, to be parametrized by a specific pipeline (see implementations within the pipeline module)
 /// 
-/// This is just a convenience wrapper around a `Model`, a `Pipeline`, and some `Parameters`.
-pub struct GLiNER<P> {
+/// This is just a convenience wrapper around a `Model`, a `Pipeline`, and some `Parameters`. The model is
+/// run by ONNX Runtime by default, but any `Backend` can be provided instead (see `with_backend()`).
+///
+/// It is `Send + Sync` for the provided pipelines, so one loaded model can be shared between threads
+/// (for example behind an `Arc`). See `pool::GLiNERPool` to run concurrent inferences on distinct sessions.
+pub struct GLiNER<P, B = Model> {
     params: Parameters,
-    model: Model,
+    model: B,
     pipeline: P,
 }
 
 
-impl<'a, P: Pipeline<'a, Parameters = Parameters>> GLiNER<P> {
-    pub fn inference(&'a self, input: P::Input) -> Result<P::Output> {
-        self.model.inference(input, &self.pipeline, &self.params)
+impl<P, B: Backend> GLiNER<P, B> {
+    /// Builds a GLiNER running the given pipeline on the given backend (see `backend::mock` for example)
+    pub fn with_backend(params: Parameters, pipeline: P, backend: B) -> Self {
+        Self {
+            params,
+            model: backend,
+            pipeline,
+        }
+    }
+
+    /// Default parameters of the inferences
+    pub fn params(&self) -> &Parameters {
+        &self.params
+    }
+
+    /// Pipeline (splitter, tokenizer and decoding) of the inferences
+    pub fn pipeline(&self) -> &P {
+        &self.pipeline
+    }
+
+    /// Runs the given pipeline with the given parameters on the model of this GLiNER. This allows to
+    /// override the default parameters, or to extract relations between the entities found by a
+    /// token-mode GLiNER (with a model supporting it):
+    ///
+    /// ```ignore
+    /// let entities = model.inference(input)?;
+    /// let pipeline = RelationPipeline::new(model.pipeline().by_ref(), &schema);
+    /// let relations = model.inference_with(&pipeline, model.params(), entities)?;
+    /// ```
+    pub fn inference_with<'a, Q: TensorPipeline<'a>>(&self, pipeline: &Q, params: &Q::Parameters, input: Q::Input) -> GlinerResult<Q::Output> {
+        backend::inference(&self.model, pipeline, params, input).map_err(GlinerError::from)
+    }
+}
+
+
+impl<'a, P: TensorPipeline<'a, Parameters = Parameters>, B: Backend> GLiNER<P, B> {
+    pub fn inference(&'a self, input: P::Input) -> GlinerResult<P::Output> {
+        backend::inference(&self.model, &self.pipeline, &self.params, input).map_err(GlinerError::from)
+    }
+}
+
+
+impl<'a, P: ScorePipeline<'a, Parameters = Parameters>, B: Backend> GLiNER<P, B> {
+    /// Runs the model but returns the raw scores instead of decoded spans (see `output::scores`)
+    pub fn scores(&'a self, input: P::Input) -> GlinerResult<ScoreOutput> {
+        let (tensors, context) = self.pipeline.tensor_pre_processor(&self.params).apply(input)?;
+        let tensors = Traced::with_name("Backend::run", |tensors| self.model.run(tensors)).apply(tensors)?;
+        Ok(self.pipeline.score_post_processor(&self.params).apply((tensors, context))?)
     }
 }
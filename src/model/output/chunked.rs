@@ -0,0 +1,116 @@
+//! Reassembly of the spans found in chunks of longer texts
+
+use composable::Composable;
+use crate::util::{error::IndexError, result::Result};
+use crate::text::span::Span;
+use crate::model::pipeline::context::ChunkContext;
+use crate::model::params::SpanSelection;
+use super::decoded::{SpanOutput, search::SpanSearch};
+
+
+/// Maps back the spans found in each chunk onto the original texts.
+///
+/// Offsets and sequence indexes are adjusted so that they refer to the original texts. Since
+/// chunks may overlap, the same span can be found more than once: only the most probable
+/// occurrence is kept. Finally, span search (greedy by default) is applied again on each text,
+/// to resolve conflicts between spans found in different chunks.
+pub struct ChunkedToSpanOutput {
+    flat_ner: bool,
+    dup_label: bool,
+    multi_label: bool,
+    span_search: SpanSearch,
+}
+
+impl ChunkedToSpanOutput {
+    /// See `GreedySearch::new()` for the meaning of the flags
+    pub fn new(flat_ner: bool, dup_label: bool, multi_label: bool) -> Self {
+        Self {
+            flat_ner,
+            dup_label,
+            multi_label,
+            span_search: SpanSearch::new(SpanSelection::default(), flat_ner, dup_label, multi_label),
+        }
+    }
+
+    /// Sets the method used to resolve conflicts between spans (see `SpanSelection`)
+    pub fn with_span_selection(mut self, selection: SpanSelection) -> Self {
+        self.span_search = SpanSearch::new(selection, self.flat_ner, self.dup_label, self.multi_label);
+        self
+    }
+
+    fn merge(&self, spans: Vec<Vec<Span>>, context: &ChunkContext) -> Result<Vec<Vec<Span>>> {
+        // gather the spans of each original text, with updated offsets
+        let mut result: Vec<Vec<Span>> = std::iter::repeat_with(Vec::new).take(context.texts.len()).collect();
+        for (chunk_id, chunk_spans) in spans.into_iter().enumerate() {
+            let (text_id, offset) = *context.chunks.get(chunk_id).ok_or(IndexError::new("context.chunks", chunk_id))?;
+            let text_spans = result.get_mut(text_id).ok_or(IndexError::new("context.texts", text_id))?;
+            for span in chunk_spans {
+                let (start, end) = span.offsets();
+                text_spans.push(Span::new(text_id, start + offset, end + offset, span.text().to_string(), span.class().to_string(), span.probability()));
+            }
+        }
+
+        // remove duplicates and apply span search again
+        result.into_iter().map(|mut spans| {
+            // sort by offsets, then class, then decreasing probability so that the first of duplicates is the one to keep
+            spans.sort_unstable_by(|s1, s2| s1.offsets().cmp(&s2.offsets())
+                .then_with(|| s1.class().cmp(s2.class()))
+                .then_with(|| s2.probability().total_cmp(&s1.probability())));
+            spans.dedup_by(|s2, s1| s1.same_offsets(s2) && s1.class() == s2.class());
+            self.span_search.search(&spans)
+        }).collect()
+    }
+}
+
+
+/// Composable: (SpanOutput, ChunkContext) => SpanOutput
+impl Composable<(SpanOutput, ChunkContext), SpanOutput> for ChunkedToSpanOutput {
+    fn apply(&self, input: (SpanOutput, ChunkContext)) -> Result<SpanOutput> {
+        let (output, context) = input;
+        let spans = self.merge(output.spans, &context)?;
+        Ok(SpanOutput::new(context.texts, output.entities, spans))
+    }
+}
+
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() -> Result<()> {
+        // Silent some clippy warnings for unit tests
+        #![allow(clippy::get_first)]
+        #![allow(clippy::unwrap_used)]
+        // Two texts, the first one being split into two overlapping chunks
+        let texts = vec!["James Bond met Auric Goldfinger".to_string(), "Hello Moneypenny".to_string()];
+        let chunks = ["James Bond met Auric", "met Auric Goldfinger", "Hello Moneypenny"];
+        let entities = vec!["person".to_string()];
+        let context = ChunkContext { texts, chunks: vec![(0, 0), (0, 11), (1, 0)] };
+        let person = || "person".to_string();
+        let spans = vec![
+            vec![Span::new(0, 0, 10, "James Bond".into(), person(), 0.9), Span::new(0, 15, 20, "Auric".into(), person(), 0.6)],
+            vec![Span::new(1, 4, 20, "Auric Goldfinger".into(), person(), 0.8), Span::new(1, 4, 9, "Auric".into(), person(), 0.7)],
+            vec![Span::new(2, 6, 16, "Moneypenny".into(), person(), 0.95)],
+        ];
+        let output = SpanOutput::new(chunks.iter().map(|c| c.to_string()).collect(), entities, spans);
+        // Processing
+        let merged = ChunkedToSpanOutput::new(true, false, false).apply((output, context))?;
+        // Assertions
+        assert_eq!(merged.texts.len(), 2);
+        assert_eq!(merged.spans.len(), 2);
+        let first = merged.spans.get(0).unwrap();
+        assert_eq!(first.len(), 2);
+        assert_eq!(first.get(0).unwrap().offsets(), (0, 10));
+        assert_eq!(first.get(1).unwrap().offsets(), (15, 31));
+        assert_eq!(first.get(1).unwrap().text(), "Auric Goldfinger");
+        assert_eq!(first.get(1).unwrap().sequence(), 0);
+        let second = merged.spans.get(1).unwrap();
+        assert_eq!(second.len(), 1);
+        assert_eq!(second.get(0).unwrap().sequence(), 1);
+        assert_eq!(second.get(0).unwrap().offsets(), (6, 16));
+        // Everything rules
+        Ok(())
+    }
+}
@@ -0,0 +1,103 @@
+//! Writer of CoNLL-2003 style files
+
+use std::io::Write;
+use crate::util::result::Result;
+use super::tagged::TaggedOutput;
+
+
+/// Writes tagged outputs (see `SpanOutputToTagged`) in the CoNLL-2003 style: one word per line followed by
+/// its tags (separated by spaces), and a blank line after each text:
+///
+/// ```text
+/// -DOCSTART- O
+///
+/// My O
+/// name O
+/// is O
+/// James B-person
+/// Bond I-person
+///
+/// ```
+///
+/// The `-DOCSTART-` line is written once, before the first text, unless disabled. Words containing
+/// whitespace (which the default splitter never produces) have it replaced by underscores.
+pub struct ConllWriter<W> {
+    writer: W,
+    docstart: bool,
+    started: bool,
+}
+
+impl<W: Write> ConllWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer, docstart: true, started: false }
+    }
+
+    /// Whether to write the `-DOCSTART-` line (default: true)
+    pub fn with_docstart(mut self, docstart: bool) -> Self {
+        self.docstart = docstart;
+        self
+    }
+
+    /// Writes all the texts of the given output
+    pub fn write(&mut self, output: &TaggedOutput) -> Result<()> {
+        if !self.started && self.docstart {
+            let tags = vec!["O"; output.columns].join(" ");
+            writeln!(self.writer, "-DOCSTART- {tags}")?;
+            writeln!(self.writer)?;
+        }
+        self.started = true;
+        for sequence in &output.sequences {
+            for (word, tags) in sequence.words.iter().zip(&sequence.tags) {
+                let word: String = word.text().chars().map(|c| if c.is_whitespace() { '_' } else { c }).collect();
+                writeln!(self.writer, "{word} {}", tags.join(" "))?;
+            }
+            writeln!(self.writer)?;
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        Ok(self.writer.flush()?)
+    }
+
+    /// Returns the underlying writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    // Silent some clippy warnings for unit tests
+    #![allow(clippy::get_first)]
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use composable::Composable;
+    use crate::text::{span::Span, splitter::RegexSplitter};
+    use crate::model::output::{decoded::SpanOutput, tagged::{SpanOutputToTagged, Scheme, Nesting}};
+    use crate::eval::gold;
+
+    #[test]
+    fn test() -> Result<()> {
+        let output = SpanOutput::new(
+            vec!["My name is James Bond".to_string(), "I drive an Aston Martin".to_string()],
+            vec!["person".to_string(), "vehicle".to_string()],
+            vec![vec![Span::new(0, 11, 21, "James Bond".into(), "person".into(), 0.9)], vec![Span::new(1, 11, 23, "Aston Martin".into(), "vehicle".into(), 0.9)]],
+        );
+        let tagged = SpanOutputToTagged::new(RegexSplitter::default(), Scheme::Bioes, Nesting::Outermost).apply(output)?;
+        // Processing
+        let mut writer = ConllWriter::new(Vec::new());
+        writer.write(&tagged)?;
+        let conll = String::from_utf8(writer.into_inner())?;
+        // Assertions
+        assert!(conll.starts_with("-DOCSTART- O\n\nMy O\nname O\nis O\nJames B-person\nBond E-person\n\nI O\n"));
+        // can be read back as gold annotations
+        let documents = gold::read_conll(conll.as_bytes())?;
+        assert_eq!(documents.len(), 2);
+        assert_eq!(documents.get(1).unwrap().spans.get(0).unwrap().text(), "Aston Martin");
+        // Everything rules
+        Ok(())
+    }
+}
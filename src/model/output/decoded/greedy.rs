@@ -63,6 +63,7 @@ impl GreedySearch {
     /// * And `multi_label=true` allows for overlapping spans with *different* labels.
     /// 
     /// The checks are only relative to the previous span, as we expect them to be sorted by offset.    
+    #[allow(clippy::if_same_then_else)]
     fn accept(&self, s1: &Span, s2: &Span) -> bool {                
         // if there is no overlap, we accept immediately
         if s1.is_disjoint(s2) { true }
@@ -5,11 +5,21 @@ pub mod token;
 pub mod token_flat;
 pub mod sort;
 pub mod greedy;
+pub mod optimal;
+pub mod search;
+pub mod offsets;
 
 use crate::text::span::Span;
 
 /// Represents the final output of the post-processing steps, as a list of spans for each input sequence
+///
+/// With the `serde` feature enabled, it is serialized as:
+/// ```json
+/// { "texts": ["..."], "entities": ["person", "..."], "spans": [[{ "sequence": 0, ... }, ...], ...] }
+/// ```
+/// See `Span` for the schema of individual spans.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SpanOutput {
     pub texts: Vec<String>,
     pub entities: Vec<String>,
@@ -35,4 +45,21 @@ impl std::fmt::Display for SpanOutput {
         }
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// Unit tests
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serde() -> crate::util::result::Result<()> {
+        let span = Span::new(0, 11, 21, "James Bond".to_string(), "person".to_string(), 0.5);
+        let output = SpanOutput::new(vec!["My name is James Bond".to_string()], vec!["person".to_string()], vec![vec![span]]);
+        let json = serde_json::to_string(&output)?;
+        assert_eq!(json, r#"{"texts":["My name is James Bond"],"entities":["person"],"spans":[[{"sequence":0,"start":11,"end":21,"text":"James Bond","class":"person","probability":0.5}]]}"#);
+        let output: SpanOutput = serde_json::from_str(&json)?;
+        assert_eq!(output.spans[0][0].text(), "James Bond");
+        Ok(())
+    }
+}
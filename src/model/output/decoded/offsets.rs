@@ -0,0 +1,69 @@
+//! Conversion of span offsets to another unit (see `OffsetUnit`)
+
+use composable::Composable;
+use crate::util::{error::IndexError, result::Result};
+use crate::text::offsets::{OffsetMap, OffsetUnit};
+use super::SpanOutput;
+
+
+/// Composable: SpanOutput => SpanOutput
+///
+/// Adds offsets in the given unit to each span (see `Span::unit_offsets()`), byte offsets being kept as is.
+/// Nothing is done for bytes.
+pub struct SpanOffsets {
+    unit: OffsetUnit,
+}
+
+impl SpanOffsets {
+    pub fn new(unit: OffsetUnit) -> Self {
+        Self { unit }
+    }
+}
+
+impl Composable<SpanOutput, SpanOutput> for SpanOffsets {
+    fn apply(&self, input: SpanOutput) -> Result<SpanOutput> {
+        if self.unit == OffsetUnit::Byte {
+            return Ok(input);
+        }
+        let mut spans = Vec::with_capacity(input.spans.len());
+        for (i, sequence) in input.spans.into_iter().enumerate() {
+            let text = input.texts.get(i).ok_or(IndexError::new("texts", i))?;
+            let map = OffsetMap::new(text, self.unit);
+            spans.push(sequence.into_iter()
+                .map(|span| map.span(&span).map(|offsets| span.with_unit_offsets(offsets)))
+                .collect::<Result<Vec<_>>>()?);
+        }
+        Ok(SpanOutput::new(input.texts, input.entities, spans))
+    }
+}
+
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    // Silent some clippy warnings for unit tests
+    #![allow(clippy::get_first)]
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use crate::text::{span::Span, offsets::UnitOffsets};
+
+    #[test]
+    fn test() -> Result<()> {
+        let text = "Le café de Zoë";
+        let output = || SpanOutput::new(
+            vec![text.to_string()],
+            vec!["person".to_string()],
+            vec![vec![Span::new(0, 12, 16, "Zoë".to_string(), "person".to_string(), 0.9)]],
+        );
+        // Processing
+        let chars = SpanOffsets::new(OffsetUnit::Char).apply(output())?;
+        let bytes = SpanOffsets::new(OffsetUnit::Byte).apply(output())?;
+        // Assertions
+        let span = chars.spans.get(0).unwrap().get(0).unwrap();
+        assert_eq!(span.offsets(), (12, 16));
+        assert_eq!(span.unit_offsets(), Some(UnitOffsets { unit: OffsetUnit::Char, start: 11, end: 14 }));
+        assert!(bytes.spans.get(0).unwrap().get(0).unwrap().unit_offsets().is_none());
+        // Everything rules
+        Ok(())
+    }
+}
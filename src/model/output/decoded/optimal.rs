@@ -0,0 +1,288 @@
+//! Optimal search is an exact alternative to greedy search, for the second step of span decoding
+
+use std::collections::HashMap;
+use composable::Composable;
+use crate::util::{error::IndexError, result::Result};
+use crate::text::span::Span;
+use super::SpanOutput;
+
+
+/// What optimal search maximizes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Objective {
+    /// Sum of the probabilities of the selected spans
+    Probability,
+    /// Joint log-likelihood of selecting the spans and rejecting the other candidates, that is the sum of
+    /// `log(p)` for the selected spans plus the sum of `log(1-p)` for the others. This is equivalent to
+    /// maximizing the sum of the log-odds `log(p/(1-p))` of the selected spans, so that a span can only be
+    /// selected if its probability is above 0.5.
+    LogProbability,
+}
+
+impl Objective {
+    fn weight(&self, probability: f32) -> f64 {
+        let p = probability as f64;
+        match self {
+            Self::Probability => p,
+            Self::LogProbability => {
+                let p = p.clamp(f64::MIN_POSITIVE, 1.0 - f64::EPSILON);
+                (p / (1.0 - p)).ln()
+            },
+        }
+    }
+}
+
+
+/// Exact decoding: selects the spans of maximum total weight (see `Objective`) such that no pair of
+/// selected spans is in conflict according to the flags (see `GreedySearch` for their meaning).
+///
+/// Unlike greedy search, which only compares each span with the previously kept one, a high-confidence
+/// span cannot be discarded because of a chain of overlaps. When all overlaps are forbidden this is the
+/// classic weighted interval scheduling problem, solved by dynamic programming in `O(n log n)`.
+pub struct OptimalSearch {
+    flat_ner: bool,
+    dup_label: bool,
+    multi_label: bool,
+    objective: Objective,
+}
+
+impl OptimalSearch {
+    /// Creates a new optimal-search performer (see `GreedySearch::new()` for the meaning of the flags)
+    pub fn new(flat_ner: bool, dup_label: bool, multi_label: bool, objective: Objective) -> Self {
+        Self { flat_ner, dup_label, multi_label, objective }
+    }
+
+    /// Performs optimal search. Spans do not need to be sorted, the result is sorted by offsets.
+    pub fn search(&self, spans: &[Span]) -> Result<Vec<Span>> {
+        // spans which do not add to the total are never worth selecting
+        let candidates: Vec<Candidate> = spans.iter()
+            .enumerate()
+            .map(|(index, span)| Candidate { index, span, weight: self.objective.weight(span.probability()) })
+            .filter(|c| c.weight > 0.0)
+            .collect();
+        let mut selected = if self.flat_ner || (!self.dup_label && !self.multi_label) {
+            // any overlap is a conflict
+            Self::schedule(candidates)?
+        }
+        else if self.dup_label && self.multi_label {
+            // no conflict at all
+            candidates
+        }
+        else if self.multi_label {
+            // only overlaps between spans of the same class are conflicts: classes are independent
+            let mut by_class: HashMap<&str, Vec<Candidate>> = HashMap::new();
+            for c in candidates {
+                by_class.entry(c.span.class()).or_default().push(c);
+            }
+            let mut selected = Vec::new();
+            for c in by_class.into_values() {
+                selected.extend(Self::schedule(c)?);
+            }
+            selected
+        }
+        else {
+            // only overlaps between spans of different classes are conflicts
+            Self::blocks(candidates)?
+        };
+        selected.sort_unstable_by_key(|c| (c.span.offsets(), c.index));
+        Ok(selected.into_iter().map(|c| c.span.clone()).collect())
+    }
+
+    /// Weighted interval scheduling: selects the mutually disjoint candidates of maximum total weight
+    fn schedule(mut candidates: Vec<Candidate>) -> Result<Vec<Candidate>> {
+        candidates.sort_unstable_by_key(|c| c.span.offsets().1);
+        let ends: Vec<usize> = candidates.iter().map(|c| c.span.offsets().1).collect();
+        // best[k]: best total using the first k candidates (by end offset)
+        let mut best = Vec::with_capacity(candidates.len() + 1);
+        best.push(0.0f64);
+        // previous[k]: number of candidates (by end offset) which are disjoint from the k-th one (they end before it starts)
+        let mut previous = Vec::with_capacity(candidates.len());
+        for (k, c) in candidates.iter().enumerate() {
+            let disjoint = ends.partition_point(|end| *end < c.span.offsets().0);
+            let total = (*get(&best, k, "best")?).max(*get(&best, disjoint, "best")? + c.weight);
+            best.push(total);
+            previous.push(disjoint);
+        }
+        // backtrack
+        let mut result = Vec::new();
+        let mut k = candidates.len();
+        while k > 0 {
+            if get(&best, k, "best")? > get(&best, k - 1, "best")? {
+                result.push(*get(&candidates, k - 1, "candidates")?);
+                k = *get(&previous, k - 1, "previous")?;
+            }
+            else {
+                k -= 1;
+            }
+        }
+        Ok(result)
+    }
+
+    /// Selection where spans of the same class may overlap, but spans of different classes may not. Selected
+    /// spans then form disjoint blocks, each block holding spans of a single class. Positions being the distinct
+    /// offsets of the candidates, the best selection is found by dynamic programming over blocks of positions.
+    fn blocks(candidates: Vec<Candidate>) -> Result<Vec<Candidate>> {
+        let mut positions: Vec<usize> = candidates.iter().flat_map(|c| { let (s, e) = c.span.offsets(); [s, e] }).collect();
+        positions.sort_unstable();
+        positions.dedup();
+        let position = |offset: usize| positions.partition_point(|p| *p < offset);
+        let mut classes: Vec<&str> = candidates.iter().map(|c| c.span.class()).collect();
+        classes.sort_unstable();
+        classes.dedup();
+        // candidates by start position: (end position, class index, candidate)
+        let mut starting: Vec<Vec<(usize, usize, Candidate)>> = vec![Vec::new(); positions.len()];
+        for c in candidates {
+            let (start, end) = c.span.offsets();
+            let class = classes.partition_point(|class| *class < c.span.class());
+            let start = position(start);
+            starting.get_mut(start).ok_or(IndexError::new("starting", start))?.push((position(end), class, c));
+        }
+        // best[j]: best total with all blocks before position j, choice[j]: the last block (first position, class) if any
+        let mut best = Vec::with_capacity(positions.len() + 1);
+        best.push(0.0f64);
+        let mut choice: Vec<Option<(usize, usize)>> = vec![None];
+        let mut totals = vec![0.0f64; classes.len()];
+        for j in 0..positions.len() {
+            let mut best_j = (*get(&best, j, "best")?, None);
+            // blocks [i, j], for decreasing i, with the total weight of the spans of each class they contain
+            totals.iter_mut().for_each(|t| *t = 0.0);
+            for (i, (before, block_start)) in best.iter().zip(&starting).enumerate().rev() {
+                for (end, class, c) in block_start {
+                    if *end <= j {
+                        *totals.get_mut(*class).ok_or(IndexError::new("totals", *class))? += c.weight;
+                    }
+                }
+                for (class, total) in totals.iter().enumerate() {
+                    if before + total > best_j.0 {
+                        best_j = (before + total, Some((i, class)));
+                    }
+                }
+            }
+            best.push(best_j.0);
+            choice.push(best_j.1);
+        }
+        // backtrack
+        let mut result = Vec::new();
+        let mut j = positions.len();
+        while j > 0 {
+            match get(&choice, j, "choice")? {
+                Some((i, class)) if get(&best, j, "best")? > get(&best, j - 1, "best")? => {
+                    let blocks = starting.get(*i..j).ok_or(IndexError::with("inconsistent block of positions"))?;
+                    result.extend(blocks.iter().flatten().filter(|(end, c, _)| *end < j && c == class).map(|(_, _, c)| *c));
+                    j = *i;
+                },
+                _ => j -= 1,
+            }
+        }
+        Ok(result)
+    }
+}
+
+
+/// Span considered by the search, along with its index in the input (so that equal spans keep their order)
+/// and its weight (see `Objective`)
+#[derive(Debug, Clone, Copy)]
+struct Candidate<'a> {
+    index: usize,
+    span: &'a Span,
+    weight: f64,
+}
+
+
+/// Checked access to the tables of the dynamic programs
+fn get<'a, T>(slice: &'a [T], index: usize, desc: &str) -> Result<&'a T> {
+    Ok(slice.get(index).ok_or(IndexError::new(desc, index))?)
+}
+
+
+/// Composable: SpanOutput => SpanOutput
+impl Composable<SpanOutput, SpanOutput> for OptimalSearch {
+    fn apply(&self, input: SpanOutput) -> Result<SpanOutput> {
+        let spans = input.spans
+            .iter()
+            .map(|s| self.search(s))
+            .collect::<Result<_>>()?;
+        Ok(SpanOutput::new(input.texts, input.entities, spans))
+    }
+}
+
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    // Silent some clippy warnings for unit tests
+    #![allow(clippy::get_first)]
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use super::super::greedy::GreedySearch;
+
+    fn span(start: usize, end: usize, class: &str, probability: f32) -> Span {
+        Span::new(0, start, end, format!("{start}-{end}"), class.to_string(), probability)
+    }
+
+    fn offsets(spans: &[Span]) -> Vec<(usize, usize)> {
+        spans.iter().map(|s| s.offsets()).collect()
+    }
+
+    #[test]
+    fn test_chain() -> Result<()> {
+        // a chain of overlaps: the middle span is the most probable one, but not the best choice
+        let spans = [span(0, 5, "person", 0.6), span(4, 10, "person", 0.7), span(9, 15, "person", 0.6)];
+        // Processing
+        let greedy = GreedySearch::new(true, false, false).search(&spans);
+        let optimal = OptimalSearch::new(true, false, false, Objective::Probability).search(&spans)?;
+        let optimal_log = OptimalSearch::new(true, false, false, Objective::LogProbability).search(&spans)?;
+        // Assertions
+        assert_eq!(offsets(&greedy), vec![(4, 10)]);
+        assert_eq!(offsets(&optimal), vec![(0, 5), (9, 15)]);
+        // log-odds: 2*log(1.5) < log(7/3)
+        assert_eq!(offsets(&optimal_log), vec![(4, 10)]);
+        // Everything rules
+        Ok(())
+    }
+
+    #[test]
+    fn test_log_probability() -> Result<()> {
+        // spans below 0.5 are never selected with the log-probability objective
+        let spans = [span(0, 5, "person", 0.45), span(10, 15, "person", 0.55)];
+        let optimal = OptimalSearch::new(true, false, false, Objective::LogProbability).search(&spans)?;
+        assert_eq!(offsets(&optimal), vec![(10, 15)]);
+        let optimal = OptimalSearch::new(true, false, false, Objective::Probability).search(&spans)?;
+        assert_eq!(offsets(&optimal), vec![(0, 5), (10, 15)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_flags() -> Result<()> {
+        let spans = [span(0, 10, "person", 0.9), span(5, 15, "person", 0.8), span(12, 20, "location", 0.95), span(0, 10, "location", 0.7)];
+        // Processing: overlaps allowed for different classes only (each class is independent)
+        let multi = OptimalSearch::new(false, false, true, Objective::Probability).search(&spans)?;
+        // overlaps allowed for the same class only: {A, B} = 1.7 < {A, C} = 1.85
+        let dup = OptimalSearch::new(false, true, false, Objective::Probability).search(&spans)?;
+        // everything allowed
+        let all = OptimalSearch::new(false, true, true, Objective::Probability).search(&spans)?;
+        // nothing allowed (regardless of the other flags)
+        let flat = OptimalSearch::new(true, true, true, Objective::Probability).search(&spans)?;
+        // Assertions
+        assert_eq!(offsets(&multi), vec![(0, 10), (0, 10), (12, 20)]);
+        assert_eq!(offsets(&dup), vec![(0, 10), (12, 20)]);
+        assert_eq!(dup.get(0).unwrap().class(), "person");
+        assert_eq!(all.len(), 4);
+        assert_eq!(offsets(&flat), vec![(0, 10), (12, 20)]);
+        // Everything rules
+        Ok(())
+    }
+
+    #[test]
+    fn test_blocks() -> Result<()> {
+        // overlapping spans of the same class form a block, which must not overlap other classes
+        let spans = [span(0, 4, "person", 0.6), span(3, 8, "person", 0.6), span(7, 12, "person", 0.6), span(5, 6, "location", 0.5), span(11, 14, "location", 0.9)];
+        // Processing
+        let dup = OptimalSearch::new(false, true, false, Objective::Probability).search(&spans)?;
+        // Assertions: the person block [0, 8] (1.2) and the second location (0.9) beat the whole person block (1.8),
+        // as well as the first person and both locations (2.0)
+        assert_eq!(offsets(&dup), vec![(0, 4), (3, 8), (11, 14)]);
+        // Everything rules
+        Ok(())
+    }
+}
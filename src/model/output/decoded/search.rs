@@ -0,0 +1,48 @@
+//! Selection of the final spans, using the method specified by the parameters
+
+use composable::Composable;
+use crate::util::result::Result;
+use crate::text::span::Span;
+use crate::model::params::SpanSelection;
+use super::SpanOutput;
+use super::greedy::GreedySearch;
+use super::optimal::{OptimalSearch, Objective};
+
+
+/// Either greedy or optimal search, depending on the `SpanSelection` parameter
+pub enum SpanSearch {
+    Greedy(GreedySearch),
+    Optimal(OptimalSearch),
+}
+
+impl SpanSearch {
+    /// See `GreedySearch::new()` for the meaning of the flags
+    pub fn new(selection: SpanSelection, flat_ner: bool, dup_label: bool, multi_label: bool) -> Self {
+        match selection {
+            SpanSelection::Greedy => Self::Greedy(GreedySearch::new(flat_ner, dup_label, multi_label)),
+            SpanSelection::MaxProbability => Self::Optimal(OptimalSearch::new(flat_ner, dup_label, multi_label, Objective::Probability)),
+            SpanSelection::MaxLogProbability => Self::Optimal(OptimalSearch::new(flat_ner, dup_label, multi_label, Objective::LogProbability)),
+        }
+    }
+
+    /// Performs the search
+    ///
+    /// Note: spans are supposed to be sorted by start, and then end, offsets.
+    pub fn search(&self, spans: &[Span]) -> Result<Vec<Span>> {
+        match self {
+            Self::Greedy(greedy) => Ok(greedy.search(spans)),
+            Self::Optimal(optimal) => optimal.search(spans),
+        }
+    }
+}
+
+
+/// Composable: SpanOutput => SpanOutput
+impl Composable<SpanOutput, SpanOutput> for SpanSearch {
+    fn apply(&self, input: SpanOutput) -> Result<SpanOutput> {
+        match self {
+            Self::Greedy(greedy) => greedy.apply(input),
+            Self::Optimal(optimal) => optimal.apply(input),
+        }
+    }
+}
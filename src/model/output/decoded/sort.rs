@@ -15,8 +15,8 @@ impl Composable<SpanOutput, SpanOutput> for SpanSort {
         for sequence in &mut spans {
             // "Unstable" sort (which is perfectly safe despite the name ;) is more efficient, and sufficient 
             // in our case as we don't need to preserve the initial order of equal elements. Also note that
-            // calling `cmp()` on a tuple does exactly what we ant here (sort by start, then end, offsets).
-            sequence.sort_unstable_by(|s1, s2| s1.offsets().cmp(&s2.offsets()));
+            // comparing tuples does exactly what we want here (sort by start, then end, offsets).
+            sequence.sort_unstable_by_key(|s| s.offsets());
         }
         Ok(SpanOutput::new(input.texts, input.entities, spans))
     }
@@ -1,11 +1,13 @@
 //! First step of span decoding (in span mode)
 
+use std::collections::HashMap;
 use composable::Composable;
 use crate::util::math::sigmoid;
-use crate::util::result::Result;
+use crate::util::{error::{GlinerError, IndexError}, result::Result};
 use crate::text::span::Span;
 use crate::model::pipeline::context::EntityContext;
 use crate::model::output::tensors::TensorOutput;
+use crate::model::params::class_threshold;
 use super::SpanOutput;
 
 
@@ -15,6 +17,7 @@ use super::SpanOutput;
 /// Note: greedy search is not included in this step and must be applied subsequently.
 pub struct TensorsToDecoded {
     threshold: f32,
+    class_thresholds: HashMap<String, f32>,
     max_width: usize,
 }
 
@@ -22,27 +25,37 @@ impl TensorsToDecoded {
     pub fn new(threshold: f32, max_width: usize) -> Self {
         Self { 
             threshold,
+            class_thresholds: HashMap::new(),
             max_width,
         }
     }
 
+    /// Sets thresholds for specific classes (by label), overriding the global one
+    pub fn with_class_thresholds(mut self, class_thresholds: HashMap<String, f32>) -> Self {
+        self.class_thresholds = class_thresholds;
+        self
+    }
+
     fn decode(&self, input: &TensorOutput) -> Result<Vec<Vec<Span>>> {        
         // prepare output vector
         let batch_size = input.context.texts.len();
         let mut result: Vec<Vec<Span>> = Vec::new();
 
         // look for logits and check its shape
-        let logits = input.tensors.get("logits").ok_or("logits not found in model output")?;
-        self.check_shape(logits.shape()?, &input.context)?;
+        let logits = input.tensors.get("logits").ok_or_else(|| GlinerError::Schema("logits not found in model output".to_string()))?;
+        self.check_shape(logits.shape(), &input.context)?;
         
         // extract the actual array
-        let array = logits.try_extract_tensor::<f32>()?;
+        let array = logits.as_f32()?;
+
+        // threshold of each class
+        let thresholds: Vec<f32> = input.context.entities.iter().map(|e| class_threshold(self.threshold, &self.class_thresholds, e)).collect();
 
         // iterate over the sequences
         for sequence_id in 0..batch_size {
             // get a slice for the current sequence (1st dimension)
             let sequence = array.slice(ndarray::s![sequence_id, .., .., ..]);
-            let num_tokens = input.context.tokens.get(sequence_id).unwrap().len();
+            let num_tokens = input.context.tokens.get(sequence_id).ok_or(IndexError::new("context.tokens", sequence_id))?.len();
             //println!("{:?}", sequence.map(|x| crate::util::math::sigmoid(*x)));
             
             // prepare the list of spans for this sequence
@@ -56,7 +69,7 @@ impl TensorsToDecoded {
                 }
                 // check that the score is above threshold (otherwise continue)
                 let score = sigmoid(*score);
-                if score >= self.threshold {
+                if score >= *thresholds.get(class).ok_or(IndexError::new("thresholds", class))? {
                     // if yes, create the span
                     spans.push(input.context.create_span(sequence_id, start, start+end, class, score)?);
                 }
@@ -76,7 +89,7 @@ impl TensorsToDecoded {
     fn check_shape(&self, actual_shape: Vec<i64>, context: &EntityContext) -> Result<()> {
         let expected_shape = vec![context.texts.len() as i64, context.num_words as i64, self.max_width as i64, context.entities.len() as i64];
         if actual_shape != expected_shape {
-            Err("unexpected logits shape".into())
+            Err(GlinerError::ShapeMismatch { tensor: "logits".to_string(), expected: expected_shape, actual: actual_shape }.into())
         }
         else {
             Ok(())
@@ -85,7 +98,7 @@ impl TensorsToDecoded {
 
 }
 
-impl Composable<TensorOutput<'_>, SpanOutput> for TensorsToDecoded {
+impl Composable<TensorOutput, SpanOutput> for TensorsToDecoded {
     fn apply(&self, input: TensorOutput) -> Result<SpanOutput> {        
         let decoded = self.decode(&input)?;
         Ok(SpanOutput::new(input.context.texts, input.context.entities, decoded))
@@ -1,11 +1,13 @@
 //! First step of span decoding (in token mode)
 
+use std::collections::HashMap;
 use composable::Composable;
-use crate::util::result::Result;
+use crate::util::{error::{GlinerError, IndexError}, result::Result};
 use crate::util::math::sigmoid;
 use crate::text::span::Span;
 use crate::model::pipeline::context::EntityContext;
 use crate::model::output::tensors::TensorOutput;
+use crate::model::params::class_threshold;
 use super::SpanOutput;
 
 /// Decoding method for token mode.
@@ -20,28 +22,39 @@ use super::SpanOutput;
 /// step in the pipeline).
 pub struct TensorsToDecoded {
     threshold: f32,
+    class_thresholds: HashMap<String, f32>,
 }
 
 impl TensorsToDecoded {
     pub fn new(threshold: f32) -> Self {
         Self { 
             threshold,
+            class_thresholds: HashMap::new(),
         }
     }
 
+    /// Sets thresholds for specific classes (by label), overriding the global one
+    pub fn with_class_thresholds(mut self, class_thresholds: HashMap<String, f32>) -> Self {
+        self.class_thresholds = class_thresholds;
+        self
+    }
+
     fn decode(&self, input: &TensorOutput) -> Result<Vec<Vec<Span>>> {
         // prepare output vector
         let batch_size = input.context.texts.len();
         let mut result: Vec<Vec<Span>> = std::iter::repeat_with(Vec::new).take(batch_size).collect();
 
         // look for logits and check its shape
-        let logits = input.tensors.get("logits").ok_or("logits not found in model output")?;
-        self.check_shape(logits.shape()?, &input.context)?;
+        let logits = input.tensors.get("logits").ok_or_else(|| GlinerError::Schema("logits not found in model output".to_string()))?;
+        self.check_shape(logits.shape(), &input.context)?;
     
         // extract the actual array
-        let array  = logits.try_extract_tensor::<f32>()?;
+        let array  = logits.as_f32()?;
         //println!("{:?}", array.map(|x| crate::util::math::sigmoid(*x)));
 
+        // threshold of each class
+        let thresholds: Vec<f32> = input.context.entities.iter().map(|e| class_threshold(self.threshold, &self.class_thresholds, e)).collect();
+
         // iterate over sequences
         for sequence_id in 0..batch_size {
             // get a slice for the current sequence (2nd dimension)
@@ -53,11 +66,12 @@ impl TensorsToDecoded {
             let scores_inside = scores.slice(ndarray::s![2, .., ..]);            
 
             // generate all possible spans and iterate over them
-            for span in self.generate_spans(&scores_start, &scores_end) {
+            for span in self.generate_spans(&scores_start, &scores_end, &thresholds) {
                 // compute score
-                let score = self.compute_span_score(span, &scores_inside);
+                let threshold = *thresholds.get(span.2).ok_or(IndexError::new("thresholds", span.2))?;
+                let score = self.compute_span_score(span, &scores_inside, threshold);
                 // reject span if score is below threshold
-                if score < self.threshold {
+                if score < threshold {
                     continue
                 }                
                 // create actual span
@@ -72,22 +86,22 @@ impl TensorsToDecoded {
 
     /// Generates all possible `(i,j,c)` spans where:
     /// * `i <= j`
-    /// * `score(i) >= threshold`
-    /// * `score(j) >= threshold`.
+    /// * `score(i) >= threshold(c)`
+    /// * `score(j) >= threshold(c)`.
     /// * `c` == `class(i) == class(j)`
-    fn generate_spans(&self, scores_start: &ndarray::ArrayView2::<f32>, scores_end: &ndarray::ArrayView2::<f32>) -> Vec<(usize, usize, usize)> {
+    fn generate_spans(&self, scores_start: &ndarray::ArrayView2::<f32>, scores_end: &ndarray::ArrayView2::<f32>, thresholds: &[f32]) -> Vec<(usize, usize, usize)> {
         assert!(scores_start.dim() == scores_end.dim());
         let (num_tokens, num_classes) = scores_start.dim();
         let mut result = Vec::new();
-        for class in 0..num_classes {
+        for (class, threshold) in thresholds.iter().enumerate().take(num_classes) {
             for start in 0..num_tokens {            
                 let score_start = sigmoid(*scores_start.get((start, class)).unwrap());
-                if score_start < self.threshold {
+                if score_start < *threshold {
                     continue
                 }
                 for end in start..num_tokens {
                     let score_end = sigmoid(*scores_end.get((end, class)).unwrap());
-                    if score_end < self.threshold {
+                    if score_end < *threshold {
                         continue
                     }
                     result.push((start, end, class));
@@ -101,13 +115,13 @@ impl TensorsToDecoded {
     /// Computes the score of a span, defined as the mean of the inside scores (see above).
     /// Spans with one or more inside scores below the threshold will return a zero score, 
     /// since they should be discarded.
-    fn compute_span_score(&self, span: (usize, usize, usize), scores_inside: &ndarray::ArrayView2<f32>) -> f32 {
+    fn compute_span_score(&self, span: (usize, usize, usize), scores_inside: &ndarray::ArrayView2<f32>, threshold: f32) -> f32 {
         let (start, end, class) = span;
         assert!(end >= start);
         let mut sum = 0f32;
         for i in start..end+1 {
             let score_inside = sigmoid(*scores_inside.get((i, class)).unwrap());
-            if score_inside < self.threshold {
+            if score_inside < threshold {
                 return 0.;
             }
             sum += score_inside;
@@ -122,7 +136,7 @@ impl TensorsToDecoded {
     fn check_shape(&self, actual_shape: Vec<i64>, context: &EntityContext) -> Result<()> {
         let expected_shape = vec![3, context.texts.len() as i64, context.num_words as i64, context.entities.len() as i64];
         if actual_shape != expected_shape {
-            Err("unexpected logits shape".into())
+            Err(GlinerError::ShapeMismatch { tensor: "logits".to_string(), expected: expected_shape, actual: actual_shape }.into())
         }
         else {
             Ok(())
@@ -131,7 +145,7 @@ impl TensorsToDecoded {
 
 }
 
-impl Composable<TensorOutput<'_>, SpanOutput> for TensorsToDecoded {
+impl Composable<TensorOutput, SpanOutput> for TensorsToDecoded {
     fn apply(&self, input: TensorOutput) -> Result<SpanOutput> {        
         let decoded = self.decode(&input)?;
         Ok(SpanOutput::new(input.context.texts, input.context.entities, decoded))
@@ -1,11 +1,13 @@
 //! Experimental alternative for the first step of span decoding (in token mode)
 
 use std::iter;
+use std::collections::HashMap;
 use composable::Composable;
-use crate::util::result::Result;
+use crate::util::{error::{GlinerError, IndexError}, result::Result};
 use crate::model::output::tensors::TensorOutput;
 use crate::{model::pipeline::context::EntityContext, text::span::Span};
 use crate::util::math::sigmoid;
+use crate::model::params::class_threshold;
 use super::SpanOutput;
 
 
@@ -16,6 +18,7 @@ use super::SpanOutput;
 /// a much more readable way, basing on the four-dimensional output tensor.
 pub struct FlatTokenDecoder {
     threshold: f32,
+    class_thresholds: HashMap<String, f32>,
 }
 
 
@@ -24,6 +27,7 @@ impl FlatTokenDecoder {
     fn new(threshold: f32) -> Self {
         Self {
             threshold,
+            class_thresholds: HashMap::new(),
         }
     }
 
@@ -40,17 +44,23 @@ impl FlatTokenDecoder {
         // prepare the set of spans
         let mut spans: Vec<Vec<Span>> = iter::repeat_with(Vec::new).take(batch_size).collect();
 
+        // threshold of each class
+        let thresholds: Vec<f32> = input.entities.iter().map(|e| class_threshold(self.threshold, &self.class_thresholds, e)).collect();
+
         // iterate over the whole vector
         for start_idx in 0..position_padding {
+            // retrieve the class and its threshold
+            let class = start_idx % num_entities;
+            let threshold = *thresholds.get(class).ok_or(IndexError::new("thresholds", class))?;
+
             // check the start token score is above threshold, otherwise continue
-            if sigmoid(Self::get(model_output, start_idx)) < self.threshold {
+            if sigmoid(Self::get(model_output, start_idx)) < threshold {
                 continue
             }
 
             // retrieve the appropriate indices
             let sequence_id = (start_idx / sequence_padding) % batch_size;
             let start_token = (start_idx / token_padding) % input.num_words;
-            let class = start_idx % num_entities;
 
             // accumulators to compute the mean score of inside tokens
             let mut sum = 0f32;
@@ -62,10 +72,10 @@ impl FlatTokenDecoder {
 
             while (((end_idx / sequence_padding) % batch_size) == sequence_id) && (end_idx < 2 * position_padding) {
                 // check the end token score is above threshold, otherwise continue
-                if sigmoid(Self::get(model_output, end_idx)) >= self.threshold {
+                if sigmoid(Self::get(model_output, end_idx)) >= threshold {
                     // we won't consider a span at all if it contains a score below the threshold
                     let score = sigmoid(Self::get(model_output, end_idx + position_padding));
-                    if score < self.threshold {
+                    if score < threshold {
                         break
                     }
                     // consume next inside token and update the results
@@ -106,12 +116,18 @@ impl TensorsToDecoded {
             decoder: FlatTokenDecoder::new(threshold)
         }
     }
+
+    /// Sets thresholds for specific classes (by label), overriding the global one
+    pub fn with_class_thresholds(mut self, class_thresholds: HashMap<String, f32>) -> Self {
+        self.decoder.class_thresholds = class_thresholds;
+        self
+    }
 }
 
-impl Composable<TensorOutput<'_>, SpanOutput> for TensorsToDecoded {
+impl Composable<TensorOutput, SpanOutput> for TensorsToDecoded {
     fn apply(&self, input: TensorOutput) -> Result<SpanOutput> {        
-        let logits = input.tensors.get("logits").ok_or("logits not found in model output")?;
-        let (_shape, logits) = logits.try_extract_raw_tensor::<f32>()?;
+        let logits = input.tensors.get("logits").ok_or_else(|| GlinerError::Schema("logits not found in model output".to_string()))?;
+        let logits = logits.as_f32_slice()?;
         let spans = self.decoder.decode(logits, &input.context)?;        
         Ok(SpanOutput::new(input.context.texts, input.context.entities, spans))      
     }
@@ -0,0 +1,132 @@
+//! Export to brat standoff files
+
+use std::fmt::Write;
+use std::path::Path;
+use composable::Composable;
+use crate::util::result::Result;
+use crate::text::{span::Span, offsets::UnitOffsets};
+use super::super::{decoded::SpanOutput, relation::{Relation, RelationOutput}};
+use super::{char_offsets, arguments};
+
+
+/// A brat document: the text (`.txt` file) and its annotations (`.ann` file)
+#[derive(Debug, Clone)]
+pub struct BratDocument {
+    pub text: String,
+    pub annotations: String,
+}
+
+impl BratDocument {
+    /// Writes `<name>.txt` and `<name>.ann` in the given directory
+    pub fn write<P: AsRef<Path>>(&self, dir: P, name: &str) -> Result<()> {
+        std::fs::write(dir.as_ref().join(format!("{name}.txt")), &self.text)?;
+        std::fs::write(dir.as_ref().join(format!("{name}.ann")), &self.annotations)?;
+        Ok(())
+    }
+}
+
+
+/// Composable: SpanOutput => Vec<BratDocument>, or (SpanOutput, RelationOutput) => Vec<BratDocument>
+///
+/// Each text becomes a document, with one `T` line per entity and one `R` line per relation (the
+/// identifier being followed by a tab, as well as the offsets of entities):
+///
+/// ```text
+/// T1  person 0 10  Bill Gates
+/// T2  company 53 62  Microsoft
+/// R1  founded Arg1:T1 Arg2:T2
+/// ```
+///
+/// Whitespace in labels is replaced by underscores, and relations which arguments cannot be found among the
+/// entities are ignored. Probabilities can be written as annotator notes (`#` lines).
+#[derive(Default)]
+pub struct SpanOutputToBrat {
+    scores: bool,
+}
+
+impl SpanOutputToBrat {
+    /// Whether to write probabilities as annotator notes (default: false)
+    pub fn with_scores(mut self, scores: bool) -> Self {
+        self.scores = scores;
+        self
+    }
+
+    fn documents(&self, entities: SpanOutput, relations: Option<&RelationOutput>) -> Result<Vec<BratDocument>> {
+        let relations = super::relations(&entities, relations)?;
+        entities.texts.into_iter().zip(&entities.spans).zip(relations)
+            .map(|((text, spans), relations)| {
+                let annotations = self.annotations(&text, spans, relations)?;
+                Ok(BratDocument { text, annotations })
+            })
+            .collect()
+    }
+
+    fn annotations(&self, text: &str, spans: &[Span], relations: &[Relation]) -> Result<String> {
+        let offsets = char_offsets(text);
+        let mut result = String::new();
+        let mut notes = 0;
+        for (i, span) in spans.iter().enumerate() {
+            let UnitOffsets { start, end, .. } = offsets.span(span)?;
+            // brat does not allow line breaks within the text of an annotation
+            let span_text = span.text().replace(['\n', '\r'], " ");
+            writeln!(result, "T{}\t{} {start} {end}\t{span_text}", i + 1, label(span.class()))?;
+            if self.scores {
+                notes += 1;
+                writeln!(result, "#{notes}\tAnnotatorNotes T{}\t{}", i + 1, span.probability())?;
+            }
+        }
+        let arguments = relations.iter().filter_map(|r| arguments(r, spans).map(|a| (r, a)));
+        for (i, (relation, (subject, object))) in arguments.enumerate() {
+            writeln!(result, "R{}\t{} Arg1:T{} Arg2:T{}", i + 1, label(relation.class()), subject + 1, object + 1)?;
+            if self.scores {
+                notes += 1;
+                writeln!(result, "#{notes}\tAnnotatorNotes R{}\t{}", i + 1, relation.probability())?;
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl Composable<SpanOutput, Vec<BratDocument>> for SpanOutputToBrat {
+    fn apply(&self, input: SpanOutput) -> Result<Vec<BratDocument>> {
+        self.documents(input, None)
+    }
+}
+
+impl Composable<(SpanOutput, RelationOutput), Vec<BratDocument>> for SpanOutputToBrat {
+    fn apply(&self, input: (SpanOutput, RelationOutput)) -> Result<Vec<BratDocument>> {
+        self.documents(input.0, Some(&input.1))
+    }
+}
+
+
+fn label(label: &str) -> String {
+    label.split_whitespace().collect::<Vec<_>>().join("_")
+}
+
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    // Silent some clippy warnings for unit tests
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use super::super::tests::{entities, relations};
+
+    #[test]
+    fn test_entities() -> Result<()> {
+        let documents = SpanOutputToBrat::default().apply(entities())?;
+        assert_eq!(documents.len(), 1);
+        assert_eq!(documents.first().unwrap().annotations, "T1\tperson 0 10\tÉmile Zola\nT2\tbook 17 25\tGerminal\nT3\tperson 27 31\tZola\nT4\tcity 44 49\tParis\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_relations() -> Result<()> {
+        let documents = SpanOutputToBrat::default().with_scores(true).apply((entities(), relations()?))?;
+        let annotations = &documents.first().unwrap().annotations;
+        assert!(annotations.contains("#1\tAnnotatorNotes T1\t0.9\n"));
+        assert!(annotations.ends_with("R1\tborn_in Arg1:T3 Arg2:T4\n#5\tAnnotatorNotes R1\t0.8\nR2\twrote Arg1:T1 Arg2:T2\n#6\tAnnotatorNotes R2\t0.8\n"));
+        Ok(())
+    }
+}
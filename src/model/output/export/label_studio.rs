@@ -0,0 +1,159 @@
+//! Export to Label Studio tasks with predictions
+
+use composable::Composable;
+use serde_json::{json, Value};
+use crate::util::result::Result;
+use crate::text::{span::Span, offsets::{OffsetMap, OffsetUnit, UnitOffsets}};
+use super::super::{decoded::SpanOutput, relation::{Relation, RelationOutput}};
+use super::arguments;
+
+
+/// Composable: SpanOutput => Vec<Value>, or (SpanOutput, RelationOutput) => Vec<Value>
+///
+/// Each text becomes a Label Studio task with one prediction, to be imported as a JSON array:
+///
+/// ```json
+/// { "data": { "text": "..." }, "predictions": [{ "model_version": "gline-rs", "score": 0.99, "result": [
+///   { "id": "e1", "from_name": "label", "to_name": "text", "type": "labels", "score": 0.99,
+///     "value": { "start": 0, "end": 10, "text": "Bill Gates", "labels": ["person"] } },
+///   { "from_id": "e1", "to_id": "e2", "type": "relation", "direction": "right", "labels": ["founded"] }
+/// ] }] }
+/// ```
+///
+/// `from_name` and `to_name` must match the names of the `Labels` and `Text` tags of the labeling
+/// configuration (default: `label` and `text`), and the data key must match the `value` of the `Text`
+/// tag (default: `text`). Offsets are UTF-16 offsets (as expected by the JavaScript front-end, they differ
+/// from character offsets beyond the Basic Multilingual Plane, e.g. for emojis), and the score of a
+/// prediction is the mean probability of its entities.
+pub struct SpanOutputToLabelStudio {
+    from_name: String,
+    to_name: String,
+    data_key: String,
+    model_version: String,
+}
+
+impl Default for SpanOutputToLabelStudio {
+    fn default() -> Self {
+        Self {
+            from_name: "label".to_string(),
+            to_name: "text".to_string(),
+            data_key: "text".to_string(),
+            model_version: "gline-rs".to_string(),
+        }
+    }
+}
+
+impl SpanOutputToLabelStudio {
+    pub fn with_from_name(mut self, from_name: &str) -> Self {
+        self.from_name = from_name.to_string();
+        self
+    }
+
+    pub fn with_to_name(mut self, to_name: &str) -> Self {
+        self.to_name = to_name.to_string();
+        self
+    }
+
+    pub fn with_data_key(mut self, data_key: &str) -> Self {
+        self.data_key = data_key.to_string();
+        self
+    }
+
+    pub fn with_model_version(mut self, model_version: &str) -> Self {
+        self.model_version = model_version.to_string();
+        self
+    }
+
+    fn tasks(&self, entities: SpanOutput, relations: Option<&RelationOutput>) -> Result<Vec<Value>> {
+        let relations = super::relations(&entities, relations)?;
+        entities.texts.iter().zip(&entities.spans).zip(relations)
+            .map(|((text, spans), relations)| self.task(text, spans, relations))
+            .collect()
+    }
+
+    fn task(&self, text: &str, spans: &[Span], relations: &[Relation]) -> Result<Value> {
+        let offsets = OffsetMap::new(text, OffsetUnit::Utf16);
+        let mut result = Vec::with_capacity(spans.len() + relations.len());
+        for (i, span) in spans.iter().enumerate() {
+            let UnitOffsets { start, end, .. } = offsets.span(span)?;
+            result.push(json!({
+                "id": format!("e{}", i + 1),
+                "from_name": self.from_name,
+                "to_name": self.to_name,
+                "type": "labels",
+                "score": span.probability(),
+                "value": { "start": start, "end": end, "text": span.text(), "labels": [span.class()] },
+            }));
+        }
+        for (relation, (subject, object)) in relations.iter().filter_map(|r| arguments(r, spans).map(|a| (r, a))) {
+            result.push(json!({
+                "from_id": format!("e{}", subject + 1),
+                "to_id": format!("e{}", object + 1),
+                "type": "relation",
+                "direction": "right",
+                "labels": [relation.class()],
+            }));
+        }
+        let score = if spans.is_empty() { 0.0 } else { spans.iter().map(Span::probability).sum::<f32>() / spans.len() as f32 };
+        let mut data = serde_json::Map::new();
+        data.insert(self.data_key.clone(), Value::from(text));
+        Ok(json!({
+            "data": data,
+            "predictions": [{ "model_version": self.model_version, "score": score, "result": result }],
+        }))
+    }
+}
+
+impl Composable<SpanOutput, Vec<Value>> for SpanOutputToLabelStudio {
+    fn apply(&self, input: SpanOutput) -> Result<Vec<Value>> {
+        self.tasks(input, None)
+    }
+}
+
+impl Composable<(SpanOutput, RelationOutput), Vec<Value>> for SpanOutputToLabelStudio {
+    fn apply(&self, input: (SpanOutput, RelationOutput)) -> Result<Vec<Value>> {
+        self.tasks(input.0, Some(&input.1))
+    }
+}
+
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    // Silent some clippy warnings for unit tests
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use super::super::tests::{entities, relations, TEXT};
+
+    #[test]
+    fn test() -> Result<()> {
+        let tasks = SpanOutputToLabelStudio::default().with_from_name("ner").apply((entities(), relations()?))?;
+        assert_eq!(tasks.len(), 1);
+        let task = tasks.first().unwrap();
+        assert_eq!(task["data"]["text"], TEXT);
+        let result = task["predictions"][0]["result"].as_array().unwrap();
+        assert_eq!(result.len(), 6);
+        assert_eq!(result[0]["from_name"], "ner");
+        assert_eq!(result[0]["to_name"], "text");
+        assert_eq!(result[0]["value"], json!({ "start": 0, "end": 10, "text": "Émile Zola", "labels": ["person"] }));
+        assert_eq!(result[3]["value"]["start"], 44);
+        assert_eq!(result[4], json!({ "from_id": "e3", "to_id": "e4", "type": "relation", "direction": "right", "labels": ["born in"] }));
+        assert!((task["predictions"][0]["score"].as_f64().unwrap() - 0.9).abs() < 1e-6);
+        Ok(())
+    }
+
+    #[test]
+    fn test_utf16() -> Result<()> {
+        // the emoji is one character, but two UTF-16 code units
+        let text = "🎬 James Bond";
+        let spans = vec![vec![Span::new(0, 5, 15, "James Bond".to_string(), "person".to_string(), 0.9)]];
+        let output = SpanOutput::new(vec![text.to_string()], vec!["person".to_string()], spans);
+        // Processing
+        let tasks = SpanOutputToLabelStudio::default().apply(output)?;
+        // Assertions
+        let result = tasks.first().unwrap()["predictions"][0]["result"].as_array().unwrap();
+        assert_eq!(result[0]["value"], json!({ "start": 3, "end": 13, "text": "James Bond", "labels": ["person"] }));
+        // Everything rules
+        Ok(())
+    }
+}
@@ -0,0 +1,178 @@
+//! Export to annotated markup documents
+
+use composable::Composable;
+use crate::util::{error::GlinerError, result::Result};
+use crate::text::span::Span;
+use super::super::decoded::SpanOutput;
+
+
+/// Composable: SpanOutput => Vec<String>
+///
+/// Each text (typically an HTML or Markdown source document, see `HtmlNormalizer` and `MarkdownNormalizer`)
+/// is re-emitted with its entities enclosed in tags carrying their class (by default `<mark>` elements with
+/// a `class` attribute):
+///
+/// ```html
+/// <p><mark class="person">Bill Gates</mark> founded <mark class="company">Microsoft</mark>.</p>
+/// ```
+///
+/// Nested entities give nested tags, and entities crossing a previous one (if overlapping spans are
+/// allowed) are ignored. An entity spanning over markup is split into several elements around the
+/// document's own tags, so that they remain properly nested (e.g. `<b>MI</b>6` gives
+/// `<b><mark class="agency">MI</mark></b><mark class="agency">6</mark>`).
+pub struct SpanOutputToMarkup {
+    tag: String,
+    attribute: String,
+}
+
+impl Default for SpanOutputToMarkup {
+    fn default() -> Self {
+        Self {
+            tag: "mark".to_string(),
+            attribute: "class".to_string(),
+        }
+    }
+}
+
+impl SpanOutputToMarkup {
+    /// Name of the enclosing elements (default: `mark`)
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        self.tag = tag.to_string();
+        self
+    }
+
+    /// Name of the attribute carrying the class of the entities (default: `class`)
+    pub fn with_attribute(mut self, attribute: &str) -> Self {
+        self.attribute = attribute.to_string();
+        self
+    }
+
+    fn annotate(&self, text: &str, spans: &[Span]) -> Result<String> {
+        // spans by start, longest first, ignoring the ones crossing a previous one
+        let mut sorted: Vec<&Span> = spans.iter().collect();
+        sorted.sort_by(|s1, s2| s1.offsets().0.cmp(&s2.offsets().0).then_with(|| s2.offsets().1.cmp(&s1.offsets().1)));
+        let mut kept: Vec<&Span> = Vec::with_capacity(sorted.len());
+        for span in sorted {
+            let (start, end) = span.offsets();
+            if kept.iter().all(|k| k.offsets().1 <= start || end <= k.offsets().1) {
+                kept.push(span);
+            }
+        }
+
+        // walk through the text, the enclosing spans being active (outermost first) and their elements
+        // being opened before some text only, and closed before any markup
+        let close = format!("</{}>", self.tag);
+        let mut result = String::with_capacity(text.len() + kept.len() * (2 * self.tag.len() + 16));
+        let mut pending = kept.into_iter().peekable();
+        let mut active: Vec<&Span> = Vec::new();
+        let mut opened = 0;
+        let mut position = 0;
+        loop {
+            while active.last().is_some_and(|last| last.offsets().1 <= position) {
+                if opened == active.len() {
+                    result.push_str(&close);
+                    opened -= 1;
+                }
+                active.pop();
+            }
+            while let Some(span) = pending.next_if(|span| span.offsets().0 <= position) {
+                active.push(span);
+            }
+            if position >= text.len() {
+                break;
+            }
+            if let Some(end) = tag_end(text, position) {
+                // the document's own markup is kept out of the enclosing elements
+                result.push_str(&close.repeat(opened));
+                opened = 0;
+                result.push_str(slice(text, position, end)?);
+                position = end;
+            }
+            else {
+                // text up to the next markup or span boundary
+                let end = text.get(position..).and_then(|rest| rest.char_indices().skip(1).find(|(_, c)| *c == '<')).map_or(text.len(), |(i, _)| position + i);
+                let end = pending.peek().map_or(end, |span| end.min(span.offsets().0));
+                let end = active.last().map_or(end, |span| end.min(span.offsets().1));
+                for span in active.iter().skip(opened) {
+                    result.push_str(&format!("<{} {}=\"{}\">", self.tag, self.attribute, escape(span.class())));
+                }
+                opened = active.len();
+                result.push_str(slice(text, position, end)?);
+                position = end;
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl Composable<SpanOutput, Vec<String>> for SpanOutputToMarkup {
+    fn apply(&self, input: SpanOutput) -> Result<Vec<String>> {
+        input.texts.iter().zip(&input.spans)
+            .map(|(text, spans)| self.annotate(text, spans))
+            .collect()
+    }
+}
+
+
+/// End of the tag (or comment, declaration...) starting at the given offset, if any
+fn tag_end(text: &str, start: usize) -> Option<usize> {
+    let rest = text.get(start..)?.strip_prefix('<')?;
+    if !rest.starts_with(|c: char| c.is_ascii_alphabetic() || c == '/' || c == '!' || c == '?') {
+        return None;
+    }
+    rest.find('>').map(|i| start + i + 2)
+}
+
+fn slice(text: &str, start: usize, end: usize) -> Result<&str> {
+    text.get(start..end).ok_or_else(|| GlinerError::invalid_input(&format!("invalid span offsets: {start}..{end}")).into())
+}
+
+/// Escapes the given value for use in an attribute
+fn escape(value: &str) -> String {
+    value.replace('&', "&amp;").replace('"', "&quot;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    // Silent some clippy warnings for unit tests
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use super::super::tests::entities;
+
+    #[test]
+    fn test() -> Result<()> {
+        let documents = SpanOutputToMarkup::default().apply(entities())?;
+        assert_eq!(documents.len(), 1);
+        assert!(documents.first().unwrap().starts_with("<mark class=\"person\">Émile Zola</mark> wrote <mark class=\"book\">Germinal</mark>."));
+        Ok(())
+    }
+
+    #[test]
+    fn test_nested() -> Result<()> {
+        let text = "<p>Bank of <b>England</b> & co</p>";
+        let span = |start: usize, end: usize, class: &str| Span::new(0, start, end, text[start..end].to_string(), class.to_string(), 0.9);
+        let spans = vec![span(3, 25, "company"), span(14, 21, "country"), span(3, 7, "x&\"y"), span(20, 29, "crossing")];
+        let output = SpanOutput::new(vec![text.to_string()], vec![], vec![spans]);
+        // Processing
+        let documents = SpanOutputToMarkup::default().with_tag("span").with_attribute("data-label").apply(output)?;
+        // Assertions
+        assert_eq!(documents.first().unwrap(), "<p><span data-label=\"company\"><span data-label=\"x&amp;&quot;y\">Bank</span> of </span><b><span data-label=\"company\"><span data-label=\"country\">England</span></span></b> & co</p>");
+        // Everything rules
+        Ok(())
+    }
+
+    #[test]
+    fn test_markup() -> Result<()> {
+        let text = "<p>Agents of <b>MI</b>6 < 7</p>";
+        let span = |start: usize, end: usize, class: &str| Span::new(0, start, end, text[start..end].to_string(), class.to_string(), 0.9);
+        let output = SpanOutput::new(vec![text.to_string()], vec![], vec![vec![span(16, 23, "agency"), span(24, 27, "number")]]);
+        // Processing
+        let documents = SpanOutputToMarkup::default().apply(output)?;
+        // Assertions: elements are split around the tags, but not around other "<" characters
+        assert_eq!(documents.first().unwrap(), "<p>Agents of <b><mark class=\"agency\">MI</mark></b><mark class=\"agency\">6</mark> <mark class=\"number\">< 7</mark></p>");
+        // Everything rules
+        Ok(())
+    }
+}
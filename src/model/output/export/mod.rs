@@ -0,0 +1,107 @@
+//! Exports to annotation tools, to pre-annotate corpora for human correction
+//!
+//! Entities (`SpanOutput`) and optionally relations (`RelationOutput`, the entities being the ones the relation
+//! pipeline was given) can be exported to [brat](https://brat.nlplab.org/standoff.html) (see `brat`) and to
+//! [Label Studio](https://labelstud.io/guide/predictions) (see `label_studio`). Rather than byte offsets, brat
+//! expects character offsets, and Label Studio UTF-16 offsets (like JavaScript strings). Entities can also be
+//! inserted as tags into the texts themselves, typically HTML or Markdown source documents (see `markup`).
+
+pub mod brat;
+pub mod label_studio;
+pub mod markup;
+
+use crate::util::{error::GlinerError, result::Result};
+use crate::text::span::Span;
+use crate::text::offsets::{OffsetMap, OffsetUnit};
+use super::decoded::SpanOutput;
+use super::relation::{Relation, RelationOutput};
+
+
+/// Character offsets of the spans of a text
+fn char_offsets(text: &str) -> OffsetMap {
+    OffsetMap::new(text, OffsetUnit::Char)
+}
+
+
+/// Relations of each text, if any, checking that they refer to the same texts as the entities
+fn relations<'a>(entities: &SpanOutput, relations: Option<&'a RelationOutput>) -> Result<Vec<&'a [Relation]>> {
+    match relations {
+        Some(relations) if relations.relations.len() != entities.spans.len() => {
+            Err(GlinerError::invalid_input("entities and relations do not refer to the same texts").into())
+        },
+        Some(relations) => Ok(relations.relations.iter().map(Vec::as_slice).collect()),
+        None => Ok(vec![&[]; entities.spans.len()]),
+    }
+}
+
+
+/// Indexes of the entities which are the subject and the object of the given relation (within the entities
+/// of the same text), if found. Since only the text of the subject is known, the closest occurrence is chosen.
+fn arguments(relation: &Relation, spans: &[Span]) -> Option<(usize, usize)> {
+    let (start, end) = relation.offsets();
+    let object = spans.iter().position(|s| s.offsets() == (start, end))
+        .or_else(|| closest(spans, relation.object(), start, None))?;
+    let subject = closest(spans, relation.subject(), start, Some(object))?;
+    Some((subject, object))
+}
+
+
+/// Index of the entity with the given text which is the closest to the given offset
+fn closest(spans: &[Span], text: &str, offset: usize, exclude: Option<usize>) -> Option<usize> {
+    spans.iter().enumerate()
+        .filter(|(i, s)| s.text() == text && Some(*i) != exclude)
+        .min_by_key(|(_, s)| s.offsets().0.abs_diff(offset))
+        .map(|(i, _)| i)
+}
+
+
+/// Unit tests
+#[cfg(test)]
+pub(super) mod tests {
+    // Silent some clippy warnings for unit tests
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    pub const TEXT: &str = "Émile Zola wrote Germinal. Zola was born in Paris.";
+
+    /// Entities of `TEXT`, with byte offsets (the first character taking 2 bytes)
+    pub fn entities() -> SpanOutput {
+        let span = |start: usize, end: usize, class: &str| Span::new(0, start, end, TEXT[start..end].to_string(), class.to_string(), 0.9);
+        SpanOutput::new(
+            vec![TEXT.to_string()],
+            vec!["person".to_string(), "book".to_string(), "city".to_string()],
+            vec![vec![span(0, 11, "person"), span(18, 26, "book"), span(28, 32, "person"), span(45, 50, "city")]],
+        )
+    }
+
+    /// "Zola <> born in" Paris, "Émile Zola <> wrote" Germinal
+    pub fn relations() -> Result<RelationOutput> {
+        let relation = |subject: &str, class: &str, start: usize, end: usize| Relation::from(Span::new(0, start, end, TEXT[start..end].to_string(), format!("{subject} <> {class}"), 0.8));
+        Ok(RelationOutput {
+            texts: vec![TEXT.to_string()],
+            entities: vec!["born in".to_string(), "wrote".to_string()],
+            relations: vec![vec![relation("Zola", "born in", 45, 50)?, relation("Émile Zola", "wrote", 18, 26)?]],
+        })
+    }
+
+    #[test]
+    fn test_char_offsets() -> Result<()> {
+        let offsets = char_offsets(TEXT);
+        let span = offsets.span(entities().spans.first().unwrap().first().unwrap())?;
+        assert_eq!((span.start, span.end), (0, 10));
+        assert_eq!(offsets.start(TEXT.len())?, TEXT.chars().count());
+        assert!(offsets.start(TEXT.len() + 1).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_arguments() -> Result<()> {
+        let entities = entities();
+        let spans = entities.spans.first().unwrap();
+        let relations = relations()?;
+        let relations = relations.relations.first().unwrap();
+        assert_eq!(arguments(relations.first().unwrap(), spans), Some((2, 3)));
+        assert_eq!(arguments(relations.last().unwrap(), spans), Some((0, 1)));
+        Ok(())
+    }
+}
@@ -3,4 +3,10 @@
 pub mod tensors;
 pub mod decoded;
 pub mod relation;
+pub mod chunked;
+pub mod normalized;
+pub mod scores;
+pub mod tagged;
+pub mod conll;
+pub mod export;
 
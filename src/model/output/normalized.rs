@@ -0,0 +1,68 @@
+//! Mapping of the spans found in normalized texts back onto the original texts
+
+use composable::Composable;
+use crate::util::{error::IndexError, result::Result};
+use crate::text::span::Span;
+use crate::model::pipeline::context::NormalizationContext;
+use super::decoded::SpanOutput;
+
+
+/// Composable: (SpanOutput, NormalizationContext) => SpanOutput
+///
+/// Maps back the spans found in the normalized texts onto the original texts: offsets refer to the
+/// original texts, and the text of each span is taken from them.
+#[derive(Default)]
+pub struct NormalizedToSpanOutput {}
+
+impl Composable<(SpanOutput, NormalizationContext), SpanOutput> for NormalizedToSpanOutput {
+    fn apply(&self, input: (SpanOutput, NormalizationContext)) -> Result<SpanOutput> {
+        let (output, context) = input;
+        let mut spans = Vec::with_capacity(output.spans.len());
+        for (i, sequence) in output.spans.into_iter().enumerate() {
+            let text = context.texts.get(i).ok_or(IndexError::new("context.texts", i))?;
+            let alignment = context.alignments.get(i).ok_or(IndexError::new("context.alignments", i))?;
+            let mut result = Vec::with_capacity(sequence.len());
+            for span in sequence {
+                let (start, end) = span.offsets();
+                let (start, end) = alignment.original(start, end)?;
+                result.push(Span::new(span.sequence(), start, end, text[start..end].to_string(), span.class().to_string(), span.probability()));
+            }
+            spans.push(result);
+        }
+        Ok(SpanOutput::new(context.texts, output.entities, spans))
+    }
+}
+
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    // Silent some clippy warnings for unit tests
+    #![allow(clippy::get_first)]
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use crate::model::input::{text::TextInput, normalized::RawToNormalized};
+    use crate::text::normalizer::TextNormalizer;
+
+    #[test]
+    fn test() -> Result<()> {
+        let input = TextInput::from_str(&["The  ﬁle of Zoë\u{00A0}Smith"], &["person"])?;
+        let normalizer = TextNormalizer::default();
+        let (normalized, context) = RawToNormalized::new(&normalizer).apply(input)?;
+        let text = normalized.texts.get(0).unwrap();
+        assert_eq!(text, "The file of Zoë Smith");
+        let start = text.find("Zoë").unwrap();
+        let spans = vec![vec![Span::new(0, start, text.len(), "Zoë Smith".to_string(), "person".to_string(), 0.9)]];
+        let output = SpanOutput::new(normalized.texts, normalized.entities, spans);
+        // Processing
+        let output = NormalizedToSpanOutput::default().apply((output, context))?;
+        // Assertions
+        assert_eq!(output.texts, vec!["The  ﬁle of Zoë\u{00A0}Smith"]);
+        let span = output.spans.get(0).unwrap().get(0).unwrap();
+        assert_eq!(span.offsets(), (14, 25));
+        assert_eq!(span.text(), "Zoë\u{00A0}Smith");
+        assert_eq!(span.sequence(), 0);
+        // Everything rules
+        Ok(())
+    }
+}
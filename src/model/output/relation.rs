@@ -1,11 +1,18 @@
 use composable::Composable;
 use crate::model::input::relation::schema::RelationSchema;
 use crate::model::pipeline::context::RelationContext;
-use crate::util::result::Result;
+use crate::util::{error::GlinerError, result::Result};
 use crate::text::span::Span;
 use super::decoded::SpanOutput;
 
 /// Defines the final output of the relation extraction pipeline
+///
+/// With the `serde` feature enabled, it is serialized as:
+/// ```json
+/// { "texts": ["..."], "entities": ["..."], "relations": [[{ "class": "founded", ... }, ...], ...] }
+/// ```
+/// See `Relation` for the schema of individual relations.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RelationOutput {
     pub texts: Vec<String>,
     pub entities: Vec<String>,
@@ -13,6 +20,13 @@ pub struct RelationOutput {
 }
 
 /// Defines an individual relation
+///
+/// With the `serde` feature enabled, it is serialized as:
+/// ```json
+/// { "class": "founded", "subject": "Bill Gates", "object": "Microsoft", "sequence": 0, "start": 54, "end": 63, "probability": 0.99 }
+/// ```
+/// Offsets are byte offsets of the object in the corresponding entry of `RelationOutput::texts`.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Relation {
     /// Relation label
     class: String,
@@ -77,7 +91,7 @@ impl Relation {
             RelationFormatError::new(rel_class).err()
         }
         else {
-            Ok((split.get(0).unwrap().to_string(), split.get(1).unwrap().to_string()))
+            Ok((split.first().unwrap().to_string(), split.get(1).unwrap().to_string()))
         }        
     }
 }
@@ -107,8 +121,10 @@ impl<'a> SpanOutputToRelationOutput<'a> {
 
     fn is_valid(&self, relation: &Relation, context: &RelationContext) -> Result<bool> {
         // check that the class of the object of the given relation if allowed by the relation schema
-        let potential_classes = context.entity_labels.get(relation.object()).ok_or(RelationFormatError::new("unexpected entity found as object"))?;
-        let spec = self.schema.relations().get(relation.class()).ok_or(RelationFormatError::new("unexpected relation class"))?;
+        let potential_classes = context.entity_labels.get(relation.object())
+            .ok_or_else(|| GlinerError::InvalidLabel { label: relation.object().to_string(), message: "unexpected entity found as object".to_string() })?;
+        let spec = self.schema.relations().get(relation.class())
+            .ok_or_else(|| GlinerError::InvalidLabel { label: relation.class().to_string(), message: "unexpected relation class".to_string() })?;
         Ok(spec.allows_one_of_objects(potential_classes))
     }
 }
@@ -142,12 +158,18 @@ impl Composable<(SpanOutput, RelationContext), RelationOutput> for SpanOutputToR
 /// obtained by the relation extraction pipeline. This is likely to be 
 /// an internal error, unless the pipeline was not used correctly.
 pub struct RelationFormatError {
+    label: String,
     message: String,
 }
 
 impl RelationFormatError {
     pub fn new(span_label: &str) -> Self {
-        Self { message: format!("unexpected relation label format: {span_label}") }
+        Self { label: span_label.to_string(), message: format!("unexpected relation label format: {span_label}") }
+    }
+
+    /// The offending label
+    pub fn label(&self) -> &str {
+        &self.label
     }
 
     pub fn err<T>(self) -> Result<T> {
@@ -0,0 +1,215 @@
+//! Raw scores, as an alternative to decoding
+//!
+//! Decoders discard every candidate below the threshold. For downstream analysis (such as computing
+//! precision/recall curves, or tuning thresholds offline without running the model again), the full
+//! probability matrices can be obtained instead, along with the offsets of the words they refer to:
+//!
+//! ```ignore
+//! let output = model.scores(input)?;
+//! let scores = output.scores[0].view()?; // (word, width, class) in span mode
+//! ```
+
+use composable::Composable;
+use ndarray::{ArrayView3, Axis};
+use crate::util::math::sigmoid;
+use crate::util::{error::{GlinerError, IndexError}, result::Result};
+use crate::model::pipeline::context::EntityContext;
+use crate::model::backend::{TensorPipeline, Tensors};
+use super::tensors::TensorOutput;
+
+
+/// Meaning of the three dimensions of the scores of a sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum ScoreLayout {
+    /// `(start_word, width, class)`: probability of the span covering words `start_word..=start_word+width`.
+    /// Spans running past the end of the sequence have a zero probability.
+    Span,
+    /// `(kind, word, class)`: probability of each word being the start (`kind=0`), the end (`kind=1`),
+    /// or inside (`kind=2`) an entity
+    Token,
+}
+
+
+/// Probabilities for one sequence
+///
+/// With the `serde` feature enabled, it is serialized as:
+/// ```json
+/// { "words": [[0, 2], ...], "layout": "span", "shape": [5, 12, 3], "scores": [0.01, ...] }
+/// ```
+/// `scores` being flattened in row-major order.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SequenceScores {
+    /// Start and end offsets of each word within the text
+    pub words: Vec<(usize, usize)>,
+    pub layout: ScoreLayout,
+    pub shape: [usize; 3],
+    pub scores: Vec<f32>,
+}
+
+impl SequenceScores {
+    /// Scores as a 3-dimensional array (see `ScoreLayout`)
+    pub fn view(&self) -> Result<ArrayView3<'_, f32>> {
+        Ok(ArrayView3::from_shape(self.shape, &self.scores)?)
+    }
+}
+
+
+/// Raw scores of each input sequence (see `SequenceScores`)
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScoreOutput {
+    pub texts: Vec<String>,
+    pub entities: Vec<String>,
+    pub scores: Vec<SequenceScores>,
+}
+
+
+/// Composable: TensorOutput => ScoreOutput
+///
+/// Applies the sigmoid to the logits, and trims them to the actual words of each sequence.
+pub struct TensorsToScores {
+    layout: ScoreLayout,
+    max_width: usize,
+}
+
+impl TensorsToScores {
+    /// For span mode, given the maximum span width
+    pub fn span(max_width: usize) -> Self {
+        Self { layout: ScoreLayout::Span, max_width }
+    }
+
+    /// For token mode
+    pub fn token() -> Self {
+        Self { layout: ScoreLayout::Token, max_width: 0 }
+    }
+
+    fn scores(&self, input: &TensorOutput) -> Result<Vec<SequenceScores>> {
+        let context = &input.context;
+        let logits = input.tensors.get("logits").ok_or_else(|| GlinerError::Schema("logits not found in model output".to_string()))?;
+        self.check_shape(logits.shape(), context)?;
+        let array = logits.as_f32()?;
+        let mut result = Vec::with_capacity(context.texts.len());
+        for (sequence_id, tokens) in context.tokens.iter().enumerate().take(context.texts.len()) {
+            let num_words = tokens.len();
+            let scores = match self.layout {
+                ScoreLayout::Span => {
+                    let mut scores = array.slice(ndarray::s![sequence_id, ..num_words, .., ..]).mapv(sigmoid);
+                    for (start, mut widths) in scores.axis_iter_mut(Axis(0)).enumerate() {
+                        let valid = (num_words - start).min(self.max_width);
+                        widths.slice_mut(ndarray::s![valid.., ..]).fill(0.0);
+                    }
+                    scores
+                },
+                ScoreLayout::Token => array.slice(ndarray::s![.., sequence_id, ..num_words, ..]).mapv(sigmoid),
+            };
+            let shape = match scores.shape() {
+                [a, b, c] => [*a, *b, *c],
+                _ => return Err(IndexError::with("unexpected score dimensions").into()),
+            };
+            result.push(SequenceScores {
+                words: tokens.iter().map(|t| (t.start(), t.end())).collect(),
+                layout: self.layout,
+                shape,
+                scores: scores.iter().copied().collect(),
+            });
+        }
+        Ok(result)
+    }
+
+    /// Checks coherence of the output shape (see the related decoders)
+    fn check_shape(&self, actual_shape: Vec<i64>, context: &EntityContext) -> Result<()> {
+        let (batch_size, num_words, num_classes) = (context.texts.len() as i64, context.num_words as i64, context.entities.len() as i64);
+        let expected_shape = match self.layout {
+            ScoreLayout::Span => vec![batch_size, num_words, self.max_width as i64, num_classes],
+            ScoreLayout::Token => vec![3, batch_size, num_words, num_classes],
+        };
+        if actual_shape != expected_shape {
+            Err(GlinerError::ShapeMismatch { tensor: "logits".to_string(), expected: expected_shape, actual: actual_shape }.into())
+        }
+        else {
+            Ok(())
+        }
+    }
+}
+
+impl Composable<TensorOutput, ScoreOutput> for TensorsToScores {
+    fn apply(&self, input: TensorOutput) -> Result<ScoreOutput> {
+        let scores = self.scores(&input)?;
+        Ok(ScoreOutput { texts: input.context.texts, entities: input.context.entities, scores })
+    }
+}
+
+
+/// Pipeline which can output raw scores instead of decoded spans (see `GLiNER::scores()`)
+pub trait ScorePipeline<'a>: TensorPipeline<'a> {
+    fn score_post_processor(&self, params: &Self::Parameters) -> impl Composable<(Tensors, Self::Context), ScoreOutput>;
+}
+
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    // Silent some clippy warnings for unit tests
+    #![allow(clippy::get_first)]
+    #![allow(clippy::unwrap_used)]
+    use crate::util::result::Result;
+    use crate::model::{GLiNER, params::Parameters, input::text::TextInput};
+    use crate::model::pipeline::span::SpanPipeline;
+    use crate::model::backend::{Tensors, mock::{MockBackend, ScriptedSpan, span_logits, token_gliner}};
+    use crate::text::{splitter::RegexSplitter, tokenizer::HFTokenizer};
+    use crate::util::math::sigmoid;
+    use super::ScoreLayout;
+
+    const TOKENIZER: &str = "data/tokenizer-tiny.json";
+
+    fn input() -> Result<TextInput> {
+        TextInput::from_str(&["My name is James Bond", "I drive"], &["person", "vehicle"])
+    }
+
+    #[test]
+    fn test_span_mode() -> Result<()> {
+        let params = Parameters::default().with_max_width(4);
+        let pipeline = SpanPipeline::with_components(RegexSplitter::default(), HFTokenizer::from_file(TOKENIZER)?);
+        let backend = MockBackend::new(|inputs: &Tensors| span_logits(inputs, 2, 4, &[ScriptedSpan::new(0, 3, 4, 0, 4.0)]));
+        let model = GLiNER::with_backend(params, pipeline, backend);
+        // Processing
+        let output = model.scores(input()?)?;
+        // Assertions
+        assert_eq!(output.scores.len(), 2);
+        let first = output.scores.get(0).unwrap();
+        assert_eq!(first.layout, ScoreLayout::Span);
+        assert_eq!(first.shape, [5, 4, 2]);
+        assert_eq!(first.words.get(3), Some(&(11, 16)));
+        let scores = first.view()?;
+        assert!((scores[[3, 1, 0]] - sigmoid(4.0)).abs() < 1e-6);
+        assert!((scores[[3, 1, 1]] - sigmoid(-10.0)).abs() < 1e-6);
+        // spans running past the end of the sequence
+        assert_eq!(scores[[3, 2, 0]], 0.0);
+        // sequences are trimmed to their actual number of words
+        let second = output.scores.get(1).unwrap();
+        assert_eq!(second.shape, [2, 4, 2]);
+        assert_eq!(second.words, vec![(0, 1), (2, 7)]);
+        // Everything rules
+        Ok(())
+    }
+
+    #[test]
+    fn test_token_mode() -> Result<()> {
+        let model = token_gliner(2, &[ScriptedSpan::new(0, 3, 4, 0, 4.0)])?;
+        // Processing
+        let output = model.scores(input()?)?;
+        // Assertions
+        let first = output.scores.get(0).unwrap();
+        assert_eq!(first.layout, ScoreLayout::Token);
+        assert_eq!(first.shape, [3, 5, 2]);
+        let scores = first.view()?;
+        assert!((scores[[0, 3, 0]] - sigmoid(4.0)).abs() < 1e-6);
+        assert!((scores[[1, 4, 0]] - sigmoid(4.0)).abs() < 1e-6);
+        assert!((scores[[0, 4, 0]] - sigmoid(-10.0)).abs() < 1e-6);
+        assert_eq!(output.scores.get(1).unwrap().shape, [3, 2, 2]);
+        // Everything rules
+        Ok(())
+    }
+}
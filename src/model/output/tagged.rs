@@ -0,0 +1,179 @@
+//! Conversion of spans to per-word tags (BIO or BIOES)
+
+use composable::Composable;
+use crate::util::result::Result;
+use crate::text::{span::Span, splitter::Splitter, token::Token};
+use super::decoded::SpanOutput;
+
+
+/// Tag for words outside of any entity
+pub const OUTSIDE: &str = "O";
+
+
+/// Tagging scheme
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Scheme {
+    /// `B-<label>` for the first word of an entity, `I-<label>` for the others
+    #[default]
+    Bio,
+    /// Same as BIO, but `E-<label>` for the last word of an entity, and `S-<label>` for single-word entities
+    Bioes,
+}
+
+
+/// Handling of overlapping spans, which cannot be represented by a single tag per word
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Nesting {
+    /// Only keep the outermost spans (the longest one first, then the first one for crossing spans)
+    #[default]
+    Outermost,
+    /// One tag column per nesting level, the first one holding the outermost spans
+    Columns,
+}
+
+
+/// Tags of the words of one text
+#[derive(Debug)]
+pub struct TaggedSequence {
+    pub words: Vec<Token>,
+    /// For each word, one tag per column
+    pub tags: Vec<Vec<String>>,
+}
+
+
+/// Tags of each text (see `SpanOutputToTagged`)
+#[derive(Debug)]
+pub struct TaggedOutput {
+    pub texts: Vec<String>,
+    pub entities: Vec<String>,
+    /// Number of tag columns (the same for all the sequences)
+    pub columns: usize,
+    pub sequences: Vec<TaggedSequence>,
+}
+
+
+/// Composable: SpanOutput => TaggedOutput
+///
+/// Texts are split into words using the given splitter, which should be the one used by the pipeline
+/// so that the words are the ones the model actually labelled (see `EntityContext::tokens`). A word
+/// belongs to a span if they overlap.
+pub struct SpanOutputToTagged<S> {
+    splitter: S,
+    scheme: Scheme,
+    nesting: Nesting,
+}
+
+impl<S: Splitter> SpanOutputToTagged<S> {
+    pub fn new(splitter: S, scheme: Scheme, nesting: Nesting) -> Self {
+        Self { splitter, scheme, nesting }
+    }
+
+    /// Spans as word index ranges (inclusive) with their label, by column
+    fn columns(&self, words: &[Token], spans: &[Span]) -> Vec<Vec<(usize, usize, String)>> {
+        let mut ranges: Vec<(usize, usize, String)> = spans.iter().filter_map(|span| {
+            let (start, end) = span.offsets();
+            let mut indexes = words.iter().enumerate().filter(|(_, w)| w.start() < end && w.end() > start).map(|(i, _)| i);
+            let first = indexes.next()?;
+            Some((first, indexes.next_back().unwrap_or(first), span.class().to_string()))
+        }).collect();
+        // outermost first: by start, then decreasing length
+        ranges.sort_by(|r1, r2| r1.0.cmp(&r2.0).then_with(|| r2.1.cmp(&r1.1)));
+        let mut columns: Vec<Vec<(usize, usize, String)>> = Vec::new();
+        for range in ranges {
+            let free = columns.iter().position(|c| c.iter().all(|r| r.1 < range.0 || range.1 < r.0));
+            if let Some(column) = free.and_then(|i| columns.get_mut(i)) {
+                column.push(range);
+            }
+            else if columns.is_empty() || self.nesting == Nesting::Columns {
+                columns.push(vec![range]);
+            }
+        }
+        columns
+    }
+
+    fn tag(&self, num_words: usize, column: &[(usize, usize, String)]) -> Vec<String> {
+        let mut tags = vec![OUTSIDE.to_string(); num_words];
+        for (first, last, label) in column {
+            for (i, tag) in tags.iter_mut().enumerate().take(*last + 1).skip(*first) {
+                let prefix = match self.scheme {
+                    Scheme::Bioes if first == last => "S",
+                    Scheme::Bioes if i == *last => "E",
+                    _ if i == *first => "B",
+                    _ => "I",
+                };
+                *tag = format!("{prefix}-{label}");
+            }
+        }
+        tags
+    }
+}
+
+impl<S: Splitter> Composable<SpanOutput, TaggedOutput> for SpanOutputToTagged<S> {
+    fn apply(&self, input: SpanOutput) -> Result<TaggedOutput> {
+        let mut sequences = Vec::with_capacity(input.texts.len());
+        let mut all_columns = Vec::with_capacity(input.texts.len());
+        for (text, spans) in input.texts.iter().zip(&input.spans) {
+            let words = self.splitter.split(text, None)?;
+            all_columns.push(self.columns(&words, spans));
+            sequences.push(words);
+        }
+        let columns = all_columns.iter().map(Vec::len).max().unwrap_or(0).max(1);
+        let sequences = sequences.into_iter().zip(all_columns).map(|(words, spans)| {
+            let mut tags = vec![Vec::with_capacity(columns); words.len()];
+            for column in 0..columns {
+                let column_tags = self.tag(words.len(), spans.get(column).map(Vec::as_slice).unwrap_or_default());
+                tags.iter_mut().zip(column_tags).for_each(|(t, tag)| t.push(tag));
+            }
+            TaggedSequence { words, tags }
+        }).collect();
+        Ok(TaggedOutput { texts: input.texts, entities: input.entities, columns, sequences })
+    }
+}
+
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    // Silent some clippy warnings for unit tests
+    #![allow(clippy::get_first)]
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use crate::text::splitter::RegexSplitter;
+
+    fn output() -> SpanOutput {
+        let text = "The University of Oxford is in England";
+        let span = |start: usize, end: usize, class: &str| Span::new(0, start, end, text[start..end].to_string(), class.to_string(), 0.9);
+        SpanOutput::new(
+            vec![text.to_string(), "Nothing".to_string()],
+            vec!["organization".to_string(), "location".to_string()],
+            vec![vec![span(4, 24, "organization"), span(18, 24, "location"), span(31, 38, "location")], vec![]],
+        )
+    }
+
+    fn tags(sequence: &TaggedSequence, column: usize) -> Vec<&str> {
+        sequence.tags.iter().map(|t| t.get(column).unwrap().as_str()).collect()
+    }
+
+    #[test]
+    fn test_outermost() -> Result<()> {
+        let tagged = SpanOutputToTagged::new(RegexSplitter::default(), Scheme::Bio, Nesting::Outermost).apply(output())?;
+        assert_eq!(tagged.columns, 1);
+        let first = tagged.sequences.get(0).unwrap();
+        assert_eq!(first.words.len(), 7);
+        assert_eq!(tags(first, 0), vec!["O", "B-organization", "I-organization", "I-organization", "O", "O", "B-location"]);
+        assert_eq!(tags(tagged.sequences.get(1).unwrap(), 0), vec!["O"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_columns() -> Result<()> {
+        let tagged = SpanOutputToTagged::new(RegexSplitter::default(), Scheme::Bioes, Nesting::Columns).apply(output())?;
+        assert_eq!(tagged.columns, 2);
+        let first = tagged.sequences.get(0).unwrap();
+        assert_eq!(tags(first, 0), vec!["O", "B-organization", "I-organization", "E-organization", "O", "O", "S-location"]);
+        assert_eq!(tags(first, 1), vec!["O", "O", "O", "S-location", "O", "O", "O"]);
+        // every sequence has the same number of columns
+        assert_eq!(tagged.sequences.get(1).unwrap().tags, vec![vec!["O", "O"]]);
+        Ok(())
+    }
+}
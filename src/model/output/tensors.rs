@@ -1,19 +1,20 @@
 //! Encapsulation of raw tensor outputs
 
-use ort::session::SessionOutputs;
 use composable::Composable;
 use crate::util::result::Result;
 use crate::model::pipeline::context::EntityContext;
+use ort::session::SessionOutputs;
+use crate::model::backend::{Tensors, onnxruntime::from_session_outputs};
 
 /// Represents the raw tensor output of the inference step
-pub struct TensorOutput<'a> {
+pub struct TensorOutput {
     pub context: EntityContext,
-    pub tensors: SessionOutputs<'a, 'a>,
+    pub tensors: Tensors,
 }
 
 
-impl<'a> TensorOutput<'a> {
-    pub fn from(tensors: SessionOutputs<'a, 'a>, context: EntityContext) -> Self {
+impl TensorOutput {
+    pub fn from(tensors: Tensors, context: EntityContext) -> Self {
         Self { 
             context,
             tensors 
@@ -22,13 +23,27 @@ impl<'a> TensorOutput<'a> {
 }
 
 
-/// Composable: (SessionOutput, TensorMeta) => TensorOutput
+/// Composable: (Tensors, EntityContext) => TensorOutput
 #[derive(Default)]
-pub struct SessionOutputToTensors { }
+pub struct BackendOutputToTensors { }
 
 
-impl<'a> Composable<(SessionOutputs<'a, 'a>, EntityContext), TensorOutput<'a>> for SessionOutputToTensors {
-    fn apply(&self, input: (SessionOutputs<'a, 'a>, EntityContext)) -> Result<TensorOutput<'a>> {
+impl Composable<(Tensors, EntityContext), TensorOutput> for BackendOutputToTensors {
+    fn apply(&self, input: (Tensors, EntityContext)) -> Result<TensorOutput> {
         Ok(TensorOutput::from(input.0, input.1))
     }
 }
+
+
+/// Composable: (SessionOutputs, EntityContext) => TensorOutput
+#[derive(Default)]
+#[deprecated(since = "0.9.4", note = "use `backend::onnxruntime::SessionOutputsToTensors` followed by `BackendOutputToTensors`")]
+pub struct SessionOutputToTensors { }
+
+
+#[allow(deprecated)]
+impl<'a> Composable<(SessionOutputs<'a, 'a>, EntityContext), TensorOutput> for SessionOutputToTensors {
+    fn apply(&self, input: (SessionOutputs<'a, 'a>, EntityContext)) -> Result<TensorOutput> {
+        Ok(TensorOutput::from(from_session_outputs(input.0)?, input.1))
+    }
+}
@@ -1,5 +1,8 @@
 //! Processing parameters
 
+use std::collections::HashMap;
+use crate::text::offsets::OffsetUnit;
+
 
 /// Represents the set of parameters for the whole pipeline
 /// 
@@ -8,6 +11,11 @@
 /// 
 /// The easiest way to instanciate sound parameters is to use the
 /// `default()` constructor and then use individual setters as needed.
+/// 
+/// With the `serde` feature enabled, it is (de)serialized using the
+/// field names below (`max_length` being `null` if unbounded, and
+/// `class_thresholds`, `span_selection` and `offset_unit` being optional).
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Parameters {
     /// Probability threshold (default: 0.5)
     pub threshold: f32,    
@@ -19,8 +27,17 @@ pub struct Parameters {
     pub multi_label: bool,    
     /// For span mode, maximum span width (default: 12)
     pub max_width: usize,
-    /// Maximum sequence length (default: 512)
+    /// Maximum sequence length, in words (default: 512). Longer texts are truncated, unless chunking is enabled (see `ChunkedPipeline`)
     pub max_length: Option<usize>,
+    /// Probability thresholds of specific classes (by label), overriding `threshold` (default: none)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub class_thresholds: HashMap<String, f32>,
+    /// How the final spans are selected among the overlapping candidates, according to the flags above (default: greedy)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub span_selection: SpanSelection,
+    /// Unit of the offsets added to the spans beside byte offsets, if not bytes (default: bytes, see `Span::unit_offsets()`)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub offset_unit: OffsetUnit,
 }
 
 impl Default for Parameters {
@@ -38,7 +55,7 @@ impl Default for Parameters {
 }
 
 impl Parameters {
-    /// New configuration specifying every parameter
+    /// New configuration specifying every parameter (but class thresholds, span selection and offset unit)
     pub fn new(threshold: f32, max_width: usize, max_length: Option<usize>, flat_ner: bool, dup_label: bool, multi_label: bool) -> Self {
         Self { 
             threshold, 
@@ -47,6 +64,9 @@ impl Parameters {
             flat_ner,
             dup_label,
             multi_label,
+            class_thresholds: HashMap::new(),
+            span_selection: SpanSelection::default(),
+            offset_unit: OffsetUnit::default(),
         }
     }
 
@@ -80,4 +100,52 @@ impl Parameters {
         self
     }
 
+    /// Sets the threshold of a specific class
+    pub fn with_class_threshold(mut self, label: &str, threshold: f32) -> Self {
+        self.class_thresholds.insert(label.to_string(), threshold);
+        self
+    }
+
+    pub fn with_class_thresholds(mut self, class_thresholds: HashMap<String, f32>) -> Self {
+        self.class_thresholds = class_thresholds;
+        self
+    }
+
+    /// Threshold of the given class, falling back to the global one
+    pub fn class_threshold(&self, label: &str) -> f32 {
+        class_threshold(self.threshold, &self.class_thresholds, label)
+    }
+
+    pub fn with_span_selection(mut self, span_selection: SpanSelection) -> Self {
+        self.span_selection = span_selection;
+        self
+    }
+
+    pub fn with_offset_unit(mut self, offset_unit: OffsetUnit) -> Self {
+        self.offset_unit = offset_unit;
+        self
+    }
+
+}
+
+
+/// Method used to select the final spans among the overlapping candidates (see `SpanSearch`)
+///
+/// With the `serde` feature enabled, it is (de)serialized as `"greedy"`, `"max_probability"` or `"max_log_probability"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum SpanSelection {
+    /// Fast, but a span can be discarded because of a chain of overlaps (see `GreedySearch`)
+    #[default]
+    Greedy,
+    /// Exact selection maximizing the sum of probabilities (see `OptimalSearch`)
+    MaxProbability,
+    /// Exact selection maximizing the joint log-probability, ignoring spans below 0.5 (see `OptimalSearch`)
+    MaxLogProbability,
+}
+
+
+/// Threshold of the given class, falling back to the global one
+pub fn class_threshold(threshold: f32, class_thresholds: &HashMap<String, f32>, label: &str) -> f32 {
+    class_thresholds.get(label).copied().unwrap_or(threshold)
 }
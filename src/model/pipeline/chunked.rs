@@ -0,0 +1,121 @@
+//! Pipeline wrapper for NER on long texts (sliding-window chunking)
+
+use ::composable::*;
+use orp::pipeline::*;
+use crate::model::input::{text::TextInput, chunked::RawToChunked};
+use crate::model::output::{decoded::{SpanOutput, offsets::SpanOffsets}, chunked::ChunkedToSpanOutput};
+use crate::text::prompt::PromptFormat;
+use super::super::params::Parameters;
+use super::{Components, context::ChunkContext};
+use crate::util::profile::Traced;
+use super::super::backend::{TensorPipeline, Tensors, onnxruntime::{TensorsToSessionInputs, SessionOutputsToTensors}};
+
+
+/// Wraps a NER pipeline (token or span mode) so that texts longer than `Parameters::max_length`
+/// words are not truncated, but processed as overlapping windows of `max_length` words. The
+/// resulting spans are mapped back onto the original texts (see `ChunkedToSpanOutput`).
+///
+/// Texts are split by the splitter of the wrapped pipeline, so that chunk boundaries match word
+/// boundaries.
+pub struct ChunkedPipeline<P> {
+    pipeline: P,
+    overlap: usize,
+}
+
+
+impl<'a, P> TensorPipeline<'a> for ChunkedPipeline<P>
+where
+    P: TensorPipeline<'a, Input = TextInput, Output = SpanOutput, Parameters = Parameters> + Components,
+{
+    type Input = TextInput;
+    type Output = SpanOutput;
+    type Context = (ChunkContext, P::Context);
+    type Parameters = Parameters;
+
+    fn tensor_pre_processor(&self, params: &Parameters) -> impl Composable<TextInput, (Tensors, (ChunkContext, P::Context))> {
+        composed_t![
+            Traced::new(RawToChunked::new(self.pipeline.splitter(), params.max_length, self.overlap)),
+            self.pipeline.tensor_pre_processor(params)
+        ]
+    }
+
+    fn tensor_post_processor(&self, params: &Parameters) -> impl Composable<(Tensors, (ChunkContext, P::Context)), SpanOutput> {
+        // spans are re-created on the original texts, so unit offsets have to be computed again
+        composed![
+            composed_rt![
+                self.pipeline.tensor_post_processor(params),
+                Traced::new(ChunkedToSpanOutput::new(params.flat_ner, params.dup_label, params.multi_label).with_span_selection(params.span_selection))
+            ],
+            Traced::new(SpanOffsets::new(params.offset_unit))
+        ]
+    }
+}
+
+
+/// Runs on ONNX Runtime through `orp` (see `TensorPipeline` for other backends)
+impl<'a, P> Pipeline<'a> for ChunkedPipeline<P>
+where
+    P: TensorPipeline<'a, Input = TextInput, Output = SpanOutput, Parameters = Parameters> + Components,
+{
+    type Input = TextInput;
+    type Output = SpanOutput;
+    type Context = (ChunkContext, P::Context);
+    type Parameters = Parameters;
+
+    fn pre_processor(&self, params: &Parameters) -> impl PreProcessor<'a, TextInput, (ChunkContext, P::Context)> {
+        composed![
+            self.tensor_pre_processor(params),
+            TensorsToSessionInputs::default()
+        ]
+    }
+
+    fn post_processor(&self, params: &Parameters) -> impl PostProcessor<'a, SpanOutput, (ChunkContext, P::Context)> {
+        composed![
+            SessionOutputsToTensors::default(),
+            self.tensor_post_processor(params)
+        ]
+    }
+}
+
+
+impl<P> ChunkedPipeline<P> {
+    /// Wraps the given pipeline, consecutive windows sharing `overlap` words
+    pub fn new(pipeline: P, overlap: usize) -> Self {
+        Self {
+            pipeline,
+            overlap,
+        }
+    }
+}
+
+
+/// The wrapped pipeline processes the chunks, so its components are the ones of the chunked pipeline
+impl<P: Components> Components for ChunkedPipeline<P> {
+    type Splitter = P::Splitter;
+    type Tokenizer = P::Tokenizer;
+
+    fn splitter(&self) -> &P::Splitter {
+        self.pipeline.splitter()
+    }
+
+    fn tokenizer(&self) -> &P::Tokenizer {
+        self.pipeline.tokenizer()
+    }
+
+    fn prompt_format(&self) -> &PromptFormat {
+        self.pipeline.prompt_format()
+    }
+}
+
+
+/// Chunking for GLiNER implementations
+impl<P: Components, B> super::super::GLiNER<P, B> {
+    /// Enables sliding-window chunking, consecutive windows of `max_length` words sharing `overlap` words
+    pub fn chunked(self, overlap: usize) -> super::super::GLiNER<ChunkedPipeline<P>, B> {
+        super::super::GLiNER {
+            params: self.params,
+            model: self.model,
+            pipeline: ChunkedPipeline::new(self.pipeline, overlap),
+        }
+    }
+}
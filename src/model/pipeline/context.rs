@@ -4,6 +4,7 @@ use std::collections::{HashMap, HashSet};
 use crate::util::{error::IndexError, result::Result};
 use crate::text::span::Span;
 use crate::text::token::Token;
+use crate::text::normalizer::Alignment;
 
 
 // Context for NER pipelines
@@ -41,3 +42,21 @@ impl EntityContext {
 pub struct RelationContext {
     pub entity_labels: HashMap<String, HashSet<String>>
 }
+
+
+// Context for chunked pipelines
+pub struct ChunkContext {
+    /// Original (non-chunked) texts
+    pub texts: Vec<String>,
+    /// For each chunk, the index of the text it comes from and its byte offset within this text
+    pub chunks: Vec<(usize, usize)>,
+}
+
+
+// Context for normalized pipelines
+pub struct NormalizationContext {
+    /// Original (non-normalized) texts
+    pub texts: Vec<String>,
+    /// For each text, the alignment of its normalized version to the original one
+    pub alignments: Vec<Alignment>,
+}
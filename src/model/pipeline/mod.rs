@@ -3,4 +3,25 @@
 pub mod token;
 pub mod span;
 pub mod relation;
+pub mod chunked;
+pub mod normalized;
 pub mod context;
+
+use crate::text::{splitter::Splitter, tokenizer::Tokenizer, prompt::PromptFormat};
+
+
+/// Gives access to the components of a NER pipeline, so that wrappers can process the texts the
+/// same way as the wrapped pipeline (see `ChunkedPipeline` and `GLiNER::inference_bucketed()`)
+pub trait Components {
+    type Splitter: Splitter;
+    type Tokenizer: Tokenizer;
+
+    /// Word-level splitter used to build the prompts
+    fn splitter(&self) -> &Self::Splitter;
+
+    /// Sub-word tokenizer used to encode the prompts
+    fn tokenizer(&self) -> &Self::Tokenizer;
+
+    /// Markers used to build the prompts
+    fn prompt_format(&self) -> &PromptFormat;
+}
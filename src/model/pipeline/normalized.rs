@@ -0,0 +1,171 @@
+//! Pipeline wrapper for NER on noisy texts (normalization)
+
+use ::composable::*;
+use orp::pipeline::*;
+use crate::model::input::{text::TextInput, normalized::RawToNormalized};
+use crate::model::output::{decoded::{SpanOutput, offsets::SpanOffsets}, normalized::NormalizedToSpanOutput};
+use crate::text::normalizer::{Normalizer, TextNormalizer};
+use crate::text::prompt::PromptFormat;
+use super::super::params::Parameters;
+use super::context::NormalizationContext;
+use crate::util::profile::Traced;
+use super::super::backend::{TensorPipeline, Tensors, onnxruntime::{TensorsToSessionInputs, SessionOutputsToTensors}};
+
+
+/// Wraps a NER pipeline (token or span mode) so that texts are normalized before being processed
+/// (see `Normalizer`). The resulting spans are mapped back onto the original texts (see
+/// `NormalizedToSpanOutput`), so that their offsets and texts refer to the unnormalized input.
+///
+/// Normalizations can be combined by wrapping a normalized pipeline again, the outermost normalizer
+/// being applied first (e.g. extraction of the text of HTML documents, then cleanup of this text).
+pub struct NormalizedPipeline<P, N = TextNormalizer> {
+    pipeline: P,
+    normalizer: N,
+}
+
+
+impl<'a, P, N> TensorPipeline<'a> for NormalizedPipeline<P, N>
+where
+    P: TensorPipeline<'a, Input = TextInput, Output = SpanOutput, Parameters = Parameters>,
+    N: Normalizer,
+{
+    type Input = TextInput;
+    type Output = SpanOutput;
+    type Context = (NormalizationContext, P::Context);
+    type Parameters = Parameters;
+
+    fn tensor_pre_processor(&self, params: &Parameters) -> impl Composable<TextInput, (Tensors, (NormalizationContext, P::Context))> {
+        composed_t![
+            Traced::new(RawToNormalized::new(&self.normalizer)),
+            self.pipeline.tensor_pre_processor(params)
+        ]
+    }
+
+    fn tensor_post_processor(&self, params: &Parameters) -> impl Composable<(Tensors, (NormalizationContext, P::Context)), SpanOutput> {
+        // spans are re-created on the original texts, so unit offsets have to be computed again
+        composed![
+            composed_rt![
+                self.pipeline.tensor_post_processor(params),
+                Traced::new(NormalizedToSpanOutput::default())
+            ],
+            Traced::new(SpanOffsets::new(params.offset_unit))
+        ]
+    }
+}
+
+
+/// Runs on ONNX Runtime through `orp` (see `TensorPipeline` for other backends)
+impl<'a, P, N> Pipeline<'a> for NormalizedPipeline<P, N>
+where
+    P: TensorPipeline<'a, Input = TextInput, Output = SpanOutput, Parameters = Parameters>,
+    N: Normalizer,
+{
+    type Input = TextInput;
+    type Output = SpanOutput;
+    type Context = (NormalizationContext, P::Context);
+    type Parameters = Parameters;
+
+    fn pre_processor(&self, params: &Parameters) -> impl PreProcessor<'a, TextInput, (NormalizationContext, P::Context)> {
+        composed![
+            self.tensor_pre_processor(params),
+            TensorsToSessionInputs::default()
+        ]
+    }
+
+    fn post_processor(&self, params: &Parameters) -> impl PostProcessor<'a, SpanOutput, (NormalizationContext, P::Context)> {
+        composed![
+            SessionOutputsToTensors::default(),
+            self.tensor_post_processor(params)
+        ]
+    }
+}
+
+
+impl<P, N> NormalizedPipeline<P, N> {
+    /// Wraps the given pipeline, texts being normalized by the given normalizer
+    pub fn new(pipeline: P, normalizer: N) -> Self {
+        Self {
+            pipeline,
+            normalizer,
+        }
+    }
+}
+
+
+/// The components of the wrapped pipeline apply to the normalized texts
+impl<P: super::Components, N> super::Components for NormalizedPipeline<P, N> {
+    type Splitter = P::Splitter;
+    type Tokenizer = P::Tokenizer;
+
+    fn splitter(&self) -> &P::Splitter {
+        self.pipeline.splitter()
+    }
+
+    fn tokenizer(&self) -> &P::Tokenizer {
+        self.pipeline.tokenizer()
+    }
+
+    fn prompt_format(&self) -> &PromptFormat {
+        self.pipeline.prompt_format()
+    }
+}
+
+
+/// Normalization for GLiNER implementations
+impl<P, B> super::super::GLiNER<P, B> {
+    /// Enables the normalization of the input texts (see `Normalizer`)
+    pub fn normalized<N: Normalizer>(self, normalizer: N) -> super::super::GLiNER<NormalizedPipeline<P, N>, B> {
+        super::super::GLiNER {
+            params: self.params,
+            model: self.model,
+            pipeline: NormalizedPipeline::new(self.pipeline, normalizer),
+        }
+    }
+}
+
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    // Silent some clippy warnings for unit tests
+    #![allow(clippy::get_first)]
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use crate::util::result::Result;
+    use crate::model::backend::mock::{ScriptedSpan, token_gliner};
+
+    #[test]
+    fn test() -> Result<()> {
+        let model = token_gliner(1, &[ScriptedSpan::new(0, 3, 4, 0, 5.0)])?
+            .normalized(TextNormalizer::default());
+        let text = "My\u{200B} name  is Ja\u{00AD}mes Bo-\nnd";
+        // Processing
+        let output = model.inference(TextInput::from_str(&[text], &["person"])?)?;
+        // Assertions
+        assert_eq!(output.texts, vec![text]);
+        let spans = output.spans.get(0).unwrap();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans.get(0).unwrap().text(), "Ja\u{00AD}mes Bo-\nnd");
+        assert_eq!(spans.get(0).unwrap().offsets(), (text.find("Ja").unwrap(), text.len()));
+        // Everything rules
+        Ok(())
+    }
+
+    #[test]
+    fn test_html() -> Result<()> {
+        use crate::text::markup::HtmlNormalizer;
+        use crate::model::output::export::markup::SpanOutputToMarkup;
+        let model = token_gliner(1, &[ScriptedSpan::new(0, 3, 4, 0, 5.0)])?
+            .normalized(TextNormalizer::default())
+            .normalized(HtmlNormalizer::default());
+        let html = "<p>My name is <b>James</b>&nbsp;Bond</p>";
+        // Processing
+        let output = model.inference(TextInput::from_str(&[html], &["person"])?)?;
+        let annotated = SpanOutputToMarkup::default().apply(output)?;
+        // Assertions
+        // the entity is split around the tags of the document
+        assert_eq!(annotated, vec!["<p>My name is <b><mark class=\"person\">James</mark></b><mark class=\"person\">&nbsp;Bond</mark></p>"]);
+        // Everything rules
+        Ok(())
+    }
+}
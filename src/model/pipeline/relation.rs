@@ -11,6 +11,8 @@ use crate::model::output::relation::{RelationOutput, SpanOutputToRelationOutput}
 use super::token::TokenPipeline;
 use super::super::params::Parameters;
 use super::context::{RelationContext, EntityContext};
+use crate::util::profile::Traced;
+use super::super::backend::{TensorPipeline, Tensors, onnxruntime::{TensorsToSessionInputs, SessionOutputsToTensors}};
 
 
 /// Relation Extraction pipeline
@@ -22,26 +24,48 @@ pub struct RelationPipeline<'a, S, T> {
 }
 
 
-impl<'a, S: Splitter, T:Tokenizer> Pipeline<'a> for RelationPipeline<'a, S, T> {
+impl<'a, S: Splitter, T:Tokenizer> TensorPipeline<'a> for RelationPipeline<'a, S, T> {
     type Input = SpanOutput;
     type Output = RelationOutput;
     type Context = (RelationContext, EntityContext);
     type Parameters = Parameters;
 
-    fn pre_processor(&self, params: &Parameters) -> impl PreProcessor<'a, Self::Input, Self::Context> {
+    fn tensor_pre_processor(&self, params: &Parameters) -> impl Composable<SpanOutput, (Tensors, (RelationContext, EntityContext))> {
         composed_t![
             composed![
-                SpanOutputToRelationInput::new(self.relation_schema),
-                RelationInputToTextInput::default()
+                Traced::new(SpanOutputToRelationInput::new(self.relation_schema)),
+                Traced::new(RelationInputToTextInput::default())
             ],
-            self.token_pipeline.pre_processor(params)            
+            self.token_pipeline.tensor_pre_processor(params)            
         ]
     }
 
-    fn post_processor(&self, params: &Parameters) -> impl PostProcessor<'a, Self::Output, Self::Context> {
+    fn tensor_post_processor(&self, params: &Parameters) -> impl Composable<(Tensors, (RelationContext, EntityContext)), RelationOutput> {
         composed_rt![
-            self.token_pipeline.post_processor(params),
-            SpanOutputToRelationOutput::new(self.relation_schema)
+            self.token_pipeline.tensor_post_processor(params),
+            Traced::new(SpanOutputToRelationOutput::new(self.relation_schema))
+        ]
+    }
+}
+
+/// Runs on ONNX Runtime through `orp` (see `TensorPipeline` for other backends)
+impl<'a, S: Splitter, T:Tokenizer> Pipeline<'a> for RelationPipeline<'a, S, T> {
+    type Input = SpanOutput;
+    type Output = RelationOutput;
+    type Context = (RelationContext, EntityContext);
+    type Parameters = Parameters;
+
+    fn pre_processor(&self, params: &Parameters) -> impl PreProcessor<'a, SpanOutput, (RelationContext, EntityContext)> {
+        composed![
+            self.tensor_pre_processor(params),
+            TensorsToSessionInputs::default()
+        ]
+    }
+
+    fn post_processor(&self, params: &Parameters) -> impl PostProcessor<'a, RelationOutput, (RelationContext, EntityContext)> {
+        composed![
+            SessionOutputsToTensors::default(),
+            self.tensor_post_processor(params)
         ]
     }
 }
@@ -3,51 +3,118 @@
 use std::path::Path;
 use ::composable::*;
 use orp::{pipeline::*, params::RuntimeParameters};
-use crate::util::result::Result;
-use super::super::super::text::{splitter::Splitter, tokenizer::Tokenizer};
+use crate::util::result::{Result, GlinerResult};
+use super::super::super::text::{splitter::Splitter, tokenizer::Tokenizer, prompt::PromptFormat};
 use super::super::{input, output, params};
+use super::super::backend::{OnnxBackend, TensorPipeline, Tensors, onnxruntime::{TensorsToSessionInputs, SessionOutputsToTensors}};
 use super::context::EntityContext;
+use crate::util::profile::Traced;
+use super::super::{auto::Mode, config::{ModelConfig, ModelDir}};
 
 
 /// Generic span-level pipeline
 pub struct SpanPipeline<S, T> {
     splitter: S,
     tokenizer: T,
+    prompt_format: PromptFormat,
 }
 
+impl<'a, S: Splitter, T:Tokenizer> TensorPipeline<'a> for SpanPipeline<S, T> {
+    type Input = input::text::TextInput;
+    type Output = output::decoded::SpanOutput;
+    type Context = EntityContext;
+    type Parameters = params::Parameters;
+
+    fn tensor_pre_processor(&self, params: &params::Parameters) -> impl Composable<input::text::TextInput, (Tensors, EntityContext)> {
+        composed![
+            Traced::new(input::tokenized::RawToTokenized::new(&self.splitter, params.max_length)),
+            Traced::new(input::prompt::TokenizedToPrompt::new(self.prompt_format.clone())),
+            Traced::new(input::encoded::PromptsToEncoded::new(&self.tokenizer)),
+            Traced::new(input::tensors::span::EncodedToTensors::new(params.max_width)),
+            Traced::new(input::tensors::span::TensorsToBackendInput::default())
+        ]
+    }
+
+    fn tensor_post_processor(&self, params: &params::Parameters) -> impl Composable<(Tensors, EntityContext), output::decoded::SpanOutput> {
+        composed![
+            Traced::new(output::tensors::BackendOutputToTensors::default()),
+            Traced::new(output::decoded::span::TensorsToDecoded::new(params.threshold, params.max_width).with_class_thresholds(params.class_thresholds.clone())),
+            Traced::new(output::decoded::sort::SpanSort::default()),
+            Traced::new(output::decoded::search::SpanSearch::new(params.span_selection, params.flat_ner, params.dup_label, params.multi_label)),
+            Traced::new(output::decoded::offsets::SpanOffsets::new(params.offset_unit))
+        ]
+    }
+}
+
+impl<'a, S: Splitter, T:Tokenizer> output::scores::ScorePipeline<'a> for SpanPipeline<S, T> {
+    fn score_post_processor(&self, params: &params::Parameters) -> impl Composable<(Tensors, EntityContext), output::scores::ScoreOutput> {
+        composed![
+            Traced::new(output::tensors::BackendOutputToTensors::default()),
+            Traced::new(output::scores::TensorsToScores::span(params.max_width))
+        ]
+    }
+}
+
+/// Runs on ONNX Runtime through `orp` (see `TensorPipeline` for other backends)
 impl<'a, S: Splitter, T:Tokenizer> Pipeline<'a> for SpanPipeline<S, T> {
     type Input = input::text::TextInput;
     type Output = output::decoded::SpanOutput;
     type Context = EntityContext;
     type Parameters = params::Parameters;
 
-    fn pre_processor(&self, params: &Self::Parameters) -> impl PreProcessor<'a, Self::Input, Self::Context>{
+    fn pre_processor(&self, params: &params::Parameters) -> impl PreProcessor<'a, input::text::TextInput, EntityContext> {
         composed![
-            input::tokenized::RawToTokenized::new(&self.splitter, params.max_length),
-            input::prompt::TokenizedToPrompt::default(),
-            input::encoded::PromptsToEncoded::new(&self.tokenizer),
-            input::tensors::span::EncodedToTensors::new(params.max_width),
-            input::tensors::span::TensorsToSessionInput::default()
+            self.tensor_pre_processor(params),
+            TensorsToSessionInputs::default()
         ]
     }
 
-    fn post_processor(&self, params: &Self::Parameters) -> impl PostProcessor<'a, Self::Output, Self::Context> {
+    fn post_processor(&self, params: &params::Parameters) -> impl PostProcessor<'a, output::decoded::SpanOutput, EntityContext> {
         composed![
-            output::tensors::SessionOutputToTensors::default(),
-            output::decoded::span::TensorsToDecoded::new(params.threshold, params.max_width),
-            output::decoded::sort::SpanSort::default(),
-            output::decoded::greedy::GreedySearch::new(params.flat_ner, params.dup_label, params.multi_label)
+            SessionOutputsToTensors::default(),
+            self.tensor_post_processor(params)
         ]
     }
 }
 
+impl<S: Splitter, T: Tokenizer> super::Components for SpanPipeline<S, T> {
+    type Splitter = S;
+    type Tokenizer = T;
+
+    fn splitter(&self) -> &S {
+        &self.splitter
+    }
+
+    fn tokenizer(&self) -> &T {
+        &self.tokenizer
+    }
+
+    fn prompt_format(&self) -> &PromptFormat {
+        &self.prompt_format
+    }
+}
+
+
+impl<S, T> SpanPipeline<S, T> {
+    /// Creates a pipeline with the given splitter and tokenizer
+    pub fn with_components(splitter: S, tokenizer: T) -> Self {
+        Self { splitter, tokenizer, prompt_format: PromptFormat::default() }
+    }
+
+    /// Sets the markers used to build the prompts (default: `<<ENT>>` and `<<SEP>>`)
+    pub fn with_prompt_format(mut self, prompt_format: PromptFormat) -> Self {
+        self.prompt_format = prompt_format;
+        self
+    }
+}
+
 /// Specific implementation using HF tokenizer and default splitter
 impl SpanPipeline<crate::text::splitter::RegexSplitter, crate::text::tokenizer::HFTokenizer> {
     pub fn new<P: AsRef<Path>>(tokenizer_path: P) -> Result<Self> {
-        Ok(Self {
-            splitter: crate::text::splitter::RegexSplitter::default(),
-            tokenizer: crate::text::tokenizer::HFTokenizer::from_file(tokenizer_path)?,
-        })
+        Ok(Self::with_components(
+            crate::text::splitter::RegexSplitter::default(),
+            crate::text::tokenizer::HFTokenizer::from_file(tokenizer_path)?,
+        ))
     }
 }
 
@@ -55,13 +122,36 @@ impl SpanPipeline<crate::text::splitter::RegexSplitter, crate::text::tokenizer::
 pub type SpanMode = SpanPipeline<crate::text::splitter::RegexSplitter, crate::text::tokenizer::HFTokenizer>;
 
 
-/// Specific GLiNER implementation using the default span-mode pipeline
-impl super::super::GLiNER<SpanMode> {
-    pub fn new<P: AsRef<Path>>(params: params::Parameters, runtime_params: RuntimeParameters, tokenizer_path: P, model_path: P) -> Result<Self> {
+/// Specific GLiNER implementation using the default span-mode pipeline. The model is run by ONNX Runtime
+/// by default, another backend can be selected by its type (see `OnnxBackend`).
+impl<B: OnnxBackend> super::super::GLiNER<SpanMode, B> {
+    pub fn new<P: AsRef<Path>>(params: params::Parameters, runtime_params: RuntimeParameters, tokenizer_path: P, model_path: P) -> GlinerResult<Self> {
         Ok(Self {            
-            model: super::super::Model::new(model_path, runtime_params)?,
+            model: B::load(model_path.as_ref(), runtime_params)?,
             pipeline: SpanPipeline::new(tokenizer_path)?,
             params,
         })
     }
+
+    /// Loads a model directory (see `ModelDir`). The model-dependent parameters and the splitter are derived
+    /// from its configuration, the other parameters being taken from `params`.
+    pub fn from_dir<P: AsRef<Path>>(params: params::Parameters, runtime_params: RuntimeParameters, path: P) -> GlinerResult<Self> {
+        let dir = ModelDir::new(path)?;
+        let config = dir.config()?;
+        config.check_mode(Mode::Span)?;
+        let model = B::load(&dir.model, runtime_params)?;
+        Self::from_config(params, &dir, &config, model)
+    }
+}
+
+impl<B> super::super::GLiNER<SpanMode, B> {
+    /// Same as `from_dir()`, with an already read configuration and an already loaded model
+    pub(crate) fn from_config(params: params::Parameters, dir: &ModelDir, config: &ModelConfig, model: B) -> GlinerResult<Self> {
+        Ok(Self {
+            params: config.parameters(params),
+            model,
+            pipeline: SpanPipeline::with_components(config.splitter()?, crate::text::tokenizer::HFTokenizer::from_file(&dir.tokenizer)?)
+                .with_prompt_format(config.prompt_format()),
+        })
+    }
 }
\ No newline at end of file
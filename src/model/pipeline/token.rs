@@ -3,52 +3,125 @@
 use std::path::Path;
 use ::composable::*;
 use orp::{pipeline::*, params::RuntimeParameters};
-use crate::util::result::Result;
-use super::super::super::text::{splitter::Splitter, tokenizer::Tokenizer};
+use crate::util::result::{Result, GlinerResult};
+use super::super::super::text::{splitter::Splitter, tokenizer::Tokenizer, prompt::PromptFormat};
 use super::super::{input, output, params};
+use super::super::backend::{OnnxBackend, TensorPipeline, Tensors, onnxruntime::{TensorsToSessionInputs, SessionOutputsToTensors}};
 use super::context::EntityContext;
+use crate::util::profile::Traced;
+use super::super::{auto::Mode, config::{ModelConfig, ModelDir}};
 
 
 /// Generic token-level pipeline
 pub struct TokenPipeline<S, T> {
     splitter: S,
     tokenizer: T,
+    prompt_format: PromptFormat,
 }
 
+impl<'a, S: Splitter, T:Tokenizer> TensorPipeline<'a> for TokenPipeline<S, T> {
+    type Input = input::text::TextInput;
+    type Output = output::decoded::SpanOutput;
+    type Context = EntityContext;
+    type Parameters = params::Parameters;
+
+    fn tensor_pre_processor(&self, params: &params::Parameters) -> impl Composable<input::text::TextInput, (Tensors, EntityContext)> {
+        composed![
+            Traced::new(input::tokenized::RawToTokenized::new(&self.splitter, params.max_length)),
+            Traced::new(input::prompt::TokenizedToPrompt::new(self.prompt_format.clone())),
+            Traced::new(input::encoded::PromptsToEncoded::new(&self.tokenizer)),
+            Traced::new(input::tensors::token::EncodedToTensors::default()),
+            Traced::new(input::tensors::token::TensorsToBackendInput::default())
+        ]
+    }
+
+    fn tensor_post_processor(&self, params: &params::Parameters) -> impl Composable<(Tensors, EntityContext), output::decoded::SpanOutput> {
+        composed![
+            Traced::new(output::tensors::BackendOutputToTensors::default()),
+            Traced::new(output::decoded::token::TensorsToDecoded::new(params.threshold).with_class_thresholds(params.class_thresholds.clone())),
+            Traced::new(output::decoded::sort::SpanSort::default()),
+            Traced::new(output::decoded::search::SpanSearch::new(params.span_selection, params.flat_ner, params.dup_label, params.multi_label)),
+            Traced::new(output::decoded::offsets::SpanOffsets::new(params.offset_unit))
+        ]
+    }
+}
+
+impl<'a, S: Splitter, T:Tokenizer> output::scores::ScorePipeline<'a> for TokenPipeline<S, T> {
+    fn score_post_processor(&self, _params: &params::Parameters) -> impl Composable<(Tensors, EntityContext), output::scores::ScoreOutput> {
+        composed![
+            Traced::new(output::tensors::BackendOutputToTensors::default()),
+            Traced::new(output::scores::TensorsToScores::token())
+        ]
+    }
+}
+
+/// Runs on ONNX Runtime through `orp` (see `TensorPipeline` for other backends)
 impl<'a, S: Splitter, T:Tokenizer> Pipeline<'a> for TokenPipeline<S, T> {
     type Input = input::text::TextInput;
     type Output = output::decoded::SpanOutput;
     type Context = EntityContext;
     type Parameters = params::Parameters;
 
-    fn pre_processor(&self, params: &Self::Parameters) -> impl PreProcessor<'a, Self::Input, Self::Context> {
+    fn pre_processor(&self, params: &params::Parameters) -> impl PreProcessor<'a, input::text::TextInput, EntityContext> {
         composed![
-            input::tokenized::RawToTokenized::new(&self.splitter, params.max_length),
-            input::prompt::TokenizedToPrompt::default(),
-            input::encoded::PromptsToEncoded::new(&self.tokenizer),
-            input::tensors::token::EncodedToTensors::default(),
-            input::tensors::token::TensorsToSessionInput::default()
+            self.tensor_pre_processor(params),
+            TensorsToSessionInputs::default()
         ]
     }
 
-    fn post_processor(&self, params: &Self::Parameters) -> impl PostProcessor<'a, Self::Output, Self::Context> {
+    fn post_processor(&self, params: &params::Parameters) -> impl PostProcessor<'a, output::decoded::SpanOutput, EntityContext> {
         composed![
-            output::tensors::SessionOutputToTensors::default(),            
-            output::decoded::token::TensorsToDecoded::new(params.threshold),
-            output::decoded::sort::SpanSort::default(),
-            output::decoded::greedy::GreedySearch::new(params.flat_ner, params.dup_label, params.multi_label)
+            SessionOutputsToTensors::default(),
+            self.tensor_post_processor(params)
         ]
     }
 }
 
 
+impl<S: Splitter, T: Tokenizer> super::Components for TokenPipeline<S, T> {
+    type Splitter = S;
+    type Tokenizer = T;
+
+    fn splitter(&self) -> &S {
+        &self.splitter
+    }
+
+    fn tokenizer(&self) -> &T {
+        &self.tokenizer
+    }
+
+    fn prompt_format(&self) -> &PromptFormat {
+        &self.prompt_format
+    }
+}
+
+
+impl<S, T> TokenPipeline<S, T> {
+    /// Creates a pipeline with the given splitter and tokenizer
+    pub fn with_components(splitter: S, tokenizer: T) -> Self {
+        Self { splitter, tokenizer, prompt_format: PromptFormat::default() }
+    }
+
+    /// Sets the markers used to build the prompts (default: `<<ENT>>` and `<<SEP>>`)
+    pub fn with_prompt_format(mut self, prompt_format: PromptFormat) -> Self {
+        self.prompt_format = prompt_format;
+        self
+    }
+
+    /// Same pipeline, borrowing the splitter and tokenizer of this one (so that a `RelationPipeline`
+    /// can be built on the pipeline of an existing `GLiNER` without loading the tokenizer again)
+    pub fn by_ref(&self) -> TokenPipeline<&S, &T> {
+        TokenPipeline { splitter: &self.splitter, tokenizer: &self.tokenizer, prompt_format: self.prompt_format.clone() }
+    }
+}
+
 /// Specific implementation using HF tokenizer and default splitter
 impl TokenPipeline<crate::text::splitter::RegexSplitter, crate::text::tokenizer::HFTokenizer> {
     pub fn new<P: AsRef<Path>>(tokenizer_path: P) -> Result<Self> {
-        Ok(Self {
-            splitter: crate::text::splitter::RegexSplitter::default(),
-            tokenizer: crate::text::tokenizer::HFTokenizer::from_file(tokenizer_path)?,
-        })
+        Ok(Self::with_components(
+            crate::text::splitter::RegexSplitter::default(),
+            crate::text::tokenizer::HFTokenizer::from_file(tokenizer_path)?,
+        ))
     }
 }
 
@@ -56,13 +129,36 @@ impl TokenPipeline<crate::text::splitter::RegexSplitter, crate::text::tokenizer:
 pub type TokenMode = TokenPipeline<crate::text::splitter::RegexSplitter, crate::text::tokenizer::HFTokenizer>;
 
 
-/// Specific GLiNER implementation using the default token-mode pipeline
-impl super::super::GLiNER<TokenMode> {
-    pub fn new<P: AsRef<Path>>(params: params::Parameters, runtime_params: RuntimeParameters, tokenizer_path: P, model_path: P) -> Result<Self> {
+/// Specific GLiNER implementation using the default token-mode pipeline. The model is run by ONNX Runtime
+/// by default, another backend can be selected by its type (see `OnnxBackend`).
+impl<B: OnnxBackend> super::super::GLiNER<TokenMode, B> {
+    pub fn new<P: AsRef<Path>>(params: params::Parameters, runtime_params: RuntimeParameters, tokenizer_path: P, model_path: P) -> GlinerResult<Self> {
         Ok(Self {
             params, 
-            model: super::super::Model::new(model_path, runtime_params)?,
+            model: B::load(model_path.as_ref(), runtime_params)?,
             pipeline: TokenPipeline::new(tokenizer_path)?,
         })
     }
+
+    /// Loads a model directory (see `ModelDir`). The model-dependent parameters and the splitter are derived
+    /// from its configuration, the other parameters being taken from `params`.
+    pub fn from_dir<P: AsRef<Path>>(params: params::Parameters, runtime_params: RuntimeParameters, path: P) -> GlinerResult<Self> {
+        let dir = ModelDir::new(path)?;
+        let config = dir.config()?;
+        config.check_mode(Mode::Token)?;
+        let model = B::load(&dir.model, runtime_params)?;
+        Self::from_config(params, &dir, &config, model)
+    }
+}
+
+impl<B> super::super::GLiNER<TokenMode, B> {
+    /// Same as `from_dir()`, with an already read configuration and an already loaded model
+    pub(crate) fn from_config(params: params::Parameters, dir: &ModelDir, config: &ModelConfig, model: B) -> GlinerResult<Self> {
+        Ok(Self {
+            params: config.parameters(params),
+            model,
+            pipeline: TokenPipeline::with_components(config.splitter()?, crate::text::tokenizer::HFTokenizer::from_file(&dir.tokenizer)?)
+                .with_prompt_format(config.prompt_format()),
+        })
+    }
 }
\ No newline at end of file
@@ -0,0 +1,180 @@
+//! Pool of ONNX sessions sharing one pipeline, for concurrent inferences
+
+use std::path::Path;
+use std::sync::{Mutex, Condvar};
+use orp::model::Model;
+use orp::params::RuntimeParameters;
+use crate::util::{error::{GlinerError, IndexError}, result::{Result, GlinerResult}};
+use super::params::Parameters;
+use super::backend::{self, TensorPipeline};
+use super::pipeline::{token::{TokenMode, TokenPipeline}, span::{SpanMode, SpanPipeline}};
+
+
+/// Same as `GLiNER`, but backed by several sessions of the same model, so that concurrent callers
+/// can run inferences in parallel. Each inference leases one of the sessions for its whole duration
+/// (waiting for one to be released if they are all busy), while the pipeline and parameters are shared.
+///
+/// Note that a single `GLiNER` is already `Send + Sync` and can be shared as-is between threads, but
+/// concurrent runs of the same session then compete for its threads. Using a pool is a matter of
+/// throughput (at the expense of memory, since each session holds its own copy of the model).
+pub struct GLiNERPool<P> {
+    params: Parameters,
+    models: Vec<Model>,
+    pipeline: P,
+    slots: Slots,
+}
+
+
+impl<P> GLiNERPool<P> {
+    /// Loads `size` sessions of the given model, to be used with the given pipeline
+    pub fn with_pipeline<M: AsRef<Path>>(params: Parameters, pipeline: P, runtime_params: RuntimeParameters, model_path: M, size: usize) -> GlinerResult<Self> {
+        if size == 0 {
+            return Err(GlinerError::InvalidConfiguration("session pool size must be at least 1".to_string()));
+        }
+        let models = (0..size)
+            .map(|_| Model::new(&model_path, RuntimeParameters::new(runtime_params.threads(), runtime_params.execution_providers().to_vec())).map_err(backend::onnxruntime::load_error))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            params,
+            models,
+            pipeline,
+            slots: Slots::new(size),
+        })
+    }
+
+    /// Number of sessions in the pool
+    pub fn size(&self) -> usize {
+        self.models.len()
+    }
+}
+
+
+impl<'a, P: TensorPipeline<'a, Parameters = Parameters>> GLiNERPool<P> {
+    pub fn inference(&'a self, input: P::Input) -> GlinerResult<P::Output> {
+        let lease = self.slots.acquire()?;
+        let model = self.models.get(lease.index).ok_or(IndexError::new("models", lease.index))?;
+        Ok(backend::inference(model, &self.pipeline, &self.params, input)?)
+    }
+}
+
+
+impl GLiNERPool<TokenMode> {
+    pub fn new<P: AsRef<Path>>(params: Parameters, runtime_params: RuntimeParameters, tokenizer_path: P, model_path: P, size: usize) -> GlinerResult<Self> {
+        Self::with_pipeline(params, TokenPipeline::new(tokenizer_path)?, runtime_params, model_path, size)
+    }
+}
+
+
+impl GLiNERPool<SpanMode> {
+    pub fn new<P: AsRef<Path>>(params: Parameters, runtime_params: RuntimeParameters, tokenizer_path: P, model_path: P, size: usize) -> GlinerResult<Self> {
+        Self::with_pipeline(params, SpanPipeline::new(tokenizer_path)?, runtime_params, model_path, size)
+    }
+}
+
+
+/// Indexes of the available sessions
+struct Slots {
+    available: Mutex<Vec<usize>>,
+    released: Condvar,
+}
+
+/// Lease of a session, which is released when dropped
+struct Lease<'a> {
+    slots: &'a Slots,
+    index: usize,
+}
+
+impl Slots {
+    fn new(size: usize) -> Self {
+        Self {
+            available: Mutex::new((0..size).collect()),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Leases an available session, waiting for one to be released if needed
+    fn acquire(&self) -> Result<Lease<'_>> {
+        let available = self.available.lock().map_err(|_| poisoned())?;
+        let mut available = self.released.wait_while(available, |a| a.is_empty()).map_err(|_| poisoned())?;
+        let index = available.pop().ok_or(IndexError::with("no available session"))?;
+        Ok(Lease { slots: self, index })
+    }
+}
+
+impl Drop for Lease<'_> {
+    fn drop(&mut self) {
+        // the lock can only be poisoned by a panic while holding it, which cannot happen in `acquire()`
+        if let Ok(mut available) = self.slots.available.lock() {
+            available.push(self.index);
+        }
+        self.slots.released.notify_one();
+    }
+}
+
+fn poisoned() -> GlinerError {
+    GlinerError::Execution("poisoned session pool".to_string())
+}
+
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_send_sync() {
+        assert_send_sync::<super::super::GLiNER<TokenMode>>();
+        assert_send_sync::<super::super::GLiNER<SpanMode>>();
+        assert_send_sync::<super::super::GLiNER<super::super::pipeline::chunked::ChunkedPipeline<TokenMode>>>();
+        assert_send_sync::<super::super::auto::AutoGLiNER>();
+        assert_send_sync::<GLiNERPool<TokenMode>>();
+        assert_send_sync::<GLiNERPool<SpanMode>>();
+    }
+
+    #[test]
+    fn test_slots() -> Result<()> {
+        // Processing: 8 threads competing for 3 slots
+        let slots = Slots::new(3);
+        let busy = AtomicUsize::new(0);
+        let max_busy = AtomicUsize::new(0);
+        let leased: Mutex<Vec<bool>> = Mutex::new(vec![false; 3]);
+        std::thread::scope(|scope| -> Result<()> {
+            let handles: Vec<_> = (0..8).map(|_| {
+                scope.spawn(|| -> Result<()> {
+                    for _ in 0..20 {
+                        let lease = slots.acquire()?;
+                        // a slot is never leased twice at the same time
+                        {
+                            let mut leased = leased.lock().map_err(|_| "poisoned")?;
+                            let slot = leased.get_mut(lease.index).ok_or("invalid index")?;
+                            assert!(!*slot);
+                            *slot = true;
+                        }
+                        let current = busy.fetch_add(1, Ordering::SeqCst) + 1;
+                        max_busy.fetch_max(current, Ordering::SeqCst);
+                        std::thread::yield_now();
+                        busy.fetch_sub(1, Ordering::SeqCst);
+                        {
+                            let mut leased = leased.lock().map_err(|_| "poisoned")?;
+                            let slot = leased.get_mut(lease.index).ok_or("invalid index")?;
+                            *slot = false;
+                        }
+                    }
+                    Ok(())
+                })
+            }).collect();
+            for handle in handles {
+                handle.join().map_err(|_| "thread panicked")??;
+            }
+            Ok(())
+        })?;
+        // Assertions
+        assert!(max_busy.load(Ordering::SeqCst) <= 3);
+        assert_eq!(slots.available.lock().map_err(|_| "poisoned")?.len(), 3);
+        // Everything rules
+        Ok(())
+    }
+}
@@ -0,0 +1,474 @@
+//! Extraction of the visible text of markup documents (HTML and Markdown)
+//!
+//! Both normalizers are lenient: they never fail, anything that cannot be recognized as markup being
+//! kept as text. Whitespace (non-breaking spaces included) is collapsed, and block-level elements are separated
+//! by whitespace, so that words of consecutive blocks are not glued together. The resulting `Alignment`
+//! maps the offsets of the visible text back to offsets in the source document.
+
+use super::normalizer::{Normalizer, Normalized, collapse_whitespace};
+
+
+/// Elements which content is not visible
+const HIDDEN_ELEMENTS: [&str; 3] = ["script", "style", "template"];
+
+/// Elements which break the flow of text
+const BLOCK_ELEMENTS: [&str; 38] = [
+    "address", "article", "aside", "blockquote", "br", "caption", "dd", "details", "div", "dl", "dt",
+    "fieldset", "figcaption", "figure", "footer", "form", "h1", "h2", "h3", "h4", "h5", "h6", "header",
+    "hr", "li", "main", "nav", "ol", "option", "p", "pre", "section", "summary", "table", "td", "th",
+    "tr", "ul",
+];
+
+/// Named character references which are decoded (others are kept as is)
+const REFERENCES: [(&str, char); 16] = [
+    ("amp", '&'), ("lt", '<'), ("gt", '>'), ("quot", '"'), ("apos", '\''), ("nbsp", '\u{00A0}'),
+    ("shy", '\u{00AD}'), ("ndash", '\u{2013}'), ("mdash", '\u{2014}'), ("hellip", '\u{2026}'),
+    ("lsquo", '\u{2018}'), ("rsquo", '\u{2019}'), ("ldquo", '\u{201C}'), ("rdquo", '\u{201D}'),
+    ("laquo", '\u{00AB}'), ("raquo", '\u{00BB}'),
+];
+
+
+/// Normalizer extracting the visible text of HTML documents
+///
+/// Tags, comments and declarations are removed, as well as the content of `script`, `style` and
+/// `template` elements. Character references are decoded (numeric ones, and the most common named ones).
+#[derive(Default)]
+pub struct HtmlNormalizer {}
+
+impl Normalizer for HtmlNormalizer {
+    fn normalize(&self, text: &str) -> Normalized {
+        let mut chars = Vec::with_capacity(text.len());
+        let mut i = 0;
+        while let Some(c) = text.get(i..).and_then(|s| s.chars().next()) {
+            match c {
+                '<' => match Tag::parse(text, i) {
+                    Some(tag) => {
+                        if tag.is_block() {
+                            chars.push(('\n', i, tag.end));
+                        }
+                        i = if tag.is_hidden() { hidden_content_end(text, &tag) } else { tag.end };
+                    },
+                    None => {
+                        chars.push((c, i, i + 1));
+                        i += 1;
+                    },
+                },
+                '&' => i = push_reference(text, i, &mut chars),
+                c => {
+                    chars.push((c, i, i + c.len_utf8()));
+                    i += c.len_utf8();
+                },
+            }
+        }
+        Normalized::from_chars(collapse_whitespace(chars))
+    }
+}
+
+
+/// Normalizer extracting the visible text of Markdown documents
+///
+/// Block markers (headings, quotes, list items, fences, rules, table delimiters...), emphasis and code
+/// delimiters, link destinations and inline HTML tags are removed, the text of links and the alternative
+/// text of images being kept. The content of code blocks and code spans is kept as is.
+#[derive(Default)]
+pub struct MarkdownNormalizer {}
+
+impl Normalizer for MarkdownNormalizer {
+    fn normalize(&self, text: &str) -> Normalized {
+        let mut chars = Vec::with_capacity(text.len());
+        let mut fence: Option<&str> = None;
+        let mut offset = 0;
+        for line in text.split_inclusive('\n') {
+            let start = offset;
+            offset += line.len();
+            let content = line.trim_end_matches(['\n', '\r']);
+            let trimmed = content.trim_start_matches(' ');
+            let indent = content.len() - trimmed.len();
+            match fence {
+                // within a fenced code block: kept as is, up to the closing fence
+                Some(marker) => {
+                    if indent < 4 && trimmed.starts_with(marker) && trimmed.trim_start_matches(&marker[..1]).trim().is_empty() {
+                        fence = None;
+                    }
+                    else {
+                        push_literal(content, start, &mut chars);
+                    }
+                },
+                None if indent < 4 && (trimmed.starts_with("```") || trimmed.starts_with("~~~")) => {
+                    let marker = &trimmed[..1];
+                    fence = Some(&trimmed[..trimmed.len() - trimmed.trim_start_matches(marker).len()]);
+                },
+                None if is_rule(trimmed) || is_reference_definition(trimmed) => (),
+                None => {
+                    let (first, last, table) = line_content(content);
+                    let slice = content.get(first..last).unwrap_or_default();
+                    push_inline(slice, start + first, table, &mut chars);
+                },
+            }
+            // line break
+            if content.len() < line.len() {
+                chars.push(('\n', start + content.len(), offset));
+            }
+        }
+        Normalized::from_chars(collapse_whitespace(chars))
+    }
+}
+
+
+/// Tag (or comment, or declaration) starting at a given offset of an HTML text
+struct Tag {
+    /// Lowercase name (empty for comments and declarations)
+    name: String,
+    closing: bool,
+    /// Offset of the end of the tag
+    end: usize,
+}
+
+impl Tag {
+    /// Parses the tag starting at the given offset, if any (a `<` which does not start a tag being text)
+    fn parse(text: &str, start: usize) -> Option<Self> {
+        let rest = text.get(start..)?;
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            let end = comment.find("-->").map_or(text.len(), |i| start + 4 + i + 3);
+            return Some(Self { name: String::new(), closing: false, end });
+        }
+        if rest.starts_with("<!") || rest.starts_with("<?") {
+            let end = rest.find('>').map_or(text.len(), |i| start + i + 1);
+            return Some(Self { name: String::new(), closing: false, end });
+        }
+        let closing = rest.starts_with("</");
+        let name_start = if closing { 2 } else { 1 };
+        let name: String = rest.get(name_start..)?.chars().take_while(|c| c.is_ascii_alphanumeric() || *c == '-').collect();
+        if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            return None;
+        }
+        // look for the end of the tag, skipping quoted attribute values
+        let mut quote = None;
+        for (i, c) in rest.char_indices().skip(name_start + name.len()) {
+            match (quote, c) {
+                (None, '>') => return Some(Self { name: name.to_ascii_lowercase(), closing, end: start + i + 1 }),
+                (None, '"' | '\'') => quote = Some(c),
+                (Some(q), c) if q == c => quote = None,
+                _ => (),
+            }
+        }
+        None
+    }
+
+    fn is_block(&self) -> bool {
+        BLOCK_ELEMENTS.contains(&self.name.as_str())
+    }
+
+    fn is_hidden(&self) -> bool {
+        !self.closing && HIDDEN_ELEMENTS.contains(&self.name.as_str())
+    }
+}
+
+
+/// Offset of the end of the closing tag of the given hidden element (or of the text if not closed)
+fn hidden_content_end(text: &str, tag: &Tag) -> usize {
+    let rest = text.get(tag.end..).unwrap_or_default().to_ascii_lowercase();
+    match rest.find(&format!("</{}", tag.name)) {
+        Some(i) => Tag::parse(text, tag.end + i).map_or(text.len(), |closing| closing.end),
+        None => text.len(),
+    }
+}
+
+
+/// Pushes the character reference starting at the given offset if any (or a `&` otherwise), and returns the offset after it
+fn push_reference(text: &str, start: usize, chars: &mut Vec<(char, usize, usize)>) -> usize {
+    let rest = text.get(start + 1..).unwrap_or_default();
+    let reference = rest.find(';').filter(|i| *i > 0 && *i <= 32).and_then(|i| rest.get(..i));
+    let decoded = reference.and_then(|name| match name.strip_prefix('#') {
+        Some(number) => match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok(),
+            None => number.parse().ok(),
+        }.and_then(char::from_u32),
+        None => REFERENCES.iter().find(|(n, _)| *n == name).map(|(_, c)| *c),
+    });
+    match (reference, decoded) {
+        (Some(name), Some(c)) => {
+            let end = start + name.len() + 2;
+            chars.push((c, start, end));
+            end
+        },
+        _ => {
+            chars.push(('&', start, start + 1));
+            start + 1
+        },
+    }
+}
+
+
+/// Pushes the given text as is
+fn push_literal(text: &str, offset: usize, chars: &mut Vec<(char, usize, usize)>) {
+    for (i, c) in text.char_indices() {
+        chars.push((c, offset + i, offset + i + c.len_utf8()));
+    }
+}
+
+
+/// Thematic break or setext heading underline (`---`, `***`, `___` or `===`), or delimiter row of a table
+fn is_rule(line: &str) -> bool {
+    let line = line.trim_end();
+    let rule = line.chars().next().is_some_and(|first| "-*_=".contains(first)
+        && line.chars().all(|c| c == first || c == ' ' || c == '\t')
+        && line.chars().filter(|c| *c == first).count() >= 3);
+    let delimiter_row = line.contains('|') && line.contains('-') && line.chars().all(|c| "|-: \t".contains(c));
+    rule || delimiter_row
+}
+
+/// Link reference definition (`[label]: destination`)
+fn is_reference_definition(line: &str) -> bool {
+    line.starts_with('[') && line.find("]:").is_some_and(|i| i > 1)
+}
+
+
+/// Range of the content of a line once its container and heading markers are removed, and whether it is a table row
+fn line_content(line: &str) -> (usize, usize, bool) {
+    let mut first = 0;
+    let mut last = line.len();
+    loop {
+        let rest = line.get(first..).unwrap_or_default();
+        let trimmed = rest.trim_start_matches([' ', '\t']);
+        let skipped = rest.len() - trimmed.len();
+        let marker = if let Some(quote) = trimmed.strip_prefix('>') {
+            Some(trimmed.len() - quote.len())
+        }
+        else {
+            list_marker(trimmed)
+        };
+        match marker {
+            Some(marker) => first += skipped + marker,
+            None => {
+                first += skipped;
+                break;
+            },
+        }
+    }
+    let rest = line.get(first..).unwrap_or_default();
+    let hashes = rest.len() - rest.trim_start_matches('#').len();
+    if (1..=6).contains(&hashes) && rest.get(hashes..).is_some_and(|r| r.is_empty() || r.starts_with([' ', '\t'])) {
+        // ATX heading, with optional closing sequence
+        first += hashes;
+        let content = line.get(first..).unwrap_or_default().trim_end();
+        let closing = content.trim_end_matches('#');
+        last = first + if closing.is_empty() || closing.ends_with([' ', '\t']) { closing.len() } else { content.len() };
+    }
+    (first, last, line.get(first..).is_some_and(|r| r.starts_with('|')))
+}
+
+/// Length of the list marker (with an optional task box) at the start of the given text, if any
+fn list_marker(text: &str) -> Option<usize> {
+    let digits = text.len() - text.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let bullet = match digits {
+        0 if text.starts_with(['-', '*', '+']) => 1,
+        1..=9 if text.get(digits..).is_some_and(|r| r.starts_with(['.', ')'])) => digits + 1,
+        _ => return None,
+    };
+    let rest = text.get(bullet..)?;
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    let task = ["[ ]", "[x]", "[X]"].iter().any(|t| rest.trim_start().starts_with(t));
+    let spaces = rest.len() - rest.trim_start().len();
+    Some(bullet + spaces + if task { 3 } else { 0 })
+}
+
+
+/// Pushes the visible text of a span of inline Markdown starting at the given offset (pipes being cell separators if `table`)
+fn push_inline(text: &str, offset: usize, table: bool, chars: &mut Vec<(char, usize, usize)>) {
+    let mut i = 0;
+    while let Some(c) = text.get(i..).and_then(|s| s.chars().next()) {
+        let rest = text.get(i..).unwrap_or_default();
+        let start = offset + i;
+        match c {
+            // escaped punctuation
+            '\\' if rest.chars().nth(1).is_some_and(|c| c.is_ascii_punctuation()) => {
+                let escaped = rest.chars().nth(1).unwrap_or(c);
+                chars.push((escaped, start, start + 2));
+                i += 2;
+            },
+            // code span
+            '`' => {
+                let run = rest.len() - rest.trim_start_matches('`').len();
+                match find_run(text, i + run, '`', run, false) {
+                    Some(end) => {
+                        push_literal(text.get(i + run..end).unwrap_or_default(), start + run, chars);
+                        i = end + run;
+                    },
+                    None => {
+                        push_literal(text.get(i..i + run).unwrap_or_default(), start, chars);
+                        i += run;
+                    },
+                }
+            },
+            // links and images (only the text is kept)
+            '[' | '!' => match link(text, if c == '!' { i + 1 } else { i }) {
+                Some((text_start, text_end, end)) => {
+                    push_inline(text.get(text_start..text_end).unwrap_or_default(), offset + text_start, table, chars);
+                    i = end;
+                },
+                None => {
+                    chars.push((c, start, start + 1));
+                    i += 1;
+                },
+            },
+            // autolinks and inline HTML
+            '<' => {
+                let autolink = rest.find('>').filter(|end| {
+                    let inner = rest.get(1..*end).unwrap_or_default();
+                    !inner.contains(char::is_whitespace) && (inner.contains("://") || inner.contains('@'))
+                });
+                match (autolink, Tag::parse(text, i)) {
+                    (Some(end), _) => {
+                        push_literal(rest.get(1..end).unwrap_or_default(), start + 1, chars);
+                        i += end + 1;
+                    },
+                    (None, Some(tag)) => {
+                        if tag.is_block() {
+                            chars.push(('\n', start, offset + tag.end));
+                        }
+                        i = tag.end;
+                    },
+                    (None, None) => {
+                        chars.push((c, start, start + 1));
+                        i += 1;
+                    },
+                }
+            },
+            '&' => {
+                let mut decoded = Vec::new();
+                let end = push_reference(text, i, &mut decoded);
+                chars.extend(decoded.into_iter().map(|(c, s, e)| (c, s + offset, e + offset)));
+                i = end;
+            },
+            // emphasis and strikethrough: delimiters are removed if matched
+            '*' | '_' | '~' => {
+                let run = rest.len() - rest.trim_start_matches(c).len();
+                let before = text.get(..i).and_then(|s| s.chars().next_back());
+                let after = rest.get(run..).and_then(|s| s.chars().next());
+                let opening = after.is_some_and(|a| !a.is_whitespace())
+                    && !(c == '_' && before.is_some_and(char::is_alphanumeric))
+                    && (c != '~' || run == 2);
+                match find_run(text, i + run, c, run, true).filter(|_| opening) {
+                    Some(end) => {
+                        push_inline(text.get(i + run..end).unwrap_or_default(), start + run, table, chars);
+                        i = end + run;
+                    },
+                    None => {
+                        push_literal(text.get(i..i + run).unwrap_or_default(), start, chars);
+                        i += run;
+                    },
+                }
+            },
+            '|' if table => {
+                chars.push((' ', start, start + 1));
+                i += 1;
+            },
+            c => {
+                chars.push((c, start, start + c.len_utf8()));
+                i += c.len_utf8();
+            },
+        }
+    }
+}
+
+/// Offset of the first run of exactly `length` characters `c` from the given offset (which must be able
+/// to close an emphasis if `closing`)
+fn find_run(text: &str, from: usize, c: char, length: usize, closing: bool) -> Option<usize> {
+    let mut i = from;
+    while let Some(found) = text.get(i..).and_then(|s| s.find(c)) {
+        let start = i + found;
+        let rest = text.get(start..).unwrap_or_default();
+        let run = rest.len() - rest.trim_start_matches(c).len();
+        let before = text.get(..start).and_then(|s| s.chars().next_back());
+        let after = rest.get(run..).and_then(|s| s.chars().next());
+        // closing underscores cannot be followed by an alphanumeric character either (as in `snake_case`)
+        let flanking = before.is_some_and(|b| !b.is_whitespace()) && !(c == '_' && after.is_some_and(char::is_alphanumeric));
+        if run == length && start > from && (!closing || flanking) {
+            return Some(start);
+        }
+        i = start + run;
+    }
+    None
+}
+
+/// Range of the text of the link (`[text](destination)` or `[text][label]`) starting at the given offset,
+/// and offset of its end
+fn link(text: &str, start: usize) -> Option<(usize, usize, usize)> {
+    let rest = text.get(start..)?;
+    if !rest.starts_with('[') {
+        return None;
+    }
+    // matching bracket
+    let mut depth = 0;
+    let mut escaped = false;
+    let mut close = None;
+    for (i, c) in rest.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    close = Some(i);
+                    break;
+                }
+            },
+            _ => (),
+        }
+    }
+    let close = close?;
+    let after = rest.get(close + 1..)?;
+    let end = match after.chars().next() {
+        Some('(') => after.find(')')?,
+        Some('[') => after.find(']')?,
+        _ => return None,
+    };
+    Some((start + 1, start + close, start + close + 1 + end + 1))
+}
+
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    // Silent some clippy warnings for unit tests
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    /// Source text of the given substring of the normalized text
+    fn source<'a>(source: &'a str, normalized: &Normalized, text: &str) -> &'a str {
+        let start = normalized.text.find(text).unwrap();
+        let (start, end) = normalized.alignment.original(start, start + text.len()).unwrap();
+        &source[start..end]
+    }
+
+    #[test]
+    fn test_html() {
+        let html = "<html><head><title>Bio</title><style>p { color: red; }</style></head>\n<body><!-- draft --><h1>James&nbsp;Bond</h1><p>Born in <a href=\"/wiki?a=1&b=2\" title='x > y'>Wattenscheid</a>,<br/>works for <b>MI</b>6 &amp; the&#160;Crown &lt;3.</p><script>var x = \"<p>\";</script><p>AT&T</p></body></html>";
+        // Processing
+        let normalized = HtmlNormalizer::default().normalize(html);
+        // Assertions
+        assert_eq!(normalized.text, "Bio James Bond Born in Wattenscheid, works for MI6 & the Crown <3. AT&T");
+        assert_eq!(source(html, &normalized, "James Bond"), "James&nbsp;Bond");
+        assert_eq!(source(html, &normalized, "Wattenscheid"), "Wattenscheid");
+        assert_eq!(source(html, &normalized, "MI6"), "MI</b>6");
+        assert_eq!(source(html, &normalized, "& the Crown"), "&amp; the&#160;Crown");
+    }
+
+    #[test]
+    fn test_markdown() {
+        let markdown = "# James *Bond* #\n\n> Born in [Wattenscheid](https://en.wikipedia.org/wiki/Wattenscheid \"town\"),\n> works for **MI6**.\n\n- [ ] call `M_agent`\n- ~~kill~~ see_also _Q_ <br> \\*not emphasis\\*\n\n---\n\n```rust\nlet x = *y;\n```\n\n| Name | Agency |\n|------|--------|\n| Bond | MI6 |\n\n![Sean Connery](img.png) & <https://example.com>\n\n[ref]: https://example.com\n";
+        // Processing
+        let normalized = MarkdownNormalizer::default().normalize(markdown);
+        // Assertions
+        assert_eq!(normalized.text, "James Bond Born in Wattenscheid, works for MI6. call M_agent kill see_also Q *not emphasis* let x = *y; Name Agency Bond MI6 Sean Connery & https://example.com");
+        assert_eq!(source(markdown, &normalized, "James Bond"), "James *Bond");
+        assert_eq!(source(markdown, &normalized, "Wattenscheid"), "Wattenscheid");
+        assert_eq!(source(markdown, &normalized, "MI6."), "MI6**.");
+        assert_eq!(source(markdown, &normalized, "M_agent"), "M_agent");
+        assert_eq!(source(markdown, &normalized, "Sean Connery"), "Sean Connery");
+    }
+}
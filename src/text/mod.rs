@@ -3,5 +3,8 @@
 pub mod token;
 pub mod prompt;
 pub mod span;
+pub mod offsets;
+pub mod normalizer;
+pub mod markup;
 pub mod splitter;
 pub mod tokenizer;
\ No newline at end of file
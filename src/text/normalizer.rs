@@ -0,0 +1,268 @@
+//! Text normalization, keeping track of where each normalized character comes from
+//!
+//! Inputs coming from OCR or web scraping often contain soft hyphens, zero-width characters, words broken
+//! at line ends, compatibility characters (ligatures, full-width forms, non-breaking spaces...) or curly
+//! quotes, which hurt the model. A `TextNormalizer` cleans them up, and returns an `Alignment` allowing to map
+//! the offsets of the normalized text back to the original one. Other normalizers extract the visible text
+//! of markup documents (see `markup`).
+
+use unicode_normalization::{UnicodeNormalization, is_nfkc_quick, IsNormalized};
+use unicode_segmentation::UnicodeSegmentation;
+use crate::util::{error::GlinerError, result::Result};
+
+
+/// Zero-width characters removed along with whitespace normalization (joiners are kept, as they matter to some scripts and emojis)
+const ZERO_WIDTH: [char; 3] = ['\u{200B}', '\u{2060}', '\u{FEFF}'];
+
+/// Soft hyphen, removed along with dehyphenation
+const SOFT_HYPHEN: char = '\u{00AD}';
+
+
+/// Transformation of texts into the ones to be processed, keeping track of offsets
+pub trait Normalizer {
+    fn normalize(&self, text: &str) -> Normalized;
+}
+
+
+/// Text normalizer for noisy inputs
+///
+/// Every step is enabled by default:
+/// * NFKC normalization (applied to each grapheme cluster)
+/// * whitespace normalization: zero-width characters are removed, and runs of whitespace are collapsed
+///   into a single space (leading and trailing whitespace being removed)
+/// * dehyphenation: soft hyphens are removed, and words broken by a hyphen at the end of a line are joined
+///   (when the next line starts with a lowercase letter)
+/// * quote folding: curly single and double quotes are replaced by their ASCII counterparts
+#[derive(Debug, Clone)]
+pub struct TextNormalizer {
+    nfkc: bool,
+    whitespace: bool,
+    dehyphenation: bool,
+    quote_folding: bool,
+}
+
+impl Default for TextNormalizer {
+    fn default() -> Self {
+        Self {
+            nfkc: true,
+            whitespace: true,
+            dehyphenation: true,
+            quote_folding: true,
+        }
+    }
+}
+
+impl TextNormalizer {
+    pub fn with_nfkc(mut self, nfkc: bool) -> Self {
+        self.nfkc = nfkc;
+        self
+    }
+
+    pub fn with_whitespace(mut self, whitespace: bool) -> Self {
+        self.whitespace = whitespace;
+        self
+    }
+
+    pub fn with_dehyphenation(mut self, dehyphenation: bool) -> Self {
+        self.dehyphenation = dehyphenation;
+        self
+    }
+
+    pub fn with_quote_folding(mut self, quote_folding: bool) -> Self {
+        self.quote_folding = quote_folding;
+        self
+    }
+
+    /// Characters of the text after NFKC normalization, removal of invisible characters and quote folding,
+    /// with the byte range of the original text they come from
+    fn characters(&self, text: &str) -> Vec<(char, usize, usize)> {
+        let mut result = Vec::with_capacity(text.len());
+        let mut push = |c: char, start: usize, end: usize| {
+            if (self.whitespace && ZERO_WIDTH.contains(&c)) || (self.dehyphenation && c == SOFT_HYPHEN) {
+                return;
+            }
+            result.push((if self.quote_folding { fold_quote(c) } else { c }, start, end));
+        };
+        for (start, grapheme) in text.grapheme_indices(true) {
+            if self.nfkc && is_nfkc_quick(grapheme.chars()) != IsNormalized::Yes {
+                let normalized: String = grapheme.nfkc().collect();
+                if normalized != grapheme {
+                    // the characters of a normalized cluster all come from the whole original cluster
+                    for c in normalized.chars() {
+                        push(c, start, start + grapheme.len());
+                    }
+                    continue;
+                }
+            }
+            for (i, c) in grapheme.char_indices() {
+                push(c, start + i, start + i + c.len_utf8());
+            }
+        }
+        result
+    }
+}
+
+
+impl Normalizer for TextNormalizer {
+    fn normalize(&self, text: &str) -> Normalized {
+        let chars = self.characters(text);
+        let chars = if self.dehyphenation { dehyphenate(chars) } else { chars };
+        let chars = if self.whitespace { collapse_whitespace(chars) } else { chars };
+        Normalized::from_chars(chars)
+    }
+}
+
+
+/// Normalized text, with its alignment to the original one
+pub struct Normalized {
+    pub text: String,
+    pub alignment: Alignment,
+}
+
+impl Normalized {
+    /// Builds the normalized text from its characters, given with the byte range of the original text they come from
+    pub(crate) fn from_chars(chars: Vec<(char, usize, usize)>) -> Self {
+        let mut text = String::with_capacity(chars.len());
+        let mut offsets = Vec::with_capacity(chars.len());
+        for (c, start, end) in chars {
+            offsets.push((text.len(), start, end));
+            text.push(c);
+        }
+        Self {
+            text,
+            alignment: Alignment { offsets },
+        }
+    }
+}
+
+
+/// Maps the byte offsets of a normalized text to the byte offsets of the original text
+#[derive(Debug, Clone, Default)]
+pub struct Alignment {
+    /// For each character of the normalized text: its byte offset, and the byte range of the original text it comes from
+    offsets: Vec<(usize, usize, usize)>,
+}
+
+impl Alignment {
+    /// Original offsets of the given (non-empty) range of the normalized text, covering all the original
+    /// characters its normalized characters come from
+    pub fn original(&self, start: usize, end: usize) -> Result<(usize, usize)> {
+        // first character starting at `start`, and last one starting before `end`
+        let first = self.offsets.partition_point(|(offset, _, _)| *offset < start);
+        let last = self.offsets.partition_point(|(offset, _, _)| *offset < end).checked_sub(1);
+        match (self.offsets.get(first), last.and_then(|last| self.offsets.get(last))) {
+            (Some((offset, original_start, _)), Some((_, _, original_end))) if *offset == start && start < end => {
+                Ok((*original_start, *original_end))
+            },
+            _ => Err(GlinerError::invalid_input(&format!("invalid range of normalized text: {start}..{end}")).into()),
+        }
+    }
+}
+
+
+/// Replaces curly quotes by ASCII ones
+fn fold_quote(c: char) -> char {
+    match c {
+        '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' | '\u{02BC}' => '\'',
+        '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' => '"',
+        c => c,
+    }
+}
+
+
+/// Joins words broken by a hyphen at the end of a line
+fn dehyphenate(chars: Vec<(char, usize, usize)>) -> Vec<(char, usize, usize)> {
+    let mut result: Vec<(char, usize, usize)> = Vec::with_capacity(chars.len());
+    let mut i = 0;
+    while let Some(&(c, start, end)) = chars.get(i) {
+        if matches!(c, '-' | '\u{2010}') && result.last().is_some_and(|(p, _, _)| p.is_alphabetic()) {
+            // skip the hyphen and the following whitespace, provided it contains a line break and is followed by a lowercase letter
+            let next = i + 1 + chars.iter().skip(i + 1).take_while(|(c, _, _)| c.is_whitespace()).count();
+            let line_break = chars.iter().take(next).skip(i + 1).any(|(c, _, _)| *c == '\n');
+            if line_break && chars.get(next).is_some_and(|(c, _, _)| c.is_lowercase()) {
+                i = next;
+                continue;
+            }
+        }
+        result.push((c, start, end));
+        i += 1;
+    }
+    result
+}
+
+
+/// Collapses runs of whitespace into a single space, removing leading and trailing whitespace
+pub(crate) fn collapse_whitespace(chars: Vec<(char, usize, usize)>) -> Vec<(char, usize, usize)> {
+    let mut result: Vec<(char, usize, usize)> = Vec::with_capacity(chars.len());
+    let mut pending: Option<(usize, usize)> = None;
+    for (c, start, end) in chars {
+        if c.is_whitespace() {
+            pending = Some(pending.map_or((start, end), |(first, _)| (first, end)));
+            continue;
+        }
+        if let Some((first, last)) = pending.take() {
+            if !result.is_empty() {
+                result.push((' ', first, last));
+            }
+        }
+        result.push((c, start, end));
+    }
+    result
+}
+
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    // Silent some clippy warnings for unit tests
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn test() -> Result<()> {
+        let text = "  The \u{201C}ﬁnal\u{201D} infor-\n  mation\u{00AD}al re\u{301}sume\u{301}\u{200B} of Zoë\u{00A0}Smith ";
+        // Processing
+        let normalized = TextNormalizer::default().normalize(text);
+        // Assertions
+        assert_eq!(normalized.text, "The \"final\" informational résumé of Zoë Smith");
+        let original = |s: &str| -> Result<&str> {
+            let start = normalized.text.find(s).unwrap();
+            let (start, end) = normalized.alignment.original(start, start + s.len())?;
+            Ok(&text[start..end])
+        };
+        assert_eq!(original("The")?, "The");
+        assert_eq!(original("final")?, "ﬁnal");
+        assert_eq!(original("fi")?, "ﬁ");
+        assert_eq!(original("informational")?, "infor-\n  mation\u{00AD}al");
+        assert_eq!(original("résumé")?, "re\u{301}sume\u{301}");
+        assert_eq!(original("Zoë Smith")?, "Zoë\u{00A0}Smith");
+        assert_eq!(original("\"final\"")?, "\u{201C}ﬁnal\u{201D}");
+        // Everything rules
+        Ok(())
+    }
+
+    #[test]
+    fn test_steps() -> Result<()> {
+        let text = "don\u{2019}t  stop\u{00AD}ping well-\nKnown";
+        assert_eq!(TextNormalizer::default().with_quote_folding(false).normalize(text).text, "don\u{2019}t stopping well- Known");
+        assert_eq!(TextNormalizer::default().with_dehyphenation(false).normalize(text).text, "don't stop\u{00AD}ping well- Known");
+        assert_eq!(TextNormalizer::default().with_whitespace(false).normalize(text).text, "don't  stopping well-\nKnown");
+        assert_eq!(TextNormalizer::default().with_nfkc(false).normalize("ﬁ").text, "ﬁ");
+        // identity when everything is disabled
+        let normalizer = TextNormalizer::default().with_nfkc(false).with_whitespace(false).with_dehyphenation(false).with_quote_folding(false);
+        let normalized = normalizer.normalize(text);
+        assert_eq!(normalized.text, text);
+        assert_eq!(normalized.alignment.original(0, text.len())?, (0, text.len()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_ranges() -> Result<()> {
+        let normalized = TextNormalizer::default().normalize("Zoë");
+        assert_eq!(normalized.alignment.original(0, 4)?, (0, 4));
+        assert!(normalized.alignment.original(3, 4).is_err()); // not a char boundary
+        assert!(normalized.alignment.original(1, 1).is_err()); // empty
+        assert!(normalized.alignment.original(4, 5).is_err()); // out of bounds
+        Ok(())
+    }
+}
@@ -0,0 +1,155 @@
+//! Conversion of byte offsets to other units
+//!
+//! Offsets produced by the splitters (and thus carried by spans) are byte offsets into Rust strings.
+//! Other environments usually count differently: Unicode scalar values (Python, most databases),
+//! UTF-16 code units (JavaScript, Java, .NET), or user-perceived characters (grapheme clusters).
+
+use unicode_segmentation::UnicodeSegmentation;
+use crate::util::{error::GlinerError, result::Result};
+use super::span::Span;
+
+
+/// Unit of text offsets
+///
+/// With the `serde` feature enabled, it is (de)serialized as `"byte"`, `"char"`, `"utf16"` or `"grapheme"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum OffsetUnit {
+    /// UTF-8 bytes (the native offsets)
+    #[default]
+    Byte,
+    /// Unicode scalar values (Rust `char`s)
+    Char,
+    /// UTF-16 code units
+    Utf16,
+    /// Extended grapheme clusters
+    Grapheme,
+}
+
+
+/// Offsets of a span in a given unit (see `Span::unit_offsets()`)
+///
+/// With the `serde` feature enabled, it is serialized as:
+/// ```json
+/// { "unit": "utf16", "start": 10, "end": 20 }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UnitOffsets {
+    pub unit: OffsetUnit,
+    pub start: usize,
+    pub end: usize,
+}
+
+
+/// Maps the byte offsets of a text to the given unit
+pub struct OffsetMap {
+    unit: OffsetUnit,
+    /// Byte offset and unit offset of the start of each unit, followed by the end of the text
+    boundaries: Vec<(usize, usize)>,
+}
+
+impl OffsetMap {
+    pub fn new(text: &str, unit: OffsetUnit) -> Self {
+        let mut boundaries = Vec::new();
+        let mut offset = 0;
+        match unit {
+            OffsetUnit::Byte => (),
+            OffsetUnit::Char | OffsetUnit::Utf16 => for (i, c) in text.char_indices() {
+                boundaries.push((i, offset));
+                offset += if unit == OffsetUnit::Utf16 { c.len_utf16() } else { 1 };
+            },
+            OffsetUnit::Grapheme => for (i, _) in text.grapheme_indices(true) {
+                boundaries.push((i, offset));
+                offset += 1;
+            },
+        }
+        boundaries.push((text.len(), if unit == OffsetUnit::Byte { text.len() } else { offset }));
+        Self { unit, boundaries }
+    }
+
+    pub fn unit(&self) -> OffsetUnit {
+        self.unit
+    }
+
+    /// Offset of the unit containing the given byte
+    pub fn start(&self, byte_offset: usize) -> Result<usize> {
+        self.check(byte_offset)?;
+        if self.unit == OffsetUnit::Byte {
+            return Ok(byte_offset);
+        }
+        let index = self.boundaries.partition_point(|(b, _)| *b <= byte_offset);
+        Ok(index.checked_sub(1).and_then(|i| self.boundaries.get(i)).map(|(_, u)| *u).unwrap_or_default())
+    }
+
+    /// Offset of the end of the unit containing the byte before the given one, which only differs from
+    /// `start()` if the given byte is within a unit (e.g. a combining mark within a grapheme cluster)
+    pub fn end(&self, byte_offset: usize) -> Result<usize> {
+        self.check(byte_offset)?;
+        if self.unit == OffsetUnit::Byte {
+            return Ok(byte_offset);
+        }
+        let index = self.boundaries.partition_point(|(b, _)| *b < byte_offset);
+        Ok(self.boundaries.get(index).map(|(_, u)| *u).unwrap_or_default())
+    }
+
+    /// Offsets of the given span, which must refer to the text of this map
+    pub fn span(&self, span: &Span) -> Result<UnitOffsets> {
+        let (start, end) = span.offsets();
+        Ok(UnitOffsets { unit: self.unit, start: self.start(start)?, end: self.end(end)? })
+    }
+
+    fn check(&self, byte_offset: usize) -> Result<()> {
+        match self.boundaries.last() {
+            Some((len, _)) if byte_offset <= *len => Ok(()),
+            _ => Err(GlinerError::invalid_input(&format!("offset out of bounds: {byte_offset}")).into()),
+        }
+    }
+}
+
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    // Silent some clippy warnings for unit tests
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    /// "é" is 2 bytes, "𝄞" 4 bytes (2 UTF-16 code units), and "e\u{301}" is one grapheme of 2 chars (3 bytes)
+    const TEXT: &str = "Émile 𝄞 Zola, re\u{301}sume\u{301}";
+
+    fn span(start: usize, end: usize) -> Span {
+        Span::new(0, start, end, TEXT[start..end].to_string(), "test".to_string(), 0.9)
+    }
+
+    #[test]
+    fn test_units() -> Result<()> {
+        let zola = span(12, 16);
+        assert_eq!(&TEXT[12..16], "Zola");
+        // Processing
+        let offsets = |unit| OffsetMap::new(TEXT, unit).span(&zola).map(|o| (o.start, o.end));
+        // Assertions
+        assert_eq!(offsets(OffsetUnit::Byte)?, (12, 16));
+        assert_eq!(offsets(OffsetUnit::Char)?, (8, 12));
+        assert_eq!(offsets(OffsetUnit::Utf16)?, (9, 13));
+        assert_eq!(offsets(OffsetUnit::Grapheme)?, (8, 12));
+        // end of text
+        assert_eq!(OffsetMap::new(TEXT, OffsetUnit::Char).start(TEXT.len())?, TEXT.chars().count());
+        assert_eq!(OffsetMap::new(TEXT, OffsetUnit::Utf16).end(TEXT.len())?, TEXT.encode_utf16().count());
+        assert!(OffsetMap::new(TEXT, OffsetUnit::Char).start(TEXT.len() + 1).is_err());
+        // Everything rules
+        Ok(())
+    }
+
+    #[test]
+    fn test_graphemes() -> Result<()> {
+        let map = OffsetMap::new(TEXT, OffsetUnit::Grapheme);
+        let start = TEXT.find("re").unwrap();
+        // "re\u{301}sume\u{301}" is 6 graphemes
+        assert_eq!(map.span(&span(start, TEXT.len()))?, UnitOffsets { unit: OffsetUnit::Grapheme, start: 14, end: 20 });
+        // a span ending within a grapheme includes it, a span starting within a grapheme starts with it
+        assert_eq!(map.end(start + 2)?, 16);
+        assert_eq!(map.start(start + 2)?, 15);
+        Ok(())
+    }
+}
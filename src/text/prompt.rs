@@ -25,4 +25,33 @@ impl Prompt {
     pub fn entities_len(&self) -> usize {
         self.entities_length
     }
+}
+
+
+/// Special markers used to build the entities part of the prompts:
+/// 
+/// ```text
+/// [<ent>, type1, <ent>, type2, ..., <ent>, typeK, <sep>, ...]
+/// ```
+#[derive(Debug, Clone)]
+pub struct PromptFormat {
+    /// Marker preceding each entity class (default: `<<ENT>>`)
+    pub ent_token: String,
+    /// Marker separating the entity classes from the text (default: `<<SEP>>`)
+    pub sep_token: String,
+}
+
+impl PromptFormat {
+    pub fn new(ent_token: &str, sep_token: &str) -> Self {
+        Self {
+            ent_token: ent_token.to_string(),
+            sep_token: sep_token.to_string(),
+        }
+    }
+}
+
+impl Default for PromptFormat {
+    fn default() -> Self {
+        Self::new("<<ENT>>", "<<SEP>>")
+    }
 }
\ No newline at end of file
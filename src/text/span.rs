@@ -1,4 +1,18 @@
+use super::offsets::UnitOffsets;
+
+/// A span of text recognized as an entity of some class.
+///
+/// With the `serde` feature enabled, it is serialized as:
+/// ```json
+/// { "sequence": 0, "start": 11, "end": 21, "text": "James Bond", "class": "person", "probability": 0.99 }
+/// ```
+/// Offsets are byte offsets in the input text. Offsets in another unit can be added (see `Parameters::offset_unit`),
+/// in which case they are serialized as well:
+/// ```json
+/// { ..., "unit_offsets": { "unit": "utf16", "start": 10, "end": 20 } }
+/// ```
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Span {
     /// Input index in the batch
     sequence: usize, 
@@ -12,12 +26,27 @@ pub struct Span {
     class: String,
     /// Probability
     probability: f32,
+    /// Offsets in another unit, if requested
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    unit_offsets: Option<UnitOffsets>,
 }
 
 impl Span {
     pub fn new(sequence: usize, start: usize, end: usize, text: String, class: String, probability: f32) -> Self {
         assert!(end > start);
-        Self { sequence, start, end, text, class, probability }
+        Self { sequence, start, end, text, class, probability, unit_offsets: None }
+    }
+
+    /// Adds offsets in another unit (see `OffsetMap`)
+    pub fn with_unit_offsets(mut self, unit_offsets: UnitOffsets) -> Self {
+        self.unit_offsets = Some(unit_offsets);
+        self
+    }
+
+    /// Same span, within another sequence (e.g. once the outputs of several batches are reassembled)
+    pub fn with_sequence(mut self, sequence: usize) -> Self {
+        self.sequence = sequence;
+        self
     }
 
     pub fn sequence(&self) -> usize {
@@ -28,6 +57,11 @@ impl Span {
         (self.start, self.end)
     }
 
+    /// Offsets in the unit requested by `Parameters::offset_unit`, unless it is the byte
+    pub fn unit_offsets(&self) -> Option<UnitOffsets> {
+        self.unit_offsets
+    }
+
     pub fn text(&self) -> &str {
         &self.text
     }
@@ -51,6 +51,14 @@ impl Splitter for RegexSplitter {
 }
 
 
+/// Allows pipelines to borrow a splitter (see `TokenPipeline::by_ref()`)
+impl<S: Splitter + ?Sized> Splitter for &S {
+    fn split(&self, input: &str, limit: Option<usize>) -> Result<Vec<Token>> {
+        (**self).split(input, limit)
+    }
+}
+
+
 
 #[cfg(test)]
 mod tests {
@@ -1,36 +1,205 @@
 use std::path::Path;
-use crate::util::result::Result;
+use crate::util::{error::GlinerError, result::Result};
 
 
 /// Sub-word tokenization (aka encoding)
 pub trait Tokenizer {
     fn encode(&self, input: &str) -> Result<Vec<u32>>;
+
+    /// Encodes each input independently, the result being the same as calling `encode()` on each of them.
+    /// The default implementation does exactly that, but implementations may provide a more efficient one.
+    fn encode_batch(&self, inputs: &[&str]) -> Result<Vec<Vec<u32>>> {
+        inputs.iter().map(|input| self.encode(input)).collect()
+    }
+
+    /// Id of the special token starting each sequence (aka CLS or BOS). Defaults to the DeBERTa-v3 one.
+    fn start_token_id(&self) -> u32 {
+        1
+    }
+
+    /// Id of the special token ending each sequence (aka SEP or EOS). Defaults to the DeBERTa-v3 one.
+    fn end_token_id(&self) -> u32 {
+        2
+    }
 }
 
 
 /// Implement `Tokenizer` as a wrapper around Hugging Face tokenizers
 pub struct HFTokenizer {
     inner: tokenizers::Tokenizer,
+    start_token_id: u32,
+    end_token_id: u32,
 }
 
 
 impl HFTokenizer {
+    /// Number of inputs per pre-tokenized sequence in `encode_batch()` (the sequences being encoded in parallel)
+    const WORDS_PER_SEQUENCE: usize = 64;
+
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
-        Ok(Self {
-            inner: tokenizers::Tokenizer::from_file(path)?
-        })
+        Self::from_inner(tokenizers::Tokenizer::from_file(path).map_err(tokenizer_error)?)
     }
 
     pub fn from_pretrained(identifier: &str) -> Result<Self> {
-        Ok(Self {
-            inner: tokenizers::Tokenizer::from_pretrained(identifier, None)?
-        })
+        Self::from_inner(tokenizers::Tokenizer::from_pretrained(identifier, None).map_err(tokenizer_error)?)
+    }
+
+    /// Overrides the special token ids (which are otherwise resolved from the tokenizer)
+    pub fn with_special_token_ids(mut self, start_token_id: u32, end_token_id: u32) -> Self {
+        self.start_token_id = start_token_id;
+        self.end_token_id = end_token_id;
+        self
+    }
+
+    fn from_inner(inner: tokenizers::Tokenizer) -> Result<Self> {
+        let (start_token_id, end_token_id) = Self::resolve_special_token_ids(&inner)?;
+        Ok(Self { inner, start_token_id, end_token_id })
+    }
+
+    /// Resolves the start and end token ids from the post-processor of the tokenizer (as the special
+    /// tokens it adds around an empty sequence), or from the usual token names if there is none.
+    /// Tokens which cannot be resolved default to the DeBERTa-v3 ids (see `Tokenizer`).
+    fn resolve_special_token_ids(inner: &tokenizers::Tokenizer) -> Result<(u32, u32)> {
+        let encoding = inner.encode("", true).map_err(tokenizer_error)?;
+        if let [start, end] = encoding.get_ids() {
+            return Ok((*start, *end));
+        }
+        let find = |names: &[&str], default: u32| names.iter().find_map(|name| inner.token_to_id(name)).unwrap_or(default);
+        Ok((find(&["[CLS]", "<s>", "<cls>"], 1), find(&["[SEP]", "</s>", "<sep>"], 2)))
     }
 }
 
 impl Tokenizer for HFTokenizer {
     fn encode(&self, input: &str) -> Result<Vec<u32>> {
-        let encoding = self.inner.encode(input, false)?;
+        let encoding = self.inner.encode(input, false).map_err(tokenizer_error)?;
         Ok(encoding.get_ids().to_vec())
     }
-}
\ No newline at end of file
+
+    /// Relies on the (parallel) batch encoding of Hugging Face tokenizers: the inputs are grouped into
+    /// pre-tokenized sequences (each input being normalized and pre-tokenized on its own, exactly as
+    /// with `encode()`), whose tokens are then assigned back to the inputs using their word ids.
+    /// Since truncation and padding would apply to the whole sequences, each input is encoded as a
+    /// sequence of its own when the tokenizer defines any of them.
+    fn encode_batch(&self, inputs: &[&str]) -> Result<Vec<Vec<u32>>> {
+        if self.inner.get_truncation().is_some() || self.inner.get_padding().is_some() {
+            let encodings = self.inner.encode_batch(inputs.to_vec(), false).map_err(tokenizer_error)?;
+            return Ok(encodings.iter().map(|encoding| encoding.get_ids().to_vec()).collect());
+        }
+        let chunks = inputs.chunks(Self::WORDS_PER_SEQUENCE).collect::<Vec<_>>();
+        let encodings = self.inner.encode_batch(chunks.clone(), false).map_err(tokenizer_error)?;
+        let mut result = Vec::with_capacity(inputs.len());
+        for (chunk, encoding) in chunks.iter().zip(&encodings) {
+            let mut words = vec![Vec::new(); chunk.len()];
+            for (id, word_id) in encoding.get_ids().iter().zip(encoding.get_word_ids()) {
+                if let Some(word_id) = word_id {
+                    let word = words.get_mut(*word_id as usize).ok_or_else(|| GlinerError::Tokenizer(format!("unexpected word id: {word_id}")))?;
+                    word.push(*id);
+                }
+            }
+            result.extend(words);
+        }
+        Ok(result)
+    }
+
+    fn start_token_id(&self) -> u32 {
+        self.start_token_id
+    }
+
+    fn end_token_id(&self) -> u32 {
+        self.end_token_id
+    }
+}
+
+
+/// Allows pipelines to borrow a tokenizer (see `TokenPipeline::by_ref()`)
+impl<T: Tokenizer + ?Sized> Tokenizer for &T {
+    fn encode(&self, input: &str) -> Result<Vec<u32>> {
+        (**self).encode(input)
+    }
+
+    fn encode_batch(&self, inputs: &[&str]) -> Result<Vec<Vec<u32>>> {
+        (**self).encode_batch(inputs)
+    }
+
+    fn start_token_id(&self) -> u32 {
+        (**self).start_token_id()
+    }
+
+    fn end_token_id(&self) -> u32 {
+        (**self).end_token_id()
+    }
+}
+
+
+fn tokenizer_error(e: tokenizers::Error) -> GlinerError {
+    GlinerError::Tokenizer(e.to_string())
+}
+
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Word-level tokenizer with the given vocabulary and post-processor (as JSON)
+    fn word_level(vocab: &str, post_processor: &str) -> Result<HFTokenizer> {
+        let json = format!(r#"{{
+            "version": "1.0", "truncation": null, "padding": null, "added_tokens": [], "normalizer": null,
+            "pre_tokenizer": {{ "type": "Whitespace" }}, "post_processor": {post_processor}, "decoder": null,
+            "model": {{ "type": "WordLevel", "vocab": {vocab}, "unk_token": "[UNK]" }}
+        }}"#);
+        HFTokenizer::from_inner(tokenizers::Tokenizer::from_bytes(json).map_err(tokenizer_error)?)
+    }
+
+    #[test]
+    fn test_post_processor() -> Result<()> {
+        let tokenizer = HFTokenizer::from_file("data/tokenizer-tiny.json")?;
+        assert_eq!((tokenizer.start_token_id(), tokenizer.end_token_id()), (1, 2));
+        let post_processor = r#"{
+            "type": "TemplateProcessing",
+            "single": [{ "SpecialToken": { "id": "<s>", "type_id": 0 } }, { "Sequence": { "id": "A", "type_id": 0 } }, { "SpecialToken": { "id": "</s>", "type_id": 0 } }],
+            "pair": [{ "Sequence": { "id": "A", "type_id": 0 } }, { "Sequence": { "id": "B", "type_id": 1 } }],
+            "special_tokens": {
+                "<s>": { "id": "<s>", "ids": [7], "tokens": ["<s>"] },
+                "</s>": { "id": "</s>", "ids": [8], "tokens": ["</s>"] }
+            }
+        }"#;
+        let tokenizer = word_level(r#"{ "[UNK]": 0, "<s>": 7, "</s>": 8 }"#, post_processor)?;
+        assert_eq!((tokenizer.start_token_id(), tokenizer.end_token_id()), (7, 8));
+        Ok(())
+    }
+
+    #[test]
+    fn test_token_names() -> Result<()> {
+        let tokenizer = word_level(r#"{ "[UNK]": 0, "<s>": 5, "</s>": 6 }"#, "null")?;
+        assert_eq!((tokenizer.start_token_id(), tokenizer.end_token_id()), (5, 6));
+        let tokenizer = tokenizer.with_special_token_ids(3, 4);
+        assert_eq!((tokenizer.start_token_id(), tokenizer.end_token_id()), (3, 4));
+        Ok(())
+    }
+
+    #[test]
+    fn test_fallback() -> Result<()> {
+        let tokenizer = word_level(r#"{ "[UNK]": 0, "hello": 1, "<cls>": 9 }"#, "null")?;
+        assert_eq!((tokenizer.start_token_id(), tokenizer.end_token_id()), (9, 2));
+        let tokenizer = word_level(r#"{ "[UNK]": 0, "hello": 1 }"#, "null")?;
+        assert_eq!((tokenizer.start_token_id(), tokenizer.end_token_id()), (1, 2));
+        Ok(())
+    }
+    #[test]
+    fn test_encode_batch() -> Result<()> {
+        let tokenizer = HFTokenizer::from_file("data/tokenizer-tiny.json")?;
+        // Several pre-tokenized sequences, with multi-token, unknown and empty words
+        let words = ["James", "Auric", "Goldfinger", "villain", "", "1a", "Bond", "London"];
+        let inputs = words.iter().cycle().take(3 * HFTokenizer::WORDS_PER_SEQUENCE + 5).copied().collect::<Vec<_>>();
+        // Processing
+        let batch = tokenizer.encode_batch(&inputs)?;
+        let single = inputs.iter().map(|input| tokenizer.encode(input)).collect::<Result<Vec<_>>>()?;
+        // Assertions
+        assert_eq!(batch, single);
+        assert_eq!(batch.get(1), Some(&vec![29, 30]));
+        assert_eq!(batch.get(4), Some(&vec![]));
+        // Everything rules
+        Ok(())
+    }
+}
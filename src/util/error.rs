@@ -34,4 +34,177 @@ impl Display for IndexError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str(&self.message)
     }
-}
\ No newline at end of file
+}
+
+
+/// Typed errors raised by `gline-rs`, allowing to match on the cause of a failure
+/// (for example, to reject a request on invalid input, but retry on a runtime failure).
+///
+/// The pre- and post-processing steps (see `composable` and `orp`) are bound to boxed
+/// errors, so this type is boxed along the pipelines. The entry points (`GLiNER::inference()`
+/// and the like) unbox it, and any other error is converted as well (see `From<Box<dyn Error>>`).
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum GlinerError {
+    /// Invalid input data (for example, empty texts or entities), at the given sequence if relevant
+    InvalidInput { sequence: Option<usize>, message: String },
+    /// Invalid parameters or model configuration
+    InvalidConfiguration(String),
+    /// Unexpected label (relation extraction)
+    InvalidLabel { label: String, message: String },
+    /// Failure of the tokenizer
+    Tokenizer(String),
+    /// Unexpected shape of a tensor
+    ShapeMismatch { tensor: String, expected: Vec<i64>, actual: Vec<i64> },
+    /// Inputs or outputs of the model not matching the expectations of the pipeline
+    Schema(String),
+    /// Failure of the ONNX runtime while running an inference
+    Runtime(ort::Error),
+    /// Failure of the ONNX runtime while loading a model (for example, an invalid model file or an
+    /// unavailable execution provider), which retrying does not fix unlike `Runtime`
+    Load(ort::Error),
+    /// Failure while running an inference outside of the ONNX runtime (for example, another backend,
+    /// a failed inference task, or a lock poisoned by a panicking thread)
+    Execution(String),
+    /// Inconsistent index, which denotes a bug (see `IndexError`)
+    Index(IndexError),
+    /// I/O failure (for example, when loading a model)
+    Io(std::io::Error),
+    /// Inference cancelled before it started
+    Cancelled,
+    /// Inference rejected because the queue is full
+    QueueFull,
+    /// Any other error
+    Other(Box<dyn error::Error + Send + Sync>),
+}
+
+impl GlinerError {
+    pub fn invalid_input(message: &str) -> Self {
+        Self::InvalidInput { sequence: None, message: message.to_string() }
+    }
+
+    /// Attributes an invalid input error to the given sequence (if not already attributed), other errors being kept as is
+    pub fn at_sequence(self, sequence: usize) -> Self {
+        match self {
+            Self::InvalidInput { sequence: None, message } => Self::InvalidInput { sequence: Some(sequence), message },
+            e => e,
+        }
+    }
+
+    /// Attributes a runtime failure to the loading of a model (see `Load`), other errors being kept as is
+    pub fn at_load(self) -> Self {
+        match self {
+            Self::Runtime(e) => Self::Load(e),
+            e => e,
+        }
+    }
+
+    /// Whether retrying the same request later may succeed (runtime failures of an inference, I/O and
+    /// queue failures), as opposed to requests which are invalid as such and to models which failed to load
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::Runtime(_) | Self::Io(_) | Self::QueueFull)
+    }
+}
+
+impl error::Error for GlinerError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Runtime(e) | Self::Load(e) => Some(e),
+            Self::Index(e) => Some(e),
+            Self::Io(e) => Some(e),
+            Self::Other(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl Display for GlinerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidInput { sequence: Some(sequence), message } => write!(f, "invalid input (sequence {sequence}): {message}"),
+            Self::InvalidInput { sequence: None, message } => write!(f, "invalid input: {message}"),
+            Self::InvalidConfiguration(message) => write!(f, "invalid configuration: {message}"),
+            Self::InvalidLabel { label, message } => write!(f, "{message}: {label}"),
+            Self::Tokenizer(message) => write!(f, "tokenizer failure: {message}"),
+            Self::ShapeMismatch { tensor, expected, actual } => write!(f, "unexpected {tensor} shape: expected {expected:?}, found {actual:?}"),
+            Self::Schema(message) => write!(f, "unexpected model schema: {message}"),
+            Self::Runtime(e) => write!(f, "runtime failure: {e}"),
+            Self::Load(e) => write!(f, "model loading failure: {e}"),
+            Self::Execution(message) => write!(f, "execution failure: {message}"),
+            Self::Index(e) => e.fmt(f),
+            Self::Io(e) => e.fmt(f),
+            Self::Cancelled => f.write_str("inference cancelled"),
+            Self::QueueFull => f.write_str("inference queue is full"),
+            Self::Other(e) => e.fmt(f),
+        }
+    }
+}
+
+impl From<IndexError> for GlinerError {
+    fn from(e: IndexError) -> Self {
+        Self::Index(e)
+    }
+}
+
+impl From<ort::Error> for GlinerError {
+    fn from(e: ort::Error) -> Self {
+        Self::Runtime(e)
+    }
+}
+
+impl From<std::io::Error> for GlinerError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// Recovers the typed error out of a boxed one (as returned by the pipelines)
+impl From<Box<dyn error::Error + Send + Sync>> for GlinerError {
+    fn from(e: Box<dyn error::Error + Send + Sync>) -> Self {
+        let e = match e.downcast::<GlinerError>() { Ok(e) => return *e, Err(e) => e };
+        let e = match e.downcast::<IndexError>() { Ok(e) => return Self::Index(*e), Err(e) => e };
+        let e = match e.downcast::<ort::Error>() { Ok(e) => return Self::Runtime(*e), Err(e) => e };
+        let e = match e.downcast::<std::io::Error>() { Ok(e) => return Self::Io(*e), Err(e) => e };
+        let e = match e.downcast::<crate::model::output::relation::RelationFormatError>() { 
+            Ok(e) => return Self::InvalidLabel { label: e.label().to_string(), message: "unexpected relation label format".to_string() }, 
+            Err(e) => e 
+        };
+        match e.downcast::<orp::error::UnexpectedModelSchemaError>() {
+            Ok(e) => Self::Schema(e.to_string()),
+            Err(e) => Self::Other(e),
+        }
+    }
+}
+
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::result::Result;
+
+    #[test]
+    fn test_from_boxed() -> Result<()> {
+        // Processing
+        let boxed: Box<dyn error::Error + Send + Sync> = GlinerError::Schema("missing input".to_string()).into();
+        let schema = GlinerError::from(boxed);
+        let index = GlinerError::from(Box::new(IndexError::new("tokens", 3)) as Box<dyn error::Error + Send + Sync>);
+        let label = GlinerError::from(crate::model::output::relation::RelationFormatError::new("founded").err::<()>().err().ok_or("expected an error")?);
+        let other = GlinerError::from(Box::<dyn error::Error + Send + Sync>::from("something else"));
+        let input = GlinerError::from(crate::model::input::text::TextInput::from_str(&[], &["person"]).err().ok_or("expected an error")?);
+        // Assertions
+        assert!(matches!(schema, GlinerError::Schema(_)));
+        assert!(matches!(index, GlinerError::Index(_)));
+        assert!(matches!(label, GlinerError::InvalidLabel { label, .. } if label == "founded"));
+        assert!(matches!(other, GlinerError::Other(_)));
+        assert!(matches!(input, GlinerError::InvalidInput { sequence: None, .. }));
+        assert!(!input.is_retryable());
+        assert!(GlinerError::QueueFull.is_retryable());
+        assert_eq!(input.to_string(), "invalid input: empty texts and/or entities");
+        assert_eq!(input.at_sequence(2).at_sequence(3).to_string(), "invalid input (sequence 2): empty texts and/or entities");
+        assert!(matches!(GlinerError::QueueFull.at_sequence(1), GlinerError::QueueFull));
+        assert!(matches!(GlinerError::QueueFull.at_load(), GlinerError::QueueFull));
+        // Everything rules
+        Ok(())
+    }
+}
@@ -3,3 +3,4 @@
 pub mod math;
 pub mod result;
 pub mod error;
+pub mod profile;
@@ -0,0 +1,310 @@
+//! Per-stage instrumentation of the pipelines
+//!
+//! Each stage of the pre-defined pipelines (as well as the run of the model itself) is wrapped in a
+//! `Traced` composable. With the `tracing` feature enabled, every application of a stage emits a
+//! `tracing` span named `stage`, with the name of the stage, the batch size, the number of words and of
+//! tokens (when known from its output) and the elapsed time, and its duration is added to a global
+//! per-stage profile:
+//!
+//! ```ignore
+//! profile::reset();
+//! model.inference(input)?;
+//! println!("{}", profile::report());
+//! ```
+//!
+//! Without the feature, `Traced` simply delegates to the wrapped stage, and the profile stays empty.
+
+use std::fmt::{Display, Formatter};
+use std::sync::Mutex;
+use std::time::Duration;
+use composable::Composable;
+use crate::util::result::Result;
+use crate::model::backend::Tensors;
+use crate::model::input::{text::TextInput, tokenized::TokenizedInput, prompt::PromptInput, encoded::EncodedInput, relation::RelationInput};
+use crate::model::input::tensors::{span::SpanTensors, token::TokenTensors};
+use crate::model::output::{tensors::TensorOutput, decoded::SpanOutput, relation::RelationOutput, scores::ScoreOutput};
+
+
+/// Dimensions of the data produced by a stage, as reported by its span (`None` when not relevant)
+pub trait Measured {
+    fn batch_size(&self) -> Option<usize> {
+        None
+    }
+
+    /// Number of words per sequence (after padding)
+    fn num_words(&self) -> Option<usize> {
+        None
+    }
+
+    /// Number of sub-word tokens per sequence (after padding)
+    fn num_tokens(&self) -> Option<usize> {
+        None
+    }
+}
+
+
+/// Composable wrapper timing the given stage (see module documentation)
+pub struct Traced<C> {
+    name: &'static str,
+    stage: C,
+}
+
+impl<C> Traced<C> {
+    /// Wraps the given stage, named after its type and module (e.g. `span::TensorsToDecoded`, without
+    /// the rest of the path nor generics), so that homonymous stages remain distinct
+    pub fn new(stage: C) -> Self {
+        let name = std::any::type_name::<C>();
+        let name = name.split('<').next().unwrap_or(name);
+        let name = match name.rmatch_indices("::").nth(1) {
+            Some((i, _)) => name.get(i + 2..).unwrap_or(name),
+            None => name,
+        };
+        Self::with_name(name, stage)
+    }
+
+    pub fn with_name(name: &'static str, stage: C) -> Self {
+        Self { name, stage }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl<I, O: Measured, C: Composable<I, O>> Composable<I, O> for Traced<C> {
+    #[cfg(feature = "tracing")]
+    fn apply(&self, input: I) -> Result<O> {
+        use tracing::field::Empty;
+        let span = tracing::info_span!("stage", name = self.name, batch_size = Empty, num_words = Empty, num_tokens = Empty, elapsed_us = Empty);
+        let _entered = span.enter();
+        let start = std::time::Instant::now();
+        let output = self.stage.apply(input)?;
+        let elapsed = start.elapsed();
+        span.record("elapsed_us", elapsed.as_micros() as u64);
+        if let Some(batch_size) = output.batch_size() {
+            span.record("batch_size", batch_size);
+        }
+        if let Some(num_words) = output.num_words() {
+            span.record("num_words", num_words);
+        }
+        if let Some(num_tokens) = output.num_tokens() {
+            span.record("num_tokens", num_tokens);
+        }
+        record(self.name, elapsed, output.batch_size().unwrap_or_default());
+        Ok(output)
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    fn apply(&self, input: I) -> Result<O> {
+        self.stage.apply(input)
+    }
+}
+
+
+/// Aggregated timings of one stage
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StageProfile {
+    pub name: &'static str,
+    /// Number of applications
+    pub calls: usize,
+    /// Number of sequences processed (sum of the batch sizes)
+    pub sequences: usize,
+    pub total: Duration,
+    pub min: Duration,
+    pub max: Duration,
+}
+
+impl StageProfile {
+    pub fn mean(&self) -> Duration {
+        self.total.checked_div(self.calls as u32).unwrap_or_default()
+    }
+}
+
+
+/// Aggregated timings of all the stages, in order of first application
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    pub stages: Vec<StageProfile>,
+}
+
+impl Profile {
+    /// Total time spent in the given stage, if it was applied at all
+    pub fn total(&self, name: &str) -> Option<Duration> {
+        self.stages.iter().find(|s| s.name == name).map(|s| s.total)
+    }
+}
+
+impl Display for Profile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let overall: Duration = self.stages.iter().map(|s| s.total).sum();
+        writeln!(f, "{:<28} {:>8} {:>10} {:>12} {:>12} {:>12} {:>7}", "stage", "calls", "sequences", "total (ms)", "mean (ms)", "max (ms)", "share")?;
+        for stage in &self.stages {
+            let share = if overall.is_zero() { 0.0 } else { 100.0 * stage.total.as_secs_f64() / overall.as_secs_f64() };
+            writeln!(f, "{:<28} {:>8} {:>10} {:>12.3} {:>12.3} {:>12.3} {:>6.1}%",
+                stage.name, stage.calls, stage.sequences, millis(stage.total), millis(stage.mean()), millis(stage.max), share)?;
+        }
+        Ok(())
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+
+static PROFILE: Mutex<Vec<StageProfile>> = Mutex::new(Vec::new());
+
+/// Adds the given timing to the global profile
+pub fn record(name: &'static str, elapsed: Duration, sequences: usize) {
+    let mut stages = PROFILE.lock().unwrap_or_else(|e| e.into_inner());
+    let index = match stages.iter().position(|s| s.name == name) {
+        Some(index) => index,
+        None => {
+            stages.push(StageProfile { name, min: Duration::MAX, ..Default::default() });
+            stages.len() - 1
+        }
+    };
+    if let Some(stage) = stages.get_mut(index) {
+        stage.calls += 1;
+        stage.sequences += sequences;
+        stage.total += elapsed;
+        stage.min = stage.min.min(elapsed);
+        stage.max = stage.max.max(elapsed);
+    }
+}
+
+/// Snapshot of the global profile (empty unless the `tracing` feature is enabled)
+pub fn report() -> Profile {
+    Profile { stages: PROFILE.lock().unwrap_or_else(|e| e.into_inner()).clone() }
+}
+
+/// Clears the global profile
+pub fn reset() {
+    PROFILE.lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+
+impl<T: Measured, C> Measured for (T, C) {
+    fn batch_size(&self) -> Option<usize> { self.0.batch_size() }
+    fn num_words(&self) -> Option<usize> { self.0.num_words() }
+    fn num_tokens(&self) -> Option<usize> { self.0.num_tokens() }
+}
+
+impl Measured for Tensors {
+    fn batch_size(&self) -> Option<usize> {
+        self.names().next().and_then(|name| self.get(name)).and_then(|t| t.shape().first().map(|d| *d as usize))
+    }
+
+    fn num_tokens(&self) -> Option<usize> {
+        self.get("input_ids").and_then(|t| t.shape().get(1).map(|d| *d as usize))
+    }
+}
+
+impl Measured for TextInput {
+    fn batch_size(&self) -> Option<usize> { Some(self.texts.len()) }
+}
+
+impl Measured for TokenizedInput {
+    fn batch_size(&self) -> Option<usize> { Some(self.texts.len()) }
+    fn num_words(&self) -> Option<usize> { self.tokens.iter().map(Vec::len).max() }
+}
+
+impl Measured for PromptInput {
+    fn batch_size(&self) -> Option<usize> { Some(self.texts.len()) }
+    fn num_words(&self) -> Option<usize> { Some(self.num_words) }
+}
+
+impl Measured for EncodedInput {
+    fn batch_size(&self) -> Option<usize> { Some(self.texts.len()) }
+    fn num_words(&self) -> Option<usize> { Some(self.num_words) }
+    fn num_tokens(&self) -> Option<usize> { Some(self.num_tokens) }
+}
+
+impl Measured for SpanTensors {
+    fn batch_size(&self) -> Option<usize> { Some(self.context.texts.len()) }
+    fn num_words(&self) -> Option<usize> { Some(self.context.num_words) }
+    fn num_tokens(&self) -> Option<usize> { self.tensors.num_tokens() }
+}
+
+impl Measured for TokenTensors {
+    fn batch_size(&self) -> Option<usize> { Some(self.context.texts.len()) }
+    fn num_words(&self) -> Option<usize> { Some(self.context.num_words) }
+    fn num_tokens(&self) -> Option<usize> { self.tensors.num_tokens() }
+}
+
+impl Measured for TensorOutput {
+    fn batch_size(&self) -> Option<usize> { Some(self.context.texts.len()) }
+    fn num_words(&self) -> Option<usize> { Some(self.context.num_words) }
+}
+
+impl Measured for SpanOutput {
+    fn batch_size(&self) -> Option<usize> { Some(self.texts.len()) }
+}
+
+impl Measured for ScoreOutput {
+    fn batch_size(&self) -> Option<usize> { Some(self.texts.len()) }
+}
+
+impl Measured for RelationInput {
+    fn batch_size(&self) -> Option<usize> { Some(self.prompts.len()) }
+}
+
+impl Measured for RelationOutput {
+    fn batch_size(&self) -> Option<usize> { Some(self.texts.len()) }
+}
+
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    // Silent some clippy warnings for unit tests
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn test_name() {
+        let splitter = crate::text::splitter::RegexSplitter::default();
+        let stage = Traced::new(crate::model::input::tokenized::RawToTokenized::new(&splitter, None));
+        assert_eq!(stage.name(), "tokenized::RawToTokenized");
+        // homonymous stages are told apart by their module
+        let span = Traced::new(crate::model::output::decoded::span::TensorsToDecoded::new(0.5, 12));
+        let token = Traced::new(crate::model::output::decoded::token::TensorsToDecoded::new(0.5));
+        assert_eq!((span.name(), token.name()), ("span::TensorsToDecoded", "token::TensorsToDecoded"));
+    }
+
+    #[test]
+    fn test_profile() -> Result<()> {
+        let stage = Traced::with_name("test_profile", |input: TextInput| Ok(input));
+        // Processing
+        stage.apply(TextInput::from_str(&["a", "b"], &["c"])?)?;
+        stage.apply(TextInput::from_str(&["a"], &["c"])?)?;
+        // Assertions
+        let profile = report();
+        let stage = profile.stages.iter().find(|s| s.name == "test_profile");
+        if cfg!(feature = "tracing") {
+            let stage = stage.unwrap();
+            assert_eq!((stage.calls, stage.sequences), (2, 3));
+            assert!(stage.min <= stage.max && stage.max <= stage.total);
+            assert!(profile.to_string().contains("test_profile"));
+        }
+        else {
+            assert!(stage.is_none());
+        }
+        // Everything rules
+        Ok(())
+    }
+
+    #[test]
+    fn test_pipeline() -> Result<()> {
+        let model = crate::model::backend::mock::token_gliner(1, &[])?;
+        // Processing
+        model.inference(TextInput::from_str(&["My name is James Bond"], &["person"])?)?;
+        // Assertions: every stage has been timed, the model included
+        let profile = report();
+        for stage in ["tokenized::RawToTokenized", "prompt::TokenizedToPrompt", "encoded::PromptsToEncoded", "token::EncodedToTensors", "Backend::run", "token::TensorsToDecoded", "search::SpanSearch"] {
+            assert_eq!(profile.total(stage).is_some(), cfg!(feature = "tracing"));
+        }
+        // Everything rules
+        Ok(())
+    }
+}
@@ -4,4 +4,6 @@ pub type Result<T> = core::result::Result<T, Box<dyn Error + Send + Sync>>;
 
 pub trait TryDefault {
     fn default() -> Result<Self> where Self: Sized;
-}
\ No newline at end of file
+}
+/// Result type of the entry points of the library (see `GlinerError`)
+pub type GlinerResult<T> = core::result::Result<T, super::error::GlinerError>;